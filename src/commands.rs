@@ -0,0 +1,285 @@
+//! BaroBoard 自身的命令
+//!
+//! 命令面板、标题菜单与托盘菜单都从这里读取命令，新的命令只需要在这里注册
+
+use log::{debug, info};
+
+use crate::my_structs::MyApp;
+use crate::pages::popups::link::profile;
+use crate::window::event::UserEvent;
+
+
+/// 可以执行的应用命令
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppCommand {
+    ShowWindow,
+    ToggleEditMode,
+    NewLink,
+    NewTag,
+    NextProfile,
+    Profiles,
+    Undo,
+    Redo,
+    Settings,
+    SearchSettings,
+    ReloadConfig,
+    RestoreBackup,
+    ShowHiddenLinks,
+    ShowTrash,
+    ExportLinks,
+    ImportLinks,
+    ImportBookmarks,
+    OpenConfigFolder,
+    ToggleDoubleAlt,
+    HideWindow,
+    Quit,
+}
+
+impl AppCommand {
+    /// 命令面板与标题菜单中的命令，按显示顺序排列
+    pub const ALL: [AppCommand; 20] = [
+        AppCommand::ToggleEditMode,
+        AppCommand::NewLink,
+        AppCommand::NewTag,
+        AppCommand::NextProfile,
+        AppCommand::Profiles,
+        AppCommand::Undo,
+        AppCommand::Redo,
+        AppCommand::Settings,
+        AppCommand::SearchSettings,
+        AppCommand::ReloadConfig,
+        AppCommand::RestoreBackup,
+        AppCommand::ShowHiddenLinks,
+        AppCommand::ShowTrash,
+        AppCommand::ExportLinks,
+        AppCommand::ImportLinks,
+        AppCommand::ImportBookmarks,
+        AppCommand::OpenConfigFolder,
+        AppCommand::ToggleDoubleAlt,
+        AppCommand::HideWindow,
+        AppCommand::Quit,
+    ];
+
+    /// 托盘菜单中的命令
+    pub const TRAY: [AppCommand; 3] = [
+        AppCommand::ShowWindow,
+        AppCommand::ToggleDoubleAlt,
+        AppCommand::Quit,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AppCommand::ShowWindow => "显示工具箱",
+            AppCommand::ToggleEditMode => "编辑模式",
+            AppCommand::NewLink => "新建快捷方式",
+            AppCommand::NewTag => "新建标签",
+            AppCommand::NextProfile => "切换到下一个工作区",
+            AppCommand::Profiles => "管理工作区",
+            AppCommand::Undo => "撤销",
+            AppCommand::Redo => "重做",
+            AppCommand::Settings => "设置",
+            AppCommand::SearchSettings => "搜索设置",
+            AppCommand::ReloadConfig => "重新加载配置",
+            AppCommand::RestoreBackup => "恢复备份",
+            AppCommand::ShowHiddenLinks => "显示隐藏的共享快捷方式",
+            AppCommand::ShowTrash => "回收站",
+            AppCommand::ExportLinks => "导出快捷方式",
+            AppCommand::ImportLinks => "导入快捷方式",
+            AppCommand::ImportBookmarks => "导入浏览器书签",
+            AppCommand::OpenConfigFolder => "打开配置文件夹",
+            AppCommand::ToggleDoubleAlt => "双击呼出",
+            AppCommand::HideWindow => "隐藏",
+            AppCommand::Quit => "退出",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            AppCommand::ShowWindow => "显示工具箱窗口",
+            AppCommand::ToggleEditMode => "进入或退出编辑模式",
+            AppCommand::NewLink => "打开新建快捷方式的窗口",
+            AppCommand::NewTag => "打开新建标签的窗口",
+            AppCommand::NextProfile => "按顺序切换到下一个工作区 (Ctrl+Tab)，Ctrl+1 到 Ctrl+9 切换到对应的工作区",
+            AppCommand::Profiles => "新建、重命名或删除工作区，修改当前工作区的标题",
+            AppCommand::Undo => "撤销最近一次对快捷方式或标签的修改 (Ctrl+Z)",
+            AppCommand::Redo => "重做最近一次撤销的修改 (Ctrl+Shift+Z)",
+            AppCommand::Settings => "打开设置，包括呼出快捷键、窗口外观与启动选项",
+            AppCommand::SearchSettings => "打开搜索设置",
+            AppCommand::ReloadConfig => "从磁盘重新读取快捷方式配置",
+            AppCommand::RestoreBackup => "从自动备份中恢复快捷方式配置",
+            AppCommand::ShowHiddenLinks => "重新显示所有被隐藏的共享目录中的快捷方式",
+            AppCommand::ShowTrash => "查看、还原或彻底删除已删除的快捷方式",
+            AppCommand::ExportLinks => "将选中的快捷方式导出为 zip 导入包、.desktop 启动器或 shell 别名",
+            AppCommand::ImportLinks => "从 zip 文件中导入快捷方式与图标",
+            AppCommand::ImportBookmarks => "从浏览器导出的书签 HTML 文件中导入网址",
+            AppCommand::OpenConfigFolder => "在文件管理器中打开配置文件夹",
+            AppCommand::ToggleDoubleAlt => "切换是否允许双击 Alt 或设置中的按键呼出工具箱",
+            AppCommand::HideWindow => "隐藏工具箱窗口",
+            AppCommand::Quit => "退出 BaroBoard",
+        }
+    }
+
+    /// 额外的英文关键字，便于在命令面板中搜索
+    pub fn keywords(&self) -> &'static [&'static str] {
+        match self {
+            AppCommand::ShowWindow => &["show"],
+            AppCommand::ToggleEditMode => &["edit mode"],
+            AppCommand::NewLink => &["new link"],
+            AppCommand::NewTag => &["new tag"],
+            AppCommand::NextProfile => &["next profile", "workspace", "switch"],
+            AppCommand::Profiles => &["profiles", "workspaces", "title"],
+            AppCommand::Undo => &["undo"],
+            AppCommand::Redo => &["redo"],
+            AppCommand::Settings => &["settings", "preferences", "theme", "hotkey"],
+            AppCommand::SearchSettings => &["search settings"],
+            AppCommand::ReloadConfig => &["reload config"],
+            AppCommand::RestoreBackup => &["restore backup"],
+            AppCommand::ShowHiddenLinks => &["show hidden links", "catalog"],
+            AppCommand::ShowTrash => &["trash", "recycle bin", "restore"],
+            AppCommand::ExportLinks => &["export links", "bundle", "desktop", "alias", "powershell"],
+            AppCommand::ImportLinks => &["import links", "bundle"],
+            AppCommand::ImportBookmarks => &["import bookmarks", "browser"],
+            AppCommand::OpenConfigFolder => &["open config folder"],
+            AppCommand::ToggleDoubleAlt => &["double alt"],
+            AppCommand::HideWindow => &["hide"],
+            AppCommand::Quit => &["quit", "exit"],
+        }
+    }
+
+    pub fn glyph(&self) -> &'static str {
+        match self {
+            AppCommand::ShowWindow => "🗖",
+            AppCommand::ToggleEditMode => "✏",
+            AppCommand::NewLink => "➕",
+            AppCommand::NewTag => "🏷",
+            AppCommand::NextProfile => "⇄",
+            AppCommand::Profiles => "🗂",
+            AppCommand::Undo => "↩",
+            AppCommand::Redo => "↪",
+            AppCommand::Settings => "🔧",
+            AppCommand::SearchSettings => "⚙",
+            AppCommand::ReloadConfig => "🔄",
+            AppCommand::RestoreBackup => "🗄",
+            AppCommand::ShowHiddenLinks => "👁",
+            AppCommand::ShowTrash => "🗑",
+            AppCommand::ExportLinks => "📤",
+            AppCommand::ImportLinks => "📥",
+            AppCommand::ImportBookmarks => "🔖",
+            AppCommand::OpenConfigFolder => "🗁",
+            AppCommand::ToggleDoubleAlt => "⌨",
+            AppCommand::HideWindow => "🗕",
+            AppCommand::Quit => "⏻",
+        }
+    }
+
+    /// 由窗口事件循环处理的命令对应的事件
+    pub fn event(&self) -> Option<UserEvent> {
+        match self {
+            AppCommand::ShowWindow => Some(UserEvent::ShowWindow),
+            AppCommand::ToggleDoubleAlt => Some(UserEvent::ChangeDoubleAlt),
+            AppCommand::HideWindow => Some(UserEvent::HideWindow),
+            AppCommand::Quit => Some(UserEvent::Exit),
+            _ => None,
+        }
+    }
+
+    /// 从命令面板执行后是否隐藏窗口，需要继续操作界面的命令会保留窗口
+    pub fn hides_window(&self) -> bool {
+        matches!(self, AppCommand::OpenConfigFolder | AppCommand::HideWindow | AppCommand::Quit)
+    }
+}
+
+
+/// 托盘菜单，有多个工作区时在显示工具箱之后加入切换工作区的子菜单
+pub fn tray_menu(double_tap: bool) -> trayicon::MenuBuilder<UserEvent> {
+    let profiles = profile::list_profiles();
+    let current = profile::current();
+
+    // 托盘菜单与命令面板使用同一份命令
+    AppCommand::TRAY.iter().fold(trayicon::MenuBuilder::new(), |menu, command| {
+        let event = command.event().expect("托盘菜单中的命令必须由事件循环处理");
+        let menu = match command {
+            AppCommand::ToggleDoubleAlt => menu.checkable(command.label(), double_tap, event),
+            _ => menu.item(command.label(), event),
+        };
+
+        if *command != AppCommand::ShowWindow || profiles.len() < 2 {
+            return menu;
+        }
+        menu.submenu("切换工作区", profiles.iter().fold(trayicon::MenuBuilder::new(), |submenu, name| {
+            submenu.checkable(
+                profile::display_name(name.as_deref()),
+                *name == current,
+                UserEvent::SwitchProfile(name.clone()),
+            )
+        }))
+    })
+}
+
+
+impl MyApp {
+    /// 执行应用命令
+    pub fn run_app_command(&mut self, command: AppCommand) {
+        info!("执行命令: {}", command.label());
+
+        if let Some(event) = command.event() {
+            self.proxy.send_event(event).unwrap();
+            return;
+        }
+
+        // 打开弹窗的命令不能覆盖正在显示的弹窗，撤销与切换工作区也不能改动弹窗正在编辑的快捷方式
+        let opens_popup = matches!(
+            command,
+            AppCommand::NewLink
+            | AppCommand::NewTag
+            | AppCommand::NextProfile
+            | AppCommand::Profiles
+            | AppCommand::Undo
+            | AppCommand::Redo
+            | AppCommand::Settings
+            | AppCommand::SearchSettings
+            | AppCommand::RestoreBackup
+            | AppCommand::ShowTrash
+            | AppCommand::ExportLinks
+            | AppCommand::ImportLinks
+            | AppCommand::ImportBookmarks
+        );
+        if opens_popup && self.popups.called {
+            debug!("已有弹窗，忽略命令 {}", command.label());
+            return;
+        }
+
+        match command {
+            AppCommand::ToggleEditMode => self.edit_mode = !self.edit_mode,
+            AppCommand::NewLink => self.popups.config_new_link(&self.settings.launch),
+            AppCommand::NewTag => {
+                // 标签只能在编辑模式中管理
+                self.edit_mode = true;
+                self.popups.new_tag();
+            }
+            AppCommand::NextProfile => self.switch_profile(profile::next_profile(profile::current().as_deref())),
+            AppCommand::Profiles => self.open_profiles(),
+            AppCommand::Undo => self.undo_edit(),
+            AppCommand::Redo => self.redo_edit(),
+            AppCommand::Settings => self.popups.app_settings(self.settings.clone()),
+            AppCommand::SearchSettings => self.popups.search_settings(),
+            AppCommand::ReloadConfig => {
+                self.reload_conf();
+            }
+            AppCommand::RestoreBackup => self.popups.backups(),
+            AppCommand::ShowTrash => self.open_trash(),
+            AppCommand::ExportLinks => self.open_export_bundle(None),
+            AppCommand::ImportLinks => self.open_import_bundle(),
+            AppCommand::ImportBookmarks => self.open_import_bookmarks(),
+            AppCommand::ShowHiddenLinks => {
+                self.show_hidden_links();
+                self.save_conf();
+            }
+            AppCommand::OpenConfigFolder => crate::search::provider::open_path(crate::paths::config_dir()),
+            AppCommand::ShowWindow
+            | AppCommand::ToggleDoubleAlt
+            | AppCommand::HideWindow
+            | AppCommand::Quit => {}
+        }
+    }
+}
@@ -7,10 +7,14 @@ mod window;
 mod utils;
 mod texture_mgr;
 mod logging;
+mod search;
+mod settings;
+mod commands;
+mod paths;
 
 use std::sync::{Arc, Mutex};  // Arc = 原子引用计数(Atomically Reference Counted)，一种线程安全的智能指针，允许在多个线程间共享所有权
 use egui_winit::winit;
-use rdev::{listen, EventType, Key};
+use rdev::{listen, EventType};
 use std::time::{Duration, Instant};
 use trayicon;
 use single_instance::SingleInstance;
@@ -18,20 +22,27 @@ use log::{info, warn, debug, trace};
 
 use window::{event, glow_app};
 use my_structs::MyApp;
+use pages::popups::link::profile;
 use logging::init_logger;
 
 
 pub const WINDOW_SIZE: (f32, f32) = (800.0, 500.0);
 pub const PROGRAM_VERSION: &str = "v0.1.3";
-pub const CONFIG_FILE_VERSION: u32 = 5;
-pub const CONFIG_SAVE_PATH: &str = ".baro";
+pub const CONFIG_FILE_VERSION: u32 = 6;
+pub const SETTINGS_FILE_VERSION: u32 = 1;
 pub const CONFIG_FILE_NAME: &str = "links.json";
+pub const SETTINGS_FILE_NAME: &str = "settings.json";
+pub const HISTORY_FILE_NAME: &str = "history.json";
 pub const DOUBLE_ALT_COOLDOWN: u64 = 500;
 
 
 fn main() {
     init_logger();
     info!("BaroBoard 工具箱 {} 开始运行", PROGRAM_VERSION);
+    paths::init(std::env::args());
+    let mut settings = settings::load_settings_or_default();
+    // 托盘菜单与读取快捷方式之前需要先确定当前的工作区
+    settings.profile = profile::restore(settings.profile.take());
 
     let instance = SingleInstance::new("BaroBoard").unwrap();
     
@@ -61,9 +72,9 @@ fn main() {
     let called = Arc::new(Mutex::new(true));
     let called_clone = called.clone();
 
-    // 是否允许双击呼出
-    let all_by_double_alt = Arc::new(Mutex::new(true));
-    let all_by_double_alt_clone = all_by_double_alt.clone();
+    // 呼出快捷键的设置，设置窗口与托盘菜单修改后立即生效
+    let hotkey = Arc::new(Mutex::new(settings.hotkey.clone()));
+    let hotkey_clone = hotkey.clone();
 
     rt.spawn(async move {
         // loop {
@@ -75,31 +86,34 @@ fn main() {
             let mut cooldown_until = None::<Instant>;
 
             listen(move |event| {
+                let hotkey = hotkey_clone.lock().unwrap().clone();
+                let interval = Duration::from_millis(hotkey.interval);
+
                 match event.event_type {
                     EventType::KeyPress(key) => {
-                        if let Key::Alt = key {
-                            trace!("侦测到Alt键按下");
+                        if hotkey.key.matches(key) {
+                            trace!("侦测到{}键按下", hotkey.key.label());
                             // 检查是否在上次Alt释放后的限定秒内
                             let mut should_show = false;
                             {
                                 let last_release = last_alt_release;
                                 if let Some(time) = last_release {
                                     let elapsed = time.elapsed();
-                                    if elapsed <= Duration::from_millis(DOUBLE_ALT_COOLDOWN) {
-                                        debug!("侦测到双击Alt键，两次之间间隔 {:?}", elapsed);
+                                    if elapsed <= interval {
+                                        debug!("侦测到双击{}键，两次之间间隔 {:?}", hotkey.key.label(), elapsed);
                                         should_show = true;
                                     }
                                 }
                             }
                             
                             // 如果应该显示，则发送事件
-                            if should_show && *all_by_double_alt_clone.lock().unwrap() {
+                            if should_show && hotkey.double_tap {
                                 *called_clone_loop.lock().unwrap() = true;
                                 proxy_clone_loop
                                     .send_event(event::UserEvent::ShowWindow)
                                     .unwrap();
                                 // 设置冷却期，限定秒内忽略Alt释放
-                                cooldown_until = Some(Instant::now() + Duration::from_millis(DOUBLE_ALT_COOLDOWN));
+                                cooldown_until = Some(Instant::now() + interval);
                             }
                         } else {
                             // debug!("其他键释放");
@@ -107,8 +121,8 @@ fn main() {
                         }
                     },
                     EventType::KeyRelease(key) => {
-                        if let Key::Alt = key {
-                        trace!("侦测到Alt键释放");
+                        if hotkey.key.matches(key) {
+                        trace!("侦测到{}键释放", hotkey.key.label());
                             // 检查是否在冷却期内
                             let now = Instant::now();
                             if let Some(cooldown_time) = cooldown_until {
@@ -136,8 +150,8 @@ fn main() {
         .with_resizable(false)
         .with_visible(false)
         .with_inner_size(winit::dpi::LogicalSize {
-            width: WINDOW_SIZE.0,
-            height: WINDOW_SIZE.1,
+            width: settings.window.width,
+            height: settings.window.height,
         })
         .with_title("BaroBoard 工具箱") // 参见 https://github.com/emilk/egui/pull/2279
         .with_window_icon({
@@ -162,20 +176,17 @@ fn main() {
     .on_click(event::UserEvent::LeftClickTrayIcon)
     .on_right_click(event::UserEvent::RightClickTrayIcon)
 
-    .menu(
-        trayicon::MenuBuilder::new()
-        .item("显示工具箱", event::UserEvent::ShowWindow)
-        .checkable("双击呼出", *all_by_double_alt.lock().unwrap(), event::UserEvent::ChangeDoubleAlt)
-        .item("退出", event::UserEvent::Exit)
-    )
+    .menu(commands::tray_menu(hotkey.lock().unwrap().double_tap))
 
     .build()
     .unwrap();
 
     // 创建主应用程序
     let proxy_clone_app = proxy.clone();
+    let show_on_startup = settings.launch.show_on_startup;
+    let hotkey_app = hotkey.clone();
     let mut app = glow_app::GlowApp::new(
-        all_by_double_alt,
+        hotkey,
         winit_window_builder,
         tray_icon,
         proxy.clone(),
@@ -189,17 +200,21 @@ fn main() {
             egui_extras::install_image_loaders(egui_ctx);
             // 设置自定义字体，支持中文显示
             resources::setup_custom_fonts(egui_ctx);
+            egui_ctx.set_theme(settings.window.theme.preference());
 
             Box::new(MyApp::new(
                 called.clone(),
+                hotkey_app.clone(),
+                settings.clone(),
                 proxy_clone_app.clone()
             ))
         }),
     );
 
     // 在这里控制是否在打开程序的时候就显示
-    #[cfg(debug_assertions)]
-    proxy.send_event(event::UserEvent::ShowWindow).unwrap();
+    if cfg!(debug_assertions) || show_on_startup {
+        proxy.send_event(event::UserEvent::ShowWindow).unwrap();
+    }
     // 启动事件循环，这通常是阻塞的，会一直运行直到应用程序关闭
     // 事件循环会不断处理输入事件、UI更新和渲染，这是GUI应用程序的主要执行模式
     event_loop.run_app(&mut app).expect("failed to run app");
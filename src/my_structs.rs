@@ -6,13 +6,24 @@ use std::sync::{Arc, Mutex};
 use std::process::Command;
 use log::{debug, error, info, warn};
 
+use crate::pages::ShellPanel;
 use crate::pages::popups::Popups;
+use crate::pages::popups::link::{backup, catalog, migrate, save, watch};
+use crate::pages::popups::link::catalog::Catalog;
+use crate::pages::popups::link::trash::TrashedLink;
+use crate::pages::popups::link::undo::UndoStack;
+use crate::pages::popups::link::watch::LinkSnapshot;
+use crate::search::{SearchEngine, SearchHistory};
+use crate::settings::{AppSettings, HotkeySettings};
 use crate::window::{self, event::UserEvent};
 
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ProgramLink {
-    pub name: Vec<String>,
+    pub name: String,
+    // 只用于搜索匹配的别名，不会显示
+    #[serde(default)]
+    pub keywords: Vec<String>,
     pub icon_path: String,
     pub run_command: String,
     pub arguments: Vec<String>,
@@ -24,12 +35,17 @@ pub struct ProgramLink {
 
      // 自动生成
     pub uuid: String,
+
+    // 来自哪个共享目录，None 表示用户自己的快捷方式
+    #[serde(skip)]
+    pub catalog: Option<String>,
 }
 
 impl Default for ProgramLink {
     fn default() -> Self {
         Self {
-            name: Vec::new(),
+            name: "".to_string(),
+            keywords: Vec::new(),
             icon_path: "".to_string(),
             run_command: "".to_string(),
             arguments: Vec::new(),
@@ -39,12 +55,14 @@ impl Default for ProgramLink {
             is_new_window: true,
 
             uuid: Uuid::new_v4().to_string(),
+
+            catalog: None,
         }
     }
 }
 
 impl ProgramLink {
-    pub fn new(name: Vec<String>, icon_path: String, run_command: String, argument: Vec<String>, tags: HashSet<String>, is_admin: bool, is_new_window: bool) -> Self {
+    pub fn new(name: String, icon_path: String, run_command: String, argument: Vec<String>, tags: HashSet<String>, is_admin: bool, is_new_window: bool) -> Self {
         Self {
             name: name,
             icon_path: icon_path,
@@ -59,23 +77,46 @@ impl ProgramLink {
 }
 
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Page {
-    pub program_links: Vec<ProgramLink>,
-    pub title: String,
-}
-
+/// 读取快捷方式配置，配置文件不存在时为空
+///
+/// 旧版本的配置会先备份再自动迁移，配置文件无法解析时返回错误
+pub fn load_links(config_path: &std::path::Path) -> Result<LinkSnapshot, std::io::Error> {
+    match save::load_conf(config_path) {
+        Ok(mut links_config) => {
+            if migrate::needs_migration(&links_config) {
+                let version = migrate::config_version(&links_config);
+                if let Err(e) = backup::create_migration_backup(config_path, version) {
+                    warn!("迁移前备份配置文件失败: {}", e);
+                }
+                migrate::migrate(&mut links_config);
+            }
 
-#[derive(Debug)]
-pub struct LinkPosition {
-    pub link_index: usize,
+            // 尝试反序列化为正确的结构体
+            let config = serde_json::from_value::<save::LinkConfigSchema>(links_config)?;
+            Ok(LinkSnapshot::new(config.program_links, config.tags, config.hidden, config.trash).with_title(config.title))
+        },
+        // 检查文件是否存在
+        Err(e) if !config_path.exists() => {
+            debug!("{}", e);
+            Ok(LinkSnapshot::default())
+        },
+        Err(e) => Err(e),
+    }
 }
 
-impl LinkPosition {
-    pub fn new(link_index: usize) -> Self {
-        Self {
-            link_index: link_index,
-        }
+/// 读取快捷方式配置，无法读取时会显示对应的弹窗并返回 None
+fn read_links_config(
+    popups: &mut Popups,
+    proxy: &winit::event_loop::EventLoopProxy<UserEvent>,
+) -> Option<LinkSnapshot> {
+    match load_links(&save::config_path()) {
+        Ok(links) => Some(links),
+        Err(e) => {
+            debug!("{}", e);
+            proxy.send_event(crate::event::UserEvent::ShowWindow).unwrap();
+            popups.config_file_format_error();
+            None
+        },
     }
 }
 
@@ -88,14 +129,32 @@ pub struct MyApp {
     pub program_links: Vec<ProgramLink>,
     // 标签
     pub tags: HashSet<String>,
+    // 共享目录中的只读快捷方式
+    pub catalog: Catalog,
+    // 隐藏的共享快捷方式的uuid
+    pub hidden_links: HashSet<String>,
+    // 回收站
+    pub trash: Vec<TrashedLink>,
+    // 最近一次与磁盘同步的用户层
+    pub synced_links: LinkSnapshot,
+    // 配置文件被外部修改，等弹窗关闭后处理
+    pub config_changed: bool,
+    // 本次运行中的修改记录
+    pub undo: UndoStack,
     // 当前标签
     pub current_tag: Option<String>,
-    // 标题
-    pub title: String,
+    // 当前工作区的标题，None 时使用默认标题
+    pub title: Option<String>,
     // 搜索文本
     pub search_text: String,
-    // 排序后的程序链接
-    pub sorted_program_links: Vec<ProgramLink>,
+    // 搜索引擎，合并各个提供者的结果
+    pub search_engine: SearchEngine,
+    // 按下回车时搜索还没有完成，等结果返回后再执行，同时记录按下的修饰键
+    pub pending_enter: Option<egui::Modifiers>,
+    // 执行过的查询，可以在空的搜索框中用上下键回填
+    pub search_history: SearchHistory,
+    // 捕获输出的shell命令
+    pub shell_panel: ShellPanel,
 
     // 停止保存模式
     pub wont_save: bool,
@@ -114,22 +173,30 @@ pub struct MyApp {
 
     // 被唤起
     pub called: Arc<Mutex<bool>>,
+    // 与键盘监听共享的呼出快捷键设置
+    pub hotkey: Arc<Mutex<HotkeySettings>>,
+
+    // 应用程序设置
+    pub settings: AppSettings,
 }
 
 impl MyApp {
     pub fn new(
         called: Arc<Mutex<bool>>,
+        hotkey: Arc<Mutex<HotkeySettings>>,
+        settings: AppSettings,
         proxy: winit::event_loop::EventLoopProxy<UserEvent>
     ) -> Self {
         let mut wont_save = false;
 
-        // 创建.baro文件夹
-        if !std::path::Path::new(crate::CONFIG_SAVE_PATH).exists() {
-            warn!("配置文件夹 {} 不存在，尝试创建", crate::CONFIG_SAVE_PATH);
-            match std::fs::create_dir_all(crate::CONFIG_SAVE_PATH) {
-                Ok(_) => info!("创建.baro文件夹成功"),
+        // 创建配置文件夹
+        let config_dir = crate::paths::config_dir();
+        if !config_dir.exists() {
+            warn!("配置文件夹 {} 不存在，尝试创建", config_dir.display());
+            match std::fs::create_dir_all(config_dir) {
+                Ok(_) => info!("创建配置文件夹成功"),
                 Err(e) => {
-                    error!("创建.baro文件夹失败: {}", e);
+                    error!("创建配置文件夹失败: {}", e);
                     wont_save = true;
                 },
             }
@@ -137,63 +204,135 @@ impl MyApp {
 
         let mut popup = Popups::new();
 
-        let links_config = crate::pages::popups::link::save::load_conf(format!("{}/{}", crate::CONFIG_SAVE_PATH, crate::CONFIG_FILE_NAME).as_str());
-
-        let (program_links, tags) =  match links_config {
-            Ok(links_config) => {
-                let version = links_config.get("version")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0) as u32;
-                
-                if version < crate::CONFIG_FILE_VERSION {
-                    proxy.send_event(crate::event::UserEvent::ShowWindow).unwrap();
-                    popup.config_file_too_old();
-                    (Vec::new(), HashSet::new())
-                } else {
-                    // 尝试反序列化为正确的结构体
-                    match serde_json::from_value::<crate::pages::popups::link::save::LinkConfigSchema>(links_config) {
-                        Ok(config) => (config.program_links, config.tags),
-                        Err(_) => {
-                            proxy.send_event(crate::event::UserEvent::ShowWindow).unwrap();
-                            popup.config_file_format_error();
-                            (Vec::new(), HashSet::new())
-                        }
-                    }
-                }
-            },
-            Err(e) => {
-                debug!("{}", e);
-                // 检查文件是否存在
-                if std::path::Path::new(format!("{}/{}", crate::CONFIG_SAVE_PATH, crate::CONFIG_FILE_NAME).as_str()).exists() {
-                    proxy.send_event(crate::event::UserEvent::ShowWindow).unwrap();
-                    popup.config_file_format_error();
-                }
-                (Vec::new(), HashSet::new())
-            },
-        };
+        let links = read_links_config(&mut popup, &proxy).unwrap_or_default();
+        watch::spawn_watcher(proxy.clone());
+
+        let catalog = catalog::load_catalog(&settings.catalogs);
 
-        Self {  
+        let mut app = Self {  
             proxy: proxy,
 
-            program_links: program_links,
-            tags: tags,
+            program_links: Vec::new(),
+            tags: HashSet::new(),
+            catalog: catalog,
+            hidden_links: HashSet::new(),
+            trash: Vec::new(),
+            synced_links: LinkSnapshot::default(),
+            config_changed: false,
+            undo: UndoStack::default(),
             current_tag: None,
-            title: "BaroBoard 工具箱".to_string(),
+            title: None,
             search_text: "".to_string(),
-            sorted_program_links: Vec::new(),
+            search_engine: SearchEngine::new(&settings.search),
+            pending_enter: None,
+            search_history: SearchHistory::load(&settings.search.history),
+            shell_panel: ShellPanel::default(),
             popups: popup,
             cached_icon: HashMap::new(),
             icon_will_clean: Vec::new(),
             called: called,
+            hotkey: hotkey,
             edit_mode: false,
             is_hover_file: None,
             wont_save: wont_save,
+            settings: settings,
+        };
+
+        app.apply_links(links);
+        app.synced_links = app.links_snapshot();
+        app.undo.reset(app.synced_links.clone());
+
+        if app.purge_expired_trash() {
+            app.write_conf();
+            app.undo.reset(app.links_snapshot());
+        }
+        app
+    }
+
+    /// 从磁盘重新读取快捷方式配置，读取失败时保留当前的快捷方式并返回 false
+    pub fn reload_conf(&mut self) -> bool {
+        let Some(links) = read_links_config(&mut self.popups, &self.proxy) else {
+            return false;
+        };
+        self.catalog = catalog::load_catalog(&self.settings.catalogs);
+
+        info!("重新加载了 {} 个快捷方式", links.program_links.len());
+        self.apply_links(links);
+        self.synced_links = self.links_snapshot();
+        self.undo.reset(self.synced_links.clone());
+        true
+    }
+
+    /// 替换用户层的快捷方式与标签并叠加到共享目录上，保留搜索、编辑模式等界面状态
+    pub fn apply_links(&mut self, links: LinkSnapshot) {
+        let (program_links, tags) = self.catalog.compose(&links);
+        self.program_links = program_links;
+        self.tags = tags;
+        self.hidden_links = links.hidden;
+        self.trash = links.trash;
+        self.title = links.title;
+
+        if self.current_tag.as_ref().is_some_and(|tag| !self.tags.contains(tag)) {
+            self.current_tag = None;
+        }
+    }
+
+    /// 内存中的用户层，与保存到磁盘上的内容一致
+    ///
+    /// 共享目录中的标签只有在被用户的快捷方式使用时才会保存
+    pub fn links_snapshot(&self) -> LinkSnapshot {
+        let program_links: Vec<ProgramLink> = self.program_links
+            .iter()
+            .filter(|link| link.catalog.is_none())
+            .cloned()
+            .collect();
+        let used_tags: HashSet<&String> = program_links.iter().flat_map(|link| link.tags.iter()).collect();
+        let tags = self.tags
+            .iter()
+            .filter(|tag| !self.catalog.tags.contains(*tag) || used_tags.contains(tag))
+            .cloned()
+            .collect();
+
+        LinkSnapshot::new(program_links, tags, self.hidden_links.clone(), self.trash.clone())
+            .with_title(self.title.clone())
+    }
+
+    /// 隐藏共享目录中的快捷方式，用户层中的覆盖也会一起移除
+    pub fn hide_link(&mut self, uuid: &str) {
+        let Some(index) = self.program_links.iter().position(|link| link.uuid == uuid) else {
+            return;
+        };
+        let program_link = self.program_links.remove(index);
+        info!("隐藏共享快捷方式: {}", program_link.name);
+        self.hidden_links.insert(program_link.uuid);
+    }
+
+    /// 用共享目录中的版本替换用户层中的覆盖
+    pub fn revert_link(&mut self, uuid: &str) {
+        let Some(catalog_link) = self.catalog.find(uuid) else {
+            return;
+        };
+        info!("还原共享快捷方式: {}", catalog_link.name);
+        for program_link in self.program_links.iter_mut().filter(|link| link.uuid == uuid) {
+            *program_link = catalog_link.clone();
         }
     }
 
+    /// 重新显示所有隐藏的共享快捷方式
+    pub fn show_hidden_links(&mut self) {
+        let mut links = self.links_snapshot();
+        info!("显示 {} 个隐藏的共享快捷方式", links.hidden.len());
+        links.hidden.clear();
+        self.apply_links(links);
+    }
+
     pub fn clean_unused_icon(&mut self, ctx: &egui::Context) {
         for icon_path in self.icon_will_clean.iter() {
-            if self.cached_icon.get(icon_path).map_or(true, |set| set.is_empty()) {
+            // 撤销或还原后还会用到的图标不能删除
+            let in_trash = self.trash.iter().any(|trashed| &trashed.program_link.icon_path == icon_path);
+            if in_trash || self.undo.references_icon(icon_path) {
+                debug!("图片仍在撤销记录或回收站中，将不会释放 {}", icon_path);
+            } else if self.cached_icon.get(icon_path).map_or(true, |set| set.is_empty()) {
                 debug!("释放图片资源 {}", icon_path);
                 ctx.forget_image(&format!("file://{}", icon_path));
                 // ctx.forget_all_images();
@@ -201,9 +340,14 @@ impl MyApp {
 
                 #[cfg(target_os = "windows")]
                 {
-                    match std::fs::remove_file(icon_path.clone()) {
-                        Ok(_) => debug!("删除缓存图片资源 {} 成功", icon_path),
-                        Err(e) => debug!("删除缓存图片资源 {} 失败: {}", icon_path, e),
+                    // 图标缓存在工作区之间共用
+                    if crate::pages::popups::link::profile::icon_used_by_other_profiles(icon_path) {
+                        debug!("图片仍在其他工作区中使用，将不会删除 {}", icon_path);
+                    } else {
+                        match std::fs::remove_file(icon_path.clone()) {
+                            Ok(_) => debug!("删除缓存图片资源 {} 成功", icon_path),
+                            Err(e) => debug!("删除缓存图片资源 {} 失败: {}", icon_path, e),
+                        }
                     }
                 }
             } else {
@@ -217,20 +361,24 @@ impl MyApp {
         // 解析命令字符串，分离程序名和参数
         let command = program_link.run_command;
         let args = program_link.arguments;
-        let is_admin = program_link.is_admin;
-        let is_new_window = program_link.is_new_window;
 
-        let program_name = program_link.name.get(0);
+        let program_name = program_link.name;
         
         if command.is_empty() {
-            debug!("{} 运行失败: 命令为空", program_name.unwrap_or(&"".to_string()));
+            debug!("{} 运行失败: 命令为空", program_name);
+            return;
+        }
+
+        // 网址交给默认浏览器打开
+        if crate::search::provider::is_url(&command) {
+            crate::search::provider::open_url(&command);
             return;
         }
 
         #[cfg(target_os = "windows")]
         {
             // 根据不同的运行模式选择不同的执行方式
-            let result = match (is_admin, is_new_window) {
+            let result = match (program_link.is_admin, program_link.is_new_window) {
                 // 管理员权限 + 新窗口
                 (true, true) => {
                     let mut ps_command = format!(
@@ -280,12 +428,19 @@ impl MyApp {
             };
 
             match result {
-                Ok(_) => debug!("{} 运行成功", program_name.unwrap_or(&"".to_string())),
+                Ok(_) => debug!("{} 运行成功", program_name),
                 Err(e) => {
-                    debug!("{} 运行失败: {}", program_name.unwrap_or(&"".to_string()), e);
+                    debug!("{} 运行失败: {}", program_name, e);
                 },
             }
         }
+
+        // 管理员权限与新窗口只在 Windows 下有效，其他平台直接启动
+        #[cfg(not(target_os = "windows"))]
+        match Command::new(&command).args(&args).spawn() {
+            Ok(_) => debug!("{} 运行成功", program_name),
+            Err(e) => warn!("{} 运行失败: {}", program_name, e),
+        }
     }
 
     // pub fn show_window(&self) {
@@ -342,13 +497,13 @@ impl MyApp {
             let name = name.strip_suffix(".exe").unwrap_or(&name).to_string();
 
             self.program_links.push(ProgramLink::new(
-                vec![name],
+                name,
                 icon_path,
                 path.clone(),
                 Vec::new(),
                 HashSet::new(),
-                false,
-                true,
+                self.settings.launch.admin,
+                self.settings.launch.new_window,
             ));
         }
 
@@ -376,10 +531,18 @@ impl window::App for MyApp {
             self.hide_window();
         }
 
+        if self.config_changed && !self.popups.called {
+            self.sync_external_config();
+        }
+
+        self.handle_undo_shortcuts(ctx);
+        self.handle_profile_shortcuts(ctx);
+
         egui::CentralPanel::default().show(ctx, |ui| {
             // 顺序是重要的
             self.main_ui(ctx, ui);
             self.clean_unused_icon(ctx);
+            self.show_undo_toast(ctx);
             self.file_hover_ui(ctx, ui);
         });
     }
@@ -398,6 +561,29 @@ impl window::App for MyApp {
         self.is_hover_file = None;
         self.create_link_by_hover_file(path);
     }
+
+    // 托盘菜单切换了双击呼出
+    fn on_hotkey_changed(&mut self) {
+        self.settings.hotkey = self.hotkey.lock().unwrap().clone();
+        self.save_settings();
+    }
+
+    // 托盘菜单切换了工作区
+    fn on_switch_profile(&mut self, profile: Option<String>) {
+        // 没有切换时恢复托盘菜单中的勾选
+        if !self.switch_profile(profile) {
+            self.proxy.send_event(UserEvent::ProfilesChanged).unwrap();
+        }
+    }
+
+    // 配置文件发生变化
+    fn on_config_file_changed(&mut self) {
+        // 弹窗中可能正在编辑快捷方式，等关闭后再处理
+        self.config_changed = true;
+        if !self.popups.called {
+            self.sync_external_config();
+        }
+    }
 }
 
 impl Drop for MyApp {
@@ -405,11 +591,3 @@ impl Drop for MyApp {
         debug!("MyApp 被销毁");
     }
 }
-
-pub fn sort_by_tag(program_links: Vec<ProgramLink>, tag: String) -> Vec<ProgramLink> {
-    program_links
-    .iter()
-    .filter(|link| link.tags.contains(&tag))
-    .cloned()
-    .collect()
-}
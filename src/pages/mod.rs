@@ -1,17 +1,20 @@
 pub mod popups;
 mod sidebar;
+mod shell_output;
 
 use egui;
 use log::{debug, info};
 use std::collections::HashSet;
-use strsim::jaro_winkler;
-use pinyin::ToPinyin;
 
+use crate::commands::AppCommand;
 use crate::my_structs::*;
+use crate::pages::popups::link::profile;
+use crate::search::{self, ItemIcon, QueryFilter, SearchAction, SearchItem, SearchQuery};
+pub use shell_output::ShellPanel;
 
-/// 表示程序链接在列表中的索引位置
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-struct ProgramLinkIndex(usize);
+/// 拖拽中的快捷方式，显示的列表经过筛选，用 uuid 对应到程序列表
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ProgramLinkUuid(String);
 
 impl MyApp {
     pub fn main_ui(&mut self, ctx: &egui::Context, ui: &mut egui::Ui)  {        
@@ -25,7 +28,7 @@ impl MyApp {
                     ui.horizontal_wrapped(|ui| {
                         
                         ui.with_layout(egui::Layout::centered_and_justified(egui::Direction::LeftToRight), |ui| {
-                            ui.heading(egui::RichText::new(&self.title))
+                            ui.heading(egui::RichText::new(self.display_title()))
                             .context_menu(|ui| {
 
                                 if ui.button("自身信息").clicked() {
@@ -39,13 +42,17 @@ impl MyApp {
                                     info!("{:?}", self.cached_icon);
                                 }
 
-                                if ui.button("隐藏").clicked() {
-                                    self.hide_window();
-                                }
-
                                 if ui.button("获取图标").clicked() {
                                     crate::utils::windows_utils::get_icon_from_exe("C:\\Windows\\System32\\notepad.exe").unwrap();
                                 }
+
+                                ui.separator();
+                                for command in AppCommand::ALL {
+                                    if ui.button(command.label()).clicked() {
+                                        self.run_app_command(command);
+                                        ui.close_menu();
+                                    }
+                                }
                             });
                             
                         });
@@ -68,7 +75,7 @@ impl MyApp {
 
                 ui.vertical_centered(|ui: &mut egui::Ui| {
                     // 搜索框占据中间位置
-                    let search_text = ui.add(egui::TextEdit::singleline(&mut self.search_text).hint_text("搜索"));
+                    let search_text = ui.add(egui::TextEdit::singleline(&mut self.search_text).hint_text("搜索 (支持 tag: cmd: arg: 与 -排除，> 执行命令，$ 运行shell)"));
                     // 如果程序被唤起，则请求焦点
                     let mut called_guard = self.called.lock().unwrap();
                     if *called_guard {  // 被呼叫了！
@@ -76,70 +83,74 @@ impl MyApp {
                         ctx.send_viewport_cmd(egui::viewport::ViewportCommand::Focus);
                         // self.edit_mode = false;
                         self.search_text = "".to_string();
+                        self.search_history.reset_cursor();
                         search_text.request_focus();
                         *called_guard = false;
                     }
+                    drop(called_guard);
+
+                    // 用户修改了回填的内容后，不再浏览历史
+                    if search_text.changed() {
+                        self.search_history.reset_cursor();
+                    }
+                    // 在空的搜索框中，或正在回填历史时，使用上下键浏览搜索历史
+                    if search_text.has_focus() && (self.search_text.is_empty() || self.search_history.is_browsing()) {
+                        let recalled = if ctx.input(|i| i.key_pressed(egui::Key::ArrowUp)) {
+                            self.search_history.previous().map(str::to_string)
+                        } else if ctx.input(|i| i.key_pressed(egui::Key::ArrowDown)) {
+                            self.search_history.next().map(str::to_string)
+                        } else {
+                            None
+                        };
+
+                        if let Some(recalled) = recalled {
+                            self.search_text = recalled;
+                            // 将光标移到末尾
+                            if let Some(mut state) = egui::TextEdit::load_state(ctx, search_text.id) {
+                                let end = egui::text::CCursor::new(self.search_text.chars().count());
+                                state.cursor.set_char_range(Some(egui::text::CCursorRange::one(end)));
+                                state.store(ctx, search_text.id);
+                            }
+                        }
+                    }
 
-                    // 如果搜索框里有内容，则进行搜索
+                    // 如果搜索框里有内容，则交给各个提供者在后台搜索
                     if !self.search_text.is_empty() {
-                        // 计算相似度
-                        let mut results: Vec<(ProgramLink, f64)> = if let Some(tag) = self.current_tag.clone() {sort_by_tag(self.program_links.clone(), tag)} else {self.program_links.clone()}
-                            .iter()
-                            .map(|program_link| {
-                                let max_score = program_link.name.iter().map(|name| {
-                                    // 计算多种情况下的相似度得分
-                                    let original_score = jaro_winkler(&self.search_text, &name);
-                                    let lower_score = jaro_winkler(&self.search_text, &name.to_lowercase());
-
-                                    let pinyin_score = jaro_winkler(
-                                        &self.search_text, 
-                                        &name.chars().map(|c| {
-                                            c.to_pinyin()
-                                            .map(|p| p.plain().to_string())
-                                            .unwrap_or_else(|| c.to_string())
-                                    }).collect::<String>());
-
-                                    // 取最高分
-                                    original_score
-                                        .max(lower_score)
-                                        .max(pinyin_score)
-                                })
-                                .collect::<Vec<f64>>()
-                                .iter()
-                                .cloned()
-                                .fold(0., f64::max);
-
-                                (program_link.clone(), max_score)
-                            })
-                            // 设置相似度阈值
-                            .filter(|(_, score)| *score > 0.5)
-                            .collect();
-                        
-                        // 按相似度降序排列
-                        results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-
-                        if !results.is_empty() {
-                            // 更新排序后的程序列表
-                            self.sorted_program_links = results.iter().map(|(program, _)| program.clone()).collect();
-                            // 如果按下回车键，则运行选中的程序
-                            // if search_text.has_focus() {
-                            if ctx.input(|i| i.key_pressed(egui::Key::Enter)) && 
-                                // 这一步的作用是，如果用户使用Tab聚焦到按钮时，不会触发搜索框的lost_focus，避免重复触发
-                                search_text.lost_focus()
-                            {
-                                
-                                info!("选中: {} 权重: {}", self.sorted_program_links[0].name.get(0).unwrap_or(&"".to_string()), results[0].1);
-                                self.run_program(self.sorted_program_links[0].clone());
+                        // 当前标签也作为过滤条件参与搜索
+                        let query = self.search_query();
+                        self.search_engine.submit(ctx, &self.search_text, query, &self.settings.search, &self.program_links);
+                        self.search_engine.poll();
+
+                        // 如果按下回车键，则执行排在第一位的结果
+                        // 这一步的作用是，如果用户使用Tab聚焦到按钮时，不会触发搜索框的lost_focus，避免重复触发
+                        if ctx.input(|i| i.key_pressed(egui::Key::Enter)) && search_text.lost_focus() {
+                            self.pending_enter = Some(ctx.input(|i| i.modifiers));
+                        }
+
+                        // 搜索还没有完成时，等待最新的结果
+                        if self.pending_enter.is_some() && !self.search_engine.is_pending() {
+                            let modifiers = self.pending_enter.take().unwrap_or_default();
+
+                            if let Some(selected) = self.search_engine.results().first().cloned() {
+                                info!("选中: {} 权重: {}", selected.label, selected.score);
+                                self.search_history.record(&self.search_text, &self.settings.search.history);
+                                let hides_window = selected.action.hides_window();
+                                // Ctrl+回车在文件管理器中显示
+                                if modifiers.command {
+                                    self.reveal_item(ctx, selected);
+                                } else {
+                                    self.activate_item(ctx, selected);
+                                }
                                 self.search_text = "".to_string();
 
-                                self.hide_window();
+                                if hides_window {
+                                    self.hide_window();
+                                }
                             }
-                            // }
-                            
-                        } else {
-                            // 如果搜索框里没有内容，则清空排序后的程序列表
-                            self.sorted_program_links.clear();
                         }
+                    } else {
+                        self.pending_enter = None;
+                        self.search_engine.clear();
                     }
                 });
             });
@@ -169,12 +180,13 @@ impl MyApp {
                 // ui.heading("页面内容");
                 
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                    ui.checkbox(&mut self.edit_mode, "编辑模式");
+                    ui.checkbox(&mut self.edit_mode, AppCommand::ToggleEditMode.label());
                 });
             });
             // });
             egui::ScrollArea::vertical().show(ui, |ui| {
                 self.show_popup(ui);
+                self.show_shell_output(ui.ctx());
                 // self.show_delete_link(ui);
                 // self.show_setting_window(ui);
                 // self.show_config_save_error(ui);
@@ -184,6 +196,106 @@ impl MyApp {
     }
 
 
+    /// 由搜索框内容与侧边栏选中的标签组成的查询
+    fn search_query(&self) -> SearchQuery {
+        let query = SearchQuery::parse(&self.search_text);
+
+        match self.current_tag.clone() {
+            Some(tag) => query.with_filter(QueryFilter::tag(tag)),
+            None => query,
+        }
+    }
+
+    /// 执行搜索结果对应的动作
+    pub fn activate_item(&mut self, ctx: &egui::Context, item: SearchItem) {
+        match item.action {
+            SearchAction::RunLink(program_link) => self.run_program(program_link),
+            SearchAction::CopyText(text) => {
+                info!("复制到剪贴板: {}", text);
+                ctx.copy_text(text);
+            }
+            SearchAction::OpenPath(path) => search::provider::open_path(&path),
+            SearchAction::OpenUrl(url) => search::provider::open_url(&url),
+            SearchAction::RunCommand(command) => self.run_app_command(command),
+            SearchAction::SwitchProfile(name) => {
+                self.switch_profile(name);
+            }
+            SearchAction::RunShell(shell) => {
+                if shell.capture_output {
+                    self.shell_panel.run(ctx, shell);
+                } else {
+                    shell.spawn();
+                }
+            }
+            #[cfg(target_os = "linux")]
+            SearchAction::LaunchApp(app) => app.launch(),
+        }
+    }
+
+    /// 在文件管理器中显示搜索结果，不是文件的结果照常执行
+    pub fn reveal_item(&mut self, ctx: &egui::Context, item: SearchItem) {
+        match item.action {
+            SearchAction::OpenPath(path) => search::provider::reveal_path(&path),
+            _ => self.activate_item(ctx, item),
+        }
+    }
+
+    /// 将搜索结果固定为永久的快捷方式
+    pub fn pin_item(&mut self, program_link: ProgramLink) {
+        info!("固定为快捷方式: {:?}", program_link.name);
+        self.program_links.push(program_link);
+        self.save_conf();
+    }
+
+    /// 显示快捷方式以外的搜索结果，点击时执行对应的动作
+    fn show_search_item(&mut self, ui: &mut egui::Ui, item: &SearchItem) {
+        ui.style_mut().visuals.widgets.inactive.weak_bg_fill = egui::Color32::TRANSPARENT;
+
+        let response = match &item.icon {
+            ItemIcon::Image(path) => ui.add_sized(
+                egui::vec2(96.0, 96.0),
+                egui::ImageButton::new(format!("file://{}", path))
+            ),
+            ItemIcon::Glyph(glyph) => ui.add_sized(
+                egui::vec2(96.0, 96.0),
+                egui::Button::new(egui::RichText::new(*glyph).size(48.))
+            ),
+        };
+        let response = response.on_hover_text_at_pointer(&item.detail);
+
+        if self.settings.search.show_score {
+            draw_score(ui, response.rect, item.score);
+        }
+
+        if !self.popups.called {
+            if response.clicked() {
+                self.activate_item(ui.ctx(), item.clone());
+            }
+
+            response.context_menu(|ui| {
+                ui.label(&item.label);
+                ui.separator();
+
+                if ui.button("运行").clicked() {
+                    self.activate_item(ui.ctx(), item.clone());
+                    ui.close_menu();
+                }
+                if matches!(item.action, SearchAction::OpenPath(_)) && ui.button("在文件管理器中显示").clicked() {
+                    self.reveal_item(ui.ctx(), item.clone());
+                    ui.close_menu();
+                }
+                if let Some(program_link) = item.pin_link() {
+                    if ui.button("固定为快捷方式").clicked() {
+                        self.pin_item(program_link);
+                        ui.close_menu();
+                    }
+                }
+            });
+        }
+
+        item_label(ui, &item.label, &item.highlights);
+    }
+
     fn show_page(&mut self, ui: &mut egui::Ui) {
         // 显示页面
         let mut should_save = false;
@@ -191,15 +303,26 @@ impl MyApp {
         // 记录拖拽源和目标位置
         let mut drag_from = None;
         let mut drag_to = None;
+        // 右键菜单中隐藏或还原的共享快捷方式
+        let mut hide_uuid = None;
+        let mut revert_uuid = None;
+        // 右键菜单中复制或移动到其他工作区的快捷方式
+        let mut send_to_profile = None;
         
         // 如果搜索框里有内容，则使用排序后的程序列表，否则使用页面中的程序列表
-        let display_program_links = if self.search_text.is_empty() {
-            if let Some(tag) = self.current_tag.clone() {sort_by_tag(self.program_links.clone(), tag)} else {self.program_links.clone()}
+        let display_items: Vec<SearchItem> = if self.search_text.is_empty() {
+            let query = self.search_query();
+            self.search_engine.links()
+                .search_links(&self.program_links, &query, &self.settings.search)
+                .into_iter()
+                .map(SearchItem::from)
+                .collect()
         } else {
-            self.sorted_program_links.clone()
+            self.search_engine.results().to_vec()
         };
 
-        let chunks: Vec<_> = display_program_links.chunks(6).collect();
+        let columns = self.settings.window.columns.max(1);
+        let chunks: Vec<_> = display_items.chunks(columns).collect();
 
         if chunks.is_empty() && !self.edit_mode {
             ui.centered_and_justified(|ui| {
@@ -221,12 +344,15 @@ impl MyApp {
         // 遍历每个chunk显示
         for (i, chunk) in chunks.iter().enumerate() {
             ui.horizontal(|ui| {
-                for (link_index, program) in (*chunk).iter().enumerate() {
-                    // 计算当前项目在整个列表中的绝对索引
-                    let absolute_index = i * 6 + link_index;
-                    
+                for item in (*chunk).iter() {
                     // 图标与名称
                     ui.vertical(|ui| {
+                        // 其它提供者的结果只能执行，不能编辑
+                        let Some(program) = item.program_link() else {
+                            self.show_search_item(ui, item);
+                            return;
+                        };
+
                         // 注册对icon_path的缓存 - 使用entry API优化
                         self.cached_icon
                             .entry(program.icon_path.clone())
@@ -252,11 +378,12 @@ impl MyApp {
                             }
                         });
 
-                        let enable_drag = self.edit_mode && !self.popups.called;
+                        // 共享目录中的快捷方式是只读的，不能拖拽排序
+                        let enable_drag = self.edit_mode && !self.popups.called && program.catalog.is_none();
 
                         let response = if enable_drag {
                             // 在编辑模式下启用拖拽
-                            ui.dnd_drag_source(egui::Id::new(&program.uuid), ProgramLinkIndex(absolute_index), |ui| {
+                            ui.dnd_drag_source(egui::Id::new(&program.uuid), ProgramLinkUuid(program.uuid.clone()), |ui| {
                                 // 绘制图标按钮
                                 btn(ui)
                             }).response
@@ -264,11 +391,20 @@ impl MyApp {
                             btn(ui)
                         };
                         
+                        if self.edit_mode && program.catalog.is_some() {
+                            draw_read_only(ui, response.rect);
+                        }
+
+                        // 调试用，在图标右上角显示匹配得分
+                        if self.settings.search.show_score && !self.search_text.is_empty() {
+                            draw_score(ui, response.rect, item.score);
+                        }
+
                         // 检查是否有拖拽悬停在当前项目上
                         if enable_drag {
                             if let (Some(pointer), Some(_)) = (
                                 ui.input(|i| i.pointer.interact_pos()),
-                                response.dnd_hover_payload::<ProgramLinkIndex>(),
+                                response.dnd_hover_payload::<ProgramLinkUuid>(),
                             ) {
                                 // 获取当前项目的矩形区域，用于绘制视觉提示
                                 let rect = response.rect;
@@ -286,18 +422,12 @@ impl MyApp {
                                 }
                                 
                                 // 检查是否释放了拖拽
-                                if let Some(dragged_index) = response.dnd_release_payload::<ProgramLinkIndex>() {
+                                if let Some(dragged) = response.dnd_release_payload::<ProgramLinkUuid>() {
                                     // 记录拖拽源和目标
-                                    drag_from = Some(dragged_index.0);
+                                    drag_from = Some(dragged.0.clone());
                                     
                                     // 根据鼠标位置确定是插入到左侧还是右侧
-                                    let target_index = if pointer.x < rect.center().x {
-                                        absolute_index
-                                    } else {
-                                        absolute_index + 1
-                                    };
-                                    
-                                    drag_to = Some(target_index);
+                                    drag_to = Some((program.uuid.clone(), pointer.x >= rect.center().x));
 
                                     debug!("由于拖拽 尝试保存");
                                     should_save = true;
@@ -308,11 +438,14 @@ impl MyApp {
                         if !self.popups.called {
                             if self.edit_mode && response.clicked() {
                                 // 打开设置窗口
-                                self.popups.config_existing_link(LinkPosition::new(absolute_index), program);
+                                self.popups.config_existing_link(program);
 
                             } else {
                                 if response.clicked() {
                                     self.run_program(program.clone());
+                                    if self.settings.launch.hide_after_run {
+                                        self.hide_window();
+                                    }
                                 }
                                 
                                 // 右键点击图标，显示上下文菜单
@@ -322,54 +455,82 @@ impl MyApp {
                                         ui.label(if program.name.is_empty() {
                                             egui::RichText::new("未命名").weak()
                                         } else {
-                                            egui::RichText::new(&program.name.get(0).unwrap_or(&"".to_string()).to_owned())
+                                            egui::RichText::new(&program.name)
                                         });
                                     });
+                                    if let Some(catalog) = &program.catalog {
+                                        ui.label(egui::RichText::new(format!("来自共享目录 {}", catalog)).weak());
+                                    }
 
                                     ui.separator();
         
                                     if ui.button("运行")
                                     .clicked() {
                                         self.run_program(program.clone());
+                                        if self.settings.launch.hide_after_run {
+                                            self.hide_window();
+                                        }
 
                                         ui.close_menu();
                                     }
                                     if ui.button("编辑").clicked() {
-                                        self.popups.config_existing_link(LinkPosition::new(absolute_index), program);
+                                        self.popups.config_existing_link(program);
                                         ui.close_menu();
                                     }
+
+                                    // 有其他工作区时可以复制过去，自己的快捷方式还可以移动过去
+                                    let current_profile = profile::current();
+                                    let other_profiles: Vec<Option<String>> = profile::list_profiles()
+                                        .into_iter()
+                                        .filter(|name| *name != current_profile)
+                                        .collect();
+                                    if !other_profiles.is_empty() {
+                                        let mut actions = vec![("复制到工作区", false)];
+                                        if program.catalog.is_none() {
+                                            actions.push(("移动到工作区", true));
+                                        }
+                                        for (label, should_move) in actions {
+                                            ui.menu_button(label, |ui| {
+                                                for name in other_profiles.iter() {
+                                                    if ui.button(profile::display_name(name.as_deref())).clicked() {
+                                                        send_to_profile = Some((program.uuid.clone(), name.clone(), should_move));
+                                                        ui.close_menu();
+                                                    }
+                                                }
+                                            });
+                                        }
+                                    }
                                     
-                                    if ui.button("删除")
-                                    .clicked() {
-                                        self.popups.delete_link(LinkPosition::new(absolute_index));
-                                        // self.delete_link(link_index);
-                                        
-                                        ui.close_menu();
+                                    if program.catalog.is_some() {
+                                        // 共享目录中的快捷方式不能删除，只能隐藏
+                                        if ui.button("隐藏").clicked() {
+                                            hide_uuid = Some(program.uuid.clone());
+                                            ui.close_menu();
+                                        }
+                                    } else {
+                                        if self.catalog.contains(&program.uuid) && ui.button("还原为共享版本").clicked() {
+                                            revert_uuid = Some(program.uuid.clone());
+                                            ui.close_menu();
+                                        }
+                                        if ui.button("删除")
+                                        .clicked() {
+                                            self.popups.delete_link(program.uuid.clone());
+                                            // self.delete_link(link_index);
+                                            
+                                            ui.close_menu();
+                                        }
                                     }
                                 });
                             }
                         };
                         
                         // 快捷方式名称Label，最大宽度为96px，仅限一行
-                        ui.allocate_ui(egui::Vec2 { x: 96.0, y: 96.0 }, |ui| {
-                            let mut job = egui::text::LayoutJob::single_section(program.name.get(0).unwrap_or(&"".to_string()).to_owned(), 
-                                egui::TextFormat {
-                                ..Default::default()
-                            });
-                            job.wrap = egui::text::TextWrapping {
-                                max_rows: 1,
-                                break_anywhere: true,
-                                overflow_character: Some('…'),
-                                ..Default::default()
-                            };
-                            
-                            ui.label(job);
-                        });
+                        item_label(ui, &item.label, &item.highlights);
                     });
                 };
 
                 // 只有在不是最后一个chunk时才添加间隔
-                if i == chunks.len() - 1 && chunk.len() < 6 && self.edit_mode {
+                if i == chunks.len() - 1 && chunk.len() < columns && self.edit_mode {
                     show_on_next_line = false;
                     ui.vertical(|ui| {
                         let response = ui.add_sized(
@@ -377,7 +538,7 @@ impl MyApp {
                             egui::Button::new(egui::RichText::new("➕").size(48.))
                         );
                         if response.clicked() && !self.popups.called  {
-                            self.popups.config_new_link();
+                            self.popups.config_new_link(&self.settings.launch);
                         }
         
                     });
@@ -397,7 +558,7 @@ impl MyApp {
                         egui::Button::new(egui::RichText::new("➕").size(48.))
                     );
                     if response.clicked() && !self.popups.called  {
-                        self.popups.config_new_link();
+                        self.popups.config_new_link(&self.settings.launch);
                     }
                 });
             }
@@ -410,30 +571,36 @@ impl MyApp {
                     egui::Button::new(egui::RichText::new("➕").size(48.))
                 );
                 if response.clicked() && !self.popups.called  {
-                    self.popups.config_new_link();
+                    self.popups.config_new_link(&self.settings.launch);
                 }
             });
         }
         
         // 处理拖拽重排
-        if let (Some(from_idx), Some(to_idx)) = (drag_from, drag_to) {
-            if from_idx != to_idx && self.search_text.is_empty() {
-                // 获取对当前页面的可变引用
+        if let (Some(from_uuid), Some((to_uuid, after))) = (drag_from, drag_to) {
+            let from_idx = self.program_links.iter().position(|link| link.uuid == from_uuid);
+            if let Some(from_idx) = from_idx.filter(|_| from_uuid != to_uuid && self.search_text.is_empty()) {
                 // 先移除源项目
                 let program = self.program_links.remove(from_idx);
                 
-                // 调整目标索引（如果源在目标之前）
-                let adjusted_to_idx = if from_idx < to_idx {
-                    to_idx - 1
-                } else {
-                    to_idx
-                };
-                
-                // 插入到目标位置
-                self.program_links.insert(adjusted_to_idx, program);
-                
+                // 移除后再查找目标位置，插入到目标的左侧或右侧
+                match self.program_links.iter().position(|link| link.uuid == to_uuid) {
+                    Some(to_idx) => self.program_links.insert(to_idx + after as usize, program),
+                    None => self.program_links.insert(from_idx, program),
+                }
             }
         }
+        if let Some(uuid) = hide_uuid {
+            self.hide_link(&uuid);
+            should_save = true;
+        }
+        if let Some(uuid) = revert_uuid {
+            self.revert_link(&uuid);
+            should_save = true;
+        }
+        if let Some((uuid, target, should_move)) = send_to_profile {
+            self.send_link_to_profile(&uuid, target, should_move);
+        }
         // ctx.texture_ui(ui);
         
         if should_save {
@@ -441,3 +608,80 @@ impl MyApp {
         }
     }
 }
+
+
+/// 在图标左上角标记共享目录中的只读快捷方式
+fn draw_read_only(ui: &egui::Ui, icon_rect: egui::Rect) {
+    let galley = ui.painter().layout_no_wrap(
+        "🔒".to_string(),
+        egui::FontId::proportional(12.0),
+        egui::Color32::WHITE,
+    );
+    let rect = egui::Align2::LEFT_TOP
+        .anchor_size(icon_rect.left_top(), galley.size())
+        .expand(2.0);
+    ui.painter().rect_filled(rect, egui::CornerRadius::same(2), egui::Color32::from_black_alpha(160));
+    ui.painter().galley(rect.shrink(2.0).min, galley, egui::Color32::WHITE);
+}
+
+/// 在图标右上角显示匹配得分
+fn draw_score(ui: &egui::Ui, icon_rect: egui::Rect, score: f64) {
+    let galley = ui.painter().layout_no_wrap(
+        format!("{:.3}", score),
+        egui::FontId::monospace(11.0),
+        egui::Color32::WHITE,
+    );
+    let rect = egui::Align2::RIGHT_TOP
+        .anchor_size(icon_rect.right_top(), galley.size())
+        .expand(2.0);
+    ui.painter().rect_filled(rect, egui::CornerRadius::same(2), egui::Color32::from_black_alpha(160));
+    ui.painter().galley(rect.shrink(2.0).min, galley, egui::Color32::WHITE);
+}
+
+/// 结果名称，最大宽度为96px，仅限一行
+fn item_label(ui: &mut egui::Ui, label: &str, highlights: &[usize]) {
+    ui.allocate_ui(egui::Vec2 { x: 96.0, y: 96.0 }, |ui| {
+        let mut job = highlighted_name(ui, label, highlights);
+        job.wrap = egui::text::TextWrapping {
+            max_rows: 1,
+            break_anywhere: true,
+            overflow_character: Some('…'),
+            ..Default::default()
+        };
+        
+        ui.label(job);
+    });
+}
+
+/// 名称标签的排版，搜索命中的字符使用选中颜色高亮
+fn highlighted_name(ui: &egui::Ui, name: &str, highlights: &[usize]) -> egui::text::LayoutJob {
+    let mut job = egui::text::LayoutJob::default();
+
+    let normal = egui::TextFormat {
+        ..Default::default()
+    };
+    let highlighted = egui::TextFormat {
+        color: ui.visuals().strong_text_color(),
+        background: ui.visuals().selection.bg_fill,
+        ..Default::default()
+    };
+
+    // 将连续的高亮或普通字符合并为一段
+    let mut section = String::new();
+    let mut section_highlighted = false;
+
+    for (index, c) in name.chars().enumerate() {
+        let is_highlighted = highlights.binary_search(&index).is_ok();
+        if is_highlighted != section_highlighted && !section.is_empty() {
+            job.append(&section, 0.0, if section_highlighted { highlighted.clone() } else { normal.clone() });
+            section.clear();
+        }
+        section_highlighted = is_highlighted;
+        section.push(c);
+    }
+    if !section.is_empty() {
+        job.append(&section, 0.0, if section_highlighted { highlighted } else { normal });
+    }
+
+    job
+}
@@ -0,0 +1,214 @@
+use egui;
+use log::{debug, info};
+
+use crate::my_structs::*;
+use crate::search::Scorer;
+use crate::settings::{AppSettings, HotkeyKey, Language, Theme};
+use crate::window::event::UserEvent;
+use super::link::catalog;
+
+
+impl MyApp {
+    pub fn show_app_settings(&mut self, ui: &mut egui::Ui) {
+        let mut show = self.popups.called.clone();
+        let mut should_close = false;
+        let mut open_search_settings = false;
+
+        egui::Window::new("设置")
+        .collapsible(false)
+        .resizable(false)
+        .default_pos((self.settings.window.size() / 2.0).to_pos2())
+        .fade_in(true)
+        .fade_out(true)
+        .open(&mut show)
+
+        .show(ui.ctx(), |ui| {
+            egui::ScrollArea::vertical()
+            .max_height(320.)
+            .show(ui, |ui| {
+                ui.collapsing("呼出", |ui| {
+                    let hotkey = &mut self.settings.hotkey;
+                    ui.checkbox(&mut hotkey.double_tap, "双击按键呼出工具箱");
+                    ui.add_enabled_ui(hotkey.double_tap, |ui| {
+                        ui.horizontal(|ui| {
+                            egui::ComboBox::from_label("按键")
+                            .selected_text(hotkey.key.label())
+                            .show_ui(ui, |ui| {
+                                for key in HotkeyKey::ALL {
+                                    ui.selectable_value(&mut hotkey.key, key, key.label());
+                                }
+                            });
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("两次按键的最长间隔");
+                            ui.add(egui::Slider::new(&mut hotkey.interval, 200..=1000).suffix(" 毫秒"));
+                        });
+                    });
+                });
+
+                ui.collapsing("窗口与外观", |ui| {
+                    let window = &mut self.settings.window;
+                    egui::Grid::new("window_settings")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("窗口大小");
+                        ui.horizontal(|ui| {
+                            ui.add(egui::DragValue::new(&mut window.width).range(480.0..=1920.0).suffix(" 宽"));
+                            ui.add(egui::DragValue::new(&mut window.height).range(320.0..=1080.0).suffix(" 高"));
+                        });
+                        ui.end_row();
+
+                        ui.label("每行的快捷方式");
+                        ui.add(egui::Slider::new(&mut window.columns, 3..=12));
+                        ui.end_row();
+
+                        ui.label("主题");
+                        egui::ComboBox::from_id_salt("theme")
+                        .selected_text(window.theme.label())
+                        .show_ui(ui, |ui| {
+                            for theme in Theme::ALL {
+                                ui.selectable_value(&mut window.theme, theme, theme.label());
+                            }
+                        });
+                        ui.end_row();
+
+                        ui.label("语言");
+                        egui::ComboBox::from_id_salt("language")
+                        .selected_text(window.language.label())
+                        .show_ui(ui, |ui| {
+                            for language in Language::ALL {
+                                ui.selectable_value(&mut window.language, language, language.label());
+                            }
+                        });
+                        ui.end_row();
+                    });
+                });
+
+                ui.collapsing("启动与运行", |ui| {
+                    let launch = &mut self.settings.launch;
+                    ui.checkbox(&mut launch.show_on_startup, "启动后显示窗口")
+                        .on_hover_text("关闭时启动后只显示托盘图标");
+                    ui.checkbox(&mut launch.hide_after_run, "点击快捷方式运行后隐藏窗口");
+                    ui.separator();
+                    ui.label(egui::RichText::new("新建快捷方式时的默认选项").weak());
+                    ui.checkbox(&mut launch.new_window, "在新的命令行中运行");
+                    ui.checkbox(&mut launch.admin, if cfg!(target_os = "windows") { "以管理员权限运行" } else { "以超级用户运行" });
+                });
+
+                ui.collapsing("搜索", |ui| {
+                    let search = &mut self.settings.search;
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_label("匹配算法")
+                        .selected_text(search.scorer.label())
+                        .show_ui(ui, |ui| {
+                            for scorer in Scorer::ALL {
+                                ui.selectable_value(&mut search.scorer, scorer, scorer.label());
+                            }
+                        });
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("相似度阈值");
+                        ui.add(egui::Slider::new(&mut search.threshold, 0.0..=1.0).step_by(0.05));
+                    });
+                    ui.checkbox(&mut search.fold_chinese, "繁简通搜");
+                    if ui.button("更多搜索设置…").clicked() {
+                        open_search_settings = true;
+                    }
+                });
+
+                ui.collapsing("共享目录", |ui| {
+                    ui.label(egui::RichText::new("其中的快捷方式按顺序叠加在自己的快捷方式下方，且是只读的").weak());
+
+                    let mut remove_catalog = None;
+                    for (index, catalog) in self.settings.catalogs.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            if ui.small_button("🗑").clicked() {
+                                remove_catalog = Some(index);
+                            }
+                            ui.label(catalog);
+                        });
+                    }
+                    if let Some(index) = remove_catalog {
+                        self.settings.catalogs.remove(index);
+                    }
+
+                    ui.horizontal(|ui| {
+                        let add_file = ui.button("添加配置文件").clicked();
+                        let add_folder = ui.button("添加文件夹").clicked();
+                        let picked = if add_file {
+                            rfd::FileDialog::new().add_filter("共享目录", &["json"]).pick_file()
+                        } else if add_folder {
+                            rfd::FileDialog::new().pick_folder()
+                        } else {
+                            None
+                        };
+                        if let Some(path) = picked {
+                            let catalog = path.display().to_string();
+                            if !self.settings.catalogs.contains(&catalog) {
+                                self.settings.catalogs.push(catalog);
+                            }
+                        }
+                    });
+                });
+            });
+
+            ui.separator();
+            ui.with_layout(egui::Layout {
+                cross_align: egui::Align::RIGHT,
+                ..Default::default()
+            }, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("完成").clicked() {
+                        should_close = true;
+                    }
+                    if ui.button("恢复默认").clicked() {
+                        // 只重置呼出、窗口与启动设置，搜索设置在单独的窗口中恢复默认，当前的工作区保持不变
+                        self.settings = AppSettings {
+                            search: self.settings.search.clone(),
+                            catalogs: self.settings.catalogs.clone(),
+                            trash: self.settings.trash.clone(),
+                            profile: self.settings.profile.clone(),
+                            ..Default::default()
+                        };
+                    }
+                });
+            });
+        });
+
+        if (!show && !should_close && self.popups.called) || should_close || open_search_settings {
+            debug!("设置弹窗关闭");
+            self.popups.called = false;
+            self.apply_settings(ui.ctx());
+
+            if open_search_settings {
+                self.popups.search_settings();
+            }
+        }
+    }
+
+    /// 保存设置，并将与打开设置窗口时不同的部分应用到程序中
+    fn apply_settings(&mut self, ctx: &egui::Context) {
+        let Some(before) = self.popups.settings_before.take() else {
+            return;
+        };
+        if before == self.settings {
+            return;
+        }
+        info!("设置已修改");
+        self.save_settings();
+
+        if before.hotkey != self.settings.hotkey {
+            *self.hotkey.lock().unwrap() = self.settings.hotkey.clone();
+            self.proxy.send_event(UserEvent::HotkeyChanged).unwrap();
+        }
+        if before.window != self.settings.window {
+            self.settings.window.apply(ctx);
+        }
+        if before.catalogs != self.settings.catalogs {
+            // 用旧的共享目录取出用户层，再叠加到新的共享目录上
+            let links = self.links_snapshot();
+            self.catalog = catalog::load_catalog(&self.settings.catalogs);
+            self.apply_links(links);
+        }
+    }
+}
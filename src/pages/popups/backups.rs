@@ -0,0 +1,90 @@
+use egui;
+use log::{debug, error};
+
+use crate::my_structs::*;
+use super::link::{backup, save};
+
+
+impl MyApp {
+    pub fn show_backups(&mut self, ui: &mut egui::Ui) {
+        let mut show = self.popups.called.clone();
+        let mut should_close = false;
+        let mut backup_to_restore = None;
+
+        egui::Window::new("恢复备份")
+        .collapsible(false)
+        .resizable(false)
+        .default_pos((self.settings.window.size() / 2.0).to_pos2())
+        .fade_in(true)
+        .fade_out(true)
+        .open(&mut show)
+
+        .show(ui.ctx(), |ui| {
+            ui.label(egui::RichText::new(format!(
+                "每次保存前都会自动备份，最多保留 {} 份。恢复前会先备份当前的配置",
+                backup::MAX_BACKUPS,
+            )).weak());
+            ui.separator();
+
+            if self.popups.backups.is_empty() {
+                ui.label(egui::RichText::new("还没有任何备份").weak());
+            }
+
+            egui::ScrollArea::vertical()
+            .max_height(256.)
+            .show(ui, |ui| {
+                egui::Grid::new("backups")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (index, backup) in self.popups.backups.iter().enumerate() {
+                        ui.label(backup.created.format("%Y-%m-%d %H:%M:%S").to_string());
+                        ui.label(egui::RichText::new(format!("{:.1} KB", backup.size as f64 / 1024.)).weak());
+                        if ui.button("恢复").clicked() {
+                            backup_to_restore = Some(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+
+            ui.separator();
+            ui.with_layout(egui::Layout {
+                cross_align: egui::Align::RIGHT,
+                ..Default::default()
+            }, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("关闭").clicked() {
+                        should_close = true;
+                    }
+                    if ui.button("打开备份文件夹").clicked() {
+                        crate::search::provider::open_path(&backup::backup_dir());
+                    }
+                });
+            });
+        });
+
+        if (!show && !should_close && self.popups.called) || should_close {
+            debug!("恢复备份弹窗关闭");
+            self.popups.called = false;
+        }
+
+        if let Some(index) = backup_to_restore {
+            let backup = self.popups.backups[index].clone();
+            self.popups.called = false;
+
+            match backup::restore_backup(&backup, &save::config_path()) {
+                Ok(_) => {
+                    // 读取失败时会显示对应的弹窗，成功时恢复自动保存
+                    if self.reload_conf() {
+                        self.wont_save = false;
+                    }
+                }
+                Err(e) => {
+                    error!("恢复备份失败: {}", e);
+                    self.popups.cannot_save();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use egui;
+use std::collections::HashSet;
+use log::{debug, error, info};
+
+use crate::my_structs::*;
+use super::link::bookmarks::{self, Bookmark};
+
+
+/// 待导入的书签
+#[derive(Debug, Clone)]
+pub struct BookmarkEntry {
+    pub bookmark: Bookmark,
+    // 网址已经存在于快捷方式或前面的书签中
+    pub duplicate: bool,
+    pub selected: bool,
+}
+
+
+impl MyApp {
+    /// 选择浏览器导出的书签文件并读取，重复的网址默认不选中
+    pub fn open_import_bookmarks(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("书签文件", &["html", "htm"])
+            .pick_file() else {
+            return;
+        };
+
+        let html = match std::fs::read_to_string(&path) {
+            Ok(html) => html,
+            Err(e) => {
+                error!("读取书签文件 {} 失败: {}", path.display(), e);
+                self.popups.import_bookmarks(Err(e.to_string()));
+                return;
+            }
+        };
+
+        let mut urls: HashSet<String> = self.program_links.iter().map(|link| link.run_command.clone()).collect();
+        let entries = bookmarks::parse_bookmarks(&html)
+            .into_iter()
+            .map(|bookmark| {
+                let duplicate = !urls.insert(bookmark.url.clone());
+                BookmarkEntry {
+                    bookmark: bookmark,
+                    duplicate: duplicate,
+                    selected: !duplicate,
+                }
+            })
+            .collect();
+
+        self.popups.import_bookmarks(Ok(entries));
+    }
+
+    pub fn show_import_bookmarks(&mut self, ui: &mut egui::Ui) {
+        let mut show = self.popups.called.clone();
+        let mut should_close = false;
+        let mut should_import = false;
+
+        egui::Window::new("导入浏览器书签")
+        .collapsible(false)
+        .resizable(false)
+        .default_pos((self.settings.window.size() / 2.0).to_pos2())
+        .fade_in(true)
+        .fade_out(true)
+        .open(&mut show)
+
+        .show(ui.ctx(), |ui| {
+            match self.popups.bookmarks.as_mut() {
+                Some(Ok(entries)) if entries.is_empty() => {
+                    ui.label(egui::RichText::new("这个文件中没有可以导入的书签").weak());
+                }
+                Some(Ok(entries)) => {
+                    ui.label(egui::RichText::new("书签所在的文件夹会成为快捷方式的标签，已经存在的网址默认不导入").weak());
+                    ui.horizontal(|ui| {
+                        if ui.button("全选").clicked() {
+                            entries.iter_mut().for_each(|entry| entry.selected = true);
+                        }
+                        if ui.button("只选择新的书签").clicked() {
+                            entries.iter_mut().for_each(|entry| entry.selected = !entry.duplicate);
+                        }
+                        if ui.button("全不选").clicked() {
+                            entries.iter_mut().for_each(|entry| entry.selected = false);
+                        }
+                    });
+                    ui.separator();
+
+                    egui::ScrollArea::vertical()
+                    .max_height(256.)
+                    .show(ui, |ui| {
+                        egui::Grid::new("import_bookmarks")
+                        .num_columns(3)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for entry in entries.iter_mut() {
+                                let title = if entry.bookmark.title.is_empty() { &entry.bookmark.url } else { &entry.bookmark.title };
+                                ui.checkbox(&mut entry.selected, title)
+                                    .on_hover_text(&entry.bookmark.url);
+                                ui.label(egui::RichText::new(entry.bookmark.folders.join(" / ")).weak());
+                                if entry.duplicate {
+                                    ui.label(egui::RichText::new("重复").color(egui::Color32::LIGHT_RED));
+                                } else {
+                                    ui.label("");
+                                }
+                                ui.end_row();
+                            }
+                        });
+                    });
+                }
+                Some(Err(message)) => {
+                    ui.label(egui::RichText::new(format!("无法读取书签文件: {}", message)).color(egui::Color32::RED));
+                }
+                None => {}
+            }
+
+            ui.separator();
+            ui.with_layout(egui::Layout {
+                cross_align: egui::Align::RIGHT,
+                ..Default::default()
+            }, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("取消").clicked() {
+                        should_close = true;
+                    }
+                    if let Some(Ok(entries)) = &self.popups.bookmarks {
+                        let count = entries.iter().filter(|entry| entry.selected).count();
+                        if ui.add_enabled(count > 0, egui::Button::new(format!("导入 {} 个书签", count))).clicked() {
+                            should_import = true;
+                            should_close = true;
+                        }
+                    }
+                });
+            });
+        });
+
+        if (!show && !should_close && self.popups.called) || should_close {
+            debug!("导入书签弹窗关闭");
+            self.popups.called = false;
+
+            if should_import {
+                if let Some(Ok(entries)) = self.popups.bookmarks.take() {
+                    let mut imported = 0;
+                    for entry in entries.into_iter().filter(|entry| entry.selected) {
+                        let program_link = entry.bookmark.to_program_link();
+                        self.tags.extend(program_link.tags.iter().cloned());
+                        self.program_links.push(program_link);
+                        imported += 1;
+                    }
+                    info!("导入了 {} 个书签", imported);
+                    self.save_conf();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use egui;
+use log::{debug, error};
+
+use crate::my_structs::*;
+use super::link::bundle::{self, ImportAction, ImportConflict};
+use super::link::launchers::{self, LauncherFormat};
+
+
+impl MyApp {
+    /// 打开导出弹窗，指定标签时只预先选中带有此标签的快捷方式
+    pub fn open_export_bundle(&mut self, tag: Option<&str>) {
+        let selection = self.program_links
+            .iter()
+            .filter(|link| tag.is_none_or(|tag| link.tags.contains(tag)))
+            .map(|link| link.uuid.clone())
+            .collect();
+        self.popups.export_bundle(selection);
+    }
+
+    /// 选择导入包并读取，读取失败时在弹窗中显示原因
+    pub fn open_import_bundle(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("BaroBoard 导入包", &["zip"])
+            .pick_file() else {
+            return;
+        };
+
+        let bundle = bundle::read_bundle(&path, &self.program_links).map_err(|e| {
+            error!("读取导入包 {} 失败: {}", path.display(), e);
+            e.to_string()
+        });
+        self.popups.import_bundle(bundle);
+    }
+
+    pub fn show_export_bundle(&mut self, ui: &mut egui::Ui) {
+        let mut show = self.popups.called.clone();
+        let mut should_close = false;
+        let mut should_export = false;
+
+        egui::Window::new("导出快捷方式")
+        .collapsible(false)
+        .resizable(false)
+        .default_pos((self.settings.window.size() / 2.0).to_pos2())
+        .fade_in(true)
+        .fade_out(true)
+        .open(&mut show)
+
+        .show(ui.ctx(), |ui| {
+            ui.horizontal(|ui| {
+                let format = &mut self.popups.export_format;
+                egui::ComboBox::from_label("导出格式")
+                .selected_text(format.map_or(BUNDLE_LABEL, |format| format.label()))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(format, None, BUNDLE_LABEL);
+                    for launcher_format in LauncherFormat::ALL {
+                        ui.selectable_value(format, Some(launcher_format), launcher_format.label());
+                    }
+                });
+            });
+            ui.label(egui::RichText::new(match self.popups.export_format {
+                None => "选中的快捷方式会与它们的图标一起打包成 zip 文件",
+                Some(LauncherFormat::Desktop) => "每个快捷方式会生成一个 .desktop 文件，保存到选择的文件夹中",
+                Some(_) => "每个快捷方式会生成一个以别名或拼音命名的命令",
+            }).weak());
+
+            ui.horizontal(|ui| {
+                if ui.button("全选").clicked() {
+                    self.popups.export_selection = self.program_links.iter().map(|link| link.uuid.clone()).collect();
+                }
+                if ui.button("全不选").clicked() {
+                    self.popups.export_selection.clear();
+                }
+
+                let mut selected_tag = None;
+                egui::ComboBox::from_id_salt("export_tag")
+                .selected_text("按标签选择")
+                .show_ui(ui, |ui| {
+                    let mut tags: Vec<&String> = self.tags.iter().collect();
+                    tags.sort();
+                    for tag in tags {
+                        if ui.selectable_label(false, tag).clicked() {
+                            selected_tag = Some(tag.clone());
+                        }
+                    }
+                });
+                if let Some(tag) = selected_tag {
+                    self.popups.export_selection = self.program_links
+                        .iter()
+                        .filter(|link| link.tags.contains(&tag))
+                        .map(|link| link.uuid.clone())
+                        .collect();
+                }
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+            .max_height(256.)
+            .show(ui, |ui| {
+                for program_link in self.program_links.iter() {
+                    let mut selected = self.popups.export_selection.contains(&program_link.uuid);
+                    let label = if program_link.name.is_empty() { "未命名" } else { program_link.name.as_str() };
+                    if ui.checkbox(&mut selected, label).changed() {
+                        if selected {
+                            self.popups.export_selection.insert(program_link.uuid.clone());
+                        } else {
+                            self.popups.export_selection.remove(&program_link.uuid);
+                        }
+                    }
+                }
+            });
+
+            if let Some(message) = &self.popups.bundle_message {
+                ui.separator();
+                ui.label(egui::RichText::new(message).color(egui::Color32::RED));
+            }
+
+            ui.separator();
+            ui.with_layout(egui::Layout {
+                cross_align: egui::Align::RIGHT,
+                ..Default::default()
+            }, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("取消").clicked() {
+                        should_close = true;
+                    }
+                    let count = self.popups.export_selection.len();
+                    if ui.add_enabled(count > 0, egui::Button::new(format!("导出 {} 个快捷方式", count))).clicked() {
+                        should_export = true;
+                    }
+                });
+            });
+        });
+
+        if should_export {
+            let selected: Vec<ProgramLink> = self.program_links
+                .iter()
+                .filter(|link| self.popups.export_selection.contains(&link.uuid))
+                .cloned()
+                .collect();
+
+            let format = self.popups.export_format;
+            let path = match format {
+                None => rfd::FileDialog::new()
+                    .add_filter("BaroBoard 导入包", &["zip"])
+                    .set_file_name("baroboard-links.zip")
+                    .save_file(),
+                Some(format) => match format.file_name() {
+                    Some(file_name) => rfd::FileDialog::new().set_file_name(file_name).save_file(),
+                    None => rfd::FileDialog::new().pick_folder(),
+                },
+            };
+
+            if let Some(path) = path {
+                let result = match format {
+                    None => bundle::export_bundle(&selected, &path),
+                    Some(format) => launchers::export_launchers(format, &selected, &path),
+                };
+                match result {
+                    Ok(_) => should_close = true,
+                    Err(e) => {
+                        error!("导出到 {} 失败: {}", path.display(), e);
+                        self.popups.bundle_message = Some(format!("导出失败: {}", e));
+                    }
+                }
+            }
+        }
+
+        if (!show && !should_close && self.popups.called) || should_close {
+            debug!("导出快捷方式弹窗关闭");
+            self.popups.called = false;
+        }
+    }
+
+    pub fn show_import_bundle(&mut self, ui: &mut egui::Ui) {
+        let mut show = self.popups.called.clone();
+        let mut should_close = false;
+        let mut should_import = false;
+
+        egui::Window::new("导入快捷方式")
+        .collapsible(false)
+        .resizable(false)
+        .default_pos((self.settings.window.size() / 2.0).to_pos2())
+        .fade_in(true)
+        .fade_out(true)
+        .open(&mut show)
+
+        .show(ui.ctx(), |ui| {
+            match self.popups.import_bundle.as_mut() {
+                Some(Ok(bundle)) => {
+                    ui.label(egui::RichText::new(bundle.path.display().to_string()).weak());
+                    ui.separator();
+
+                    egui::ScrollArea::vertical()
+                    .max_height(256.)
+                    .show(ui, |ui| {
+                        egui::Grid::new("import_bundle")
+                        .num_columns(3)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (index, entry) in bundle.entries.iter_mut().enumerate() {
+                                ui.label(&entry.program_link.name);
+
+                                if entry.conflict == ImportConflict::None {
+                                    ui.label("");
+                                    let mut import = entry.action == ImportAction::Add;
+                                    if ui.checkbox(&mut import, ImportAction::Add.label()).changed() {
+                                        entry.action = if import { ImportAction::Add } else { ImportAction::Skip };
+                                    }
+                                } else {
+                                    ui.label(egui::RichText::new(entry.conflict.label()).color(egui::Color32::LIGHT_RED));
+                                    egui::ComboBox::from_id_salt(("import_action", index))
+                                    .selected_text(entry.action.label())
+                                    .show_ui(ui, |ui| {
+                                        for action in ImportAction::CONFLICT {
+                                            ui.selectable_value(&mut entry.action, action, action.label());
+                                        }
+                                    });
+                                }
+                                ui.end_row();
+                            }
+                        });
+                    });
+                }
+                Some(Err(message)) => {
+                    ui.label(egui::RichText::new(format!("无法读取导入包: {}", message)).color(egui::Color32::RED));
+                }
+                None => {}
+            }
+
+            ui.separator();
+            ui.with_layout(egui::Layout {
+                cross_align: egui::Align::RIGHT,
+                ..Default::default()
+            }, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("取消").clicked() {
+                        should_close = true;
+                    }
+                    if matches!(self.popups.import_bundle, Some(Ok(_))) && ui.button("导入").clicked() {
+                        should_import = true;
+                        should_close = true;
+                    }
+                });
+            });
+        });
+
+        if (!show && !should_close && self.popups.called) || should_close {
+            debug!("导入快捷方式弹窗关闭");
+            self.popups.called = false;
+
+            if should_import {
+                if let Some(Ok(bundle)) = self.popups.import_bundle.take() {
+                    let path = bundle.path.clone();
+                    match bundle::merge_bundle(bundle, &mut self.program_links, &mut self.tags) {
+                        Ok(_) => self.save_conf(),
+                        Err(e) => {
+                            error!("导入 {} 失败: {}", path.display(), e);
+                            self.popups.import_bundle(Err(e.to_string()));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+
+/// 导出格式中导入包的名称
+const BUNDLE_LABEL: &str = "BaroBoard 导入包 (zip)";
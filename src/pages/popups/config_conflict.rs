@@ -0,0 +1,139 @@
+use egui;
+use log::{debug, info, warn};
+
+use crate::my_structs::*;
+use super::link::{save, watch};
+
+
+impl MyApp {
+    /// 读取被外部修改的配置文件
+    ///
+    /// 内存中没有未保存的修改时直接重新加载，否则让用户选择合并或保留其中一边
+    pub fn sync_external_config(&mut self) {
+        self.config_changed = false;
+
+        let config_path = save::config_path();
+        // 外部程序保存时可能先删除再创建文件
+        if !config_path.exists() {
+            debug!("配置文件 {} 不存在，忽略", config_path.display());
+            return;
+        }
+
+        let external_links = match load_links(&config_path) {
+            Ok(links) => links,
+            Err(e) => {
+                warn!("无法读取被外部修改的配置文件，保留当前的快捷方式: {}", e);
+                return;
+            }
+        };
+
+        if external_links == self.synced_links {
+            debug!("配置文件的内容没有变化");
+            return;
+        }
+
+        if self.links_snapshot() == self.synced_links {
+            info!("配置文件被外部修改，重新加载了 {} 个快捷方式", external_links.program_links.len());
+            self.apply_links(external_links);
+            self.synced_links = self.links_snapshot();
+            self.undo.reset(self.synced_links.clone());
+            // 磁盘上的配置已经可以正常读取
+            self.wont_save = false;
+        } else {
+            info!("配置文件被外部修改，且内存中有未保存的修改");
+            self.proxy.send_event(crate::event::UserEvent::ShowWindow).unwrap();
+            self.popups.config_conflict(external_links);
+        }
+    }
+
+    pub fn show_config_conflict(&mut self, ui: &mut egui::Ui) {
+        let mut show = self.popups.called.clone();
+        let mut should_close = false;
+        let mut resolution = None;
+
+        egui::Window::new("配置文件被外部修改")
+        .title_bar(false)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .fade_in(true)
+        .fade_out(true)
+        .open(&mut show)
+
+        .show(ui.ctx(), |ui| {
+            ui.vertical_centered(|ui| {
+                ui.heading("配置文件被其他程序修改了");
+                ui.separator();
+                ui.label(
+                    "你在工具箱中的修改还没有保存，请选择如何处理两边的修改。"
+                );
+                ui.label(egui::RichText::new(
+                    "合并时以磁盘上的配置为基础加入你的修改，同一个快捷方式两边都修改过时以你的修改为准。"
+                ).weak());
+
+                ui.separator();
+
+                ui.with_layout(egui::Layout {
+                    cross_align: egui::Align::RIGHT,
+                    ..Default::default()
+                }, |ui| {
+                    ui.horizontal(|ui| {
+                        if ui.button("使用磁盘上的配置").clicked() {
+                            resolution = Some(ConflictResolution::KeepTheirs);
+                            should_close = true;
+                        }
+                        if ui.button("保留我的修改").clicked() {
+                            resolution = Some(ConflictResolution::KeepMine);
+                            should_close = true;
+                        }
+                        if ui.button("合并").clicked() {
+                            resolution = Some(ConflictResolution::Merge);
+                            should_close = true;
+                        }
+                    });
+                });
+            });
+        });
+
+        if (!show && !should_close && self.popups.called) || should_close {
+            debug!("配置文件冲突弹窗关闭");
+            self.popups.called = false;
+
+            let Some(external_links) = self.popups.external_links.take() else {
+                return;
+            };
+            let Some(resolution) = resolution else {
+                return;
+            };
+
+            info!("处理配置文件冲突: {:?}", resolution);
+            // 无论选择哪一边，磁盘上的配置都已经可以正常读取
+            self.wont_save = false;
+            match resolution {
+                ConflictResolution::KeepTheirs => {
+                    self.apply_links(external_links);
+                    self.synced_links = self.links_snapshot();
+                    self.undo.reset(self.synced_links.clone());
+                }
+                ConflictResolution::KeepMine => {
+                    self.synced_links = external_links;
+                    self.save_conf();
+                }
+                ConflictResolution::Merge => {
+                    let merged = watch::merge_snapshots(&self.synced_links, &self.links_snapshot(), &external_links);
+                    self.apply_links(merged);
+                    self.synced_links = external_links;
+                    self.save_conf();
+                }
+            }
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, Copy)]
+enum ConflictResolution {
+    Merge,
+    KeepMine,
+    KeepTheirs,
+}
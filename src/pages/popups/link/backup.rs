@@ -0,0 +1,233 @@
+//! 配置文件的备份
+//!
+//! 每次保存前都会把旧的配置文件复制到配置文件夹的 `backups` 中，文件名带有时间戳，只保留最近的几份
+
+use std::path::{Path, PathBuf};
+use chrono::{Local, NaiveDateTime};
+use log::{debug, info, warn};
+
+use crate::utils::file_utils::write_atomic;
+
+
+/// 备份文件夹的名称
+const BACKUP_DIR_NAME: &str = "backups";
+/// 最多保留的备份数量
+pub const MAX_BACKUPS: usize = 10;
+/// 文件名中时间戳的格式
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S%.3f";
+
+
+/// 一份备份
+#[derive(Debug, Clone)]
+pub struct Backup {
+    pub path: PathBuf,
+    pub created: NaiveDateTime,
+    // 文件大小，单位为字节
+    pub size: u64,
+}
+
+pub fn backup_dir() -> PathBuf {
+    crate::paths::config_dir().join(BACKUP_DIR_NAME)
+}
+
+/// 备份文件名的前缀，例如 links.json 的备份为 links-时间戳.json
+fn backup_prefix(path: &Path) -> String {
+    format!("{}-", path.file_stem().unwrap_or_default().to_string_lossy())
+}
+
+/// 备份配置文件，文件不存在时不做任何事
+pub fn create_backup(path: &Path) -> Result<Option<PathBuf>, std::io::Error> {
+    create_backup_in(&backup_dir(), path)
+}
+
+fn create_backup_in(dir: &Path, path: &Path) -> Result<Option<PathBuf>, std::io::Error> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    std::fs::create_dir_all(dir)?;
+    let target = dir.join(format!(
+        "{}{}.json",
+        backup_prefix(path),
+        Local::now().format(TIMESTAMP_FORMAT),
+    ));
+    std::fs::copy(path, &target)?;
+    debug!("备份配置文件到 {}", target.display());
+
+    prune_backups(dir, path);
+    Ok(Some(target))
+}
+
+/// 迁移前的备份，不参与轮换，例如 links.json 的 v5 备份为 links-v5.json
+pub fn create_migration_backup(path: &Path, version: u32) -> Result<PathBuf, std::io::Error> {
+    std::fs::create_dir_all(backup_dir())?;
+    let target = backup_dir().join(format!("{}v{}.json", backup_prefix(path), version));
+    std::fs::copy(path, &target)?;
+    info!("迁移前备份配置文件到 {}", target.display());
+    Ok(target)
+}
+
+/// 列出某个配置文件的所有备份，最新的在前
+pub fn list_backups(path: &Path) -> Vec<Backup> {
+    list_backups_in(&backup_dir(), path)
+}
+
+fn list_backups_in(dir: &Path, path: &Path) -> Vec<Backup> {
+    let prefix = backup_prefix(path);
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut backups: Vec<Backup> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let timestamp = file_name.strip_prefix(&prefix)?.strip_suffix(".json")?;
+            let created = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()?;
+
+            Some(Backup {
+                path: entry.path(),
+                created: created,
+                size: entry.metadata().map(|metadata| metadata.len()).unwrap_or(0),
+            })
+        })
+        .collect();
+
+    backups.sort_by(|a, b| b.created.cmp(&a.created));
+    backups
+}
+
+/// 删除超出数量的旧备份
+fn prune_backups(dir: &Path, path: &Path) {
+    for backup in list_backups_in(dir, path).into_iter().skip(MAX_BACKUPS) {
+        match std::fs::remove_file(&backup.path) {
+            Ok(_) => debug!("删除旧备份 {}", backup.path.display()),
+            Err(e) => warn!("删除旧备份 {} 失败: {}", backup.path.display(), e),
+        }
+    }
+}
+
+/// 用备份覆盖配置文件，覆盖前会先备份当前的配置文件
+pub fn restore_backup(backup: &Backup, path: &Path) -> Result<(), std::io::Error> {
+    restore_backup_in(&backup_dir(), backup, path)
+}
+
+fn restore_backup_in(dir: &Path, backup: &Backup, path: &Path) -> Result<(), std::io::Error> {
+    let contents = std::fs::read(&backup.path)?;
+    create_backup_in(dir, path)?;
+    write_atomic(path, &contents)?;
+
+    info!("从备份 {} 恢复配置文件", backup.path.display());
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("baroboard-backup-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// 在备份文件夹中放一份指定时间的备份
+    fn write_backup(dir: &Path, prefix: &str, timestamp: &str) -> PathBuf {
+        let path = dir.join(format!("{}{}.json", prefix, timestamp));
+        std::fs::write(&path, timestamp).unwrap();
+        path
+    }
+
+    fn timestamp(second: u32) -> String {
+        format!("20250101-1200{:02}.000", second)
+    }
+
+    #[test]
+    fn lists_newest_first() {
+        let root = temp_dir();
+        let dir = root.join(BACKUP_DIR_NAME);
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = root.join("links.json");
+
+        for second in [5, 1, 30, 12] {
+            write_backup(&dir, "links-", &timestamp(second));
+        }
+        // 迁移备份、其他配置文件的备份以及无关的文件都不算
+        write_backup(&dir, "links-", "v5");
+        write_backup(&dir, "work-", &timestamp(40));
+        write_backup(&dir, "links-", "not a time");
+        std::fs::write(dir.join("notes.txt"), "").unwrap();
+
+        let created: Vec<String> = list_backups_in(&dir, &config)
+            .iter()
+            .map(|backup| backup.created.format(TIMESTAMP_FORMAT).to_string())
+            .collect();
+        assert_eq!(created, vec![timestamp(30), timestamp(12), timestamp(5), timestamp(1)]);
+
+        // 没有备份文件夹时为空
+        assert!(list_backups_in(&root.join("missing"), &config).is_empty());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn keeps_newest_backups() {
+        let root = temp_dir();
+        let dir = root.join(BACKUP_DIR_NAME);
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = root.join("links.json");
+        std::fs::write(&config, "{}").unwrap();
+
+        for second in 0..(MAX_BACKUPS as u32 + 3) {
+            write_backup(&dir, "links-", &timestamp(second));
+        }
+        let migration = write_backup(&dir, "links-", "v5");
+        let other = write_backup(&dir, "work-", &timestamp(0));
+
+        // 新的备份加入后只保留最近的几份
+        let created = create_backup_in(&dir, &config).unwrap().unwrap();
+        let backups = list_backups_in(&dir, &config);
+        assert_eq!(backups.len(), MAX_BACKUPS);
+        assert_eq!(backups[0].path, created);
+        assert_eq!(backups[1].path, dir.join(format!("links-{}.json", timestamp(MAX_BACKUPS as u32 + 2))));
+        assert!(!dir.join(format!("links-{}.json", timestamp(3))).exists());
+
+        assert!(migration.exists());
+        assert!(other.exists());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn skips_missing_config() {
+        let root = temp_dir();
+        let dir = root.join(BACKUP_DIR_NAME);
+
+        assert_eq!(create_backup_in(&dir, &root.join("links.json")).unwrap(), None);
+        assert!(!dir.exists());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn restores_and_backs_up_current() {
+        let root = temp_dir();
+        let dir = root.join(BACKUP_DIR_NAME);
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = root.join("links.json");
+        std::fs::write(&config, "current").unwrap();
+        write_backup(&dir, "links-", &timestamp(0));
+
+        let backup = list_backups_in(&dir, &config).remove(0);
+        restore_backup_in(&dir, &backup, &config).unwrap();
+        assert_eq!(std::fs::read_to_string(&config).unwrap(), timestamp(0));
+
+        // 恢复前的配置也留有备份
+        let backups = list_backups_in(&dir, &config);
+        assert_eq!(backups.len(), 2);
+        assert_eq!(std::fs::read_to_string(&backups[0].path).unwrap(), "current");
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,244 @@
+//! 浏览器书签的导入
+//!
+//! 各个浏览器导出的书签都是 Netscape 书签格式的 HTML 文件：文件夹为 `<DT><H3>`，
+//! 其后的 `<DL>` 包含文件夹中的内容，书签为 `<DT><A HREF="...">`。
+//! 书签所在的每一层文件夹都会成为快捷方式的标签
+
+use std::collections::HashSet;
+
+use crate::my_structs::ProgramLink;
+
+
+/// 可以导入的网址协议，`file://` 等其他协议可能指向本地的可执行文件
+const ALLOWED_SCHEMES: [&str; 2] = ["http://", "https://"];
+
+
+/// 一个书签
+#[derive(Debug, Clone)]
+pub struct Bookmark {
+    pub title: String,
+    pub url: String,
+    // 从外到内的文件夹
+    pub folders: Vec<String>,
+}
+
+impl Bookmark {
+    pub fn to_program_link(&self) -> ProgramLink {
+        let name = if self.title.is_empty() { self.url.clone() } else { self.title.clone() };
+        ProgramLink::new(
+            name,
+            "".to_string(),
+            self.url.clone(),
+            Vec::new(),
+            self.folders.iter().cloned().collect::<HashSet<String>>(),
+            false,
+            false,
+        )
+    }
+}
+
+
+/// 解析书签文件，只保留 http 与 https 网址
+pub fn parse_bookmarks(html: &str) -> Vec<Bookmark> {
+    let mut bookmarks = Vec::new();
+    let mut folders: Vec<String> = Vec::new();
+    // 下一个 <DL> 所属的文件夹
+    let mut pending_folder: Option<String> = None;
+    // 每一层 <DL> 是否对应一个文件夹
+    let mut levels: Vec<bool> = Vec::new();
+
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let Some(end) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[..end];
+        rest = &rest[end + 1..];
+
+        // 标签之后到下一个标签之前的文本
+        let text = &rest[..rest.find('<').unwrap_or(rest.len())];
+        let name = tag.split_whitespace().next().unwrap_or("").to_ascii_uppercase();
+
+        match name.as_str() {
+            "H3" => pending_folder = Some(decode_entities(text.trim())),
+            "DL" => match pending_folder.take() {
+                Some(folder) => {
+                    folders.push(folder);
+                    levels.push(true);
+                }
+                None => levels.push(false),
+            },
+            "/DL" => {
+                if levels.pop() == Some(true) {
+                    folders.pop();
+                }
+            }
+            "A" => {
+                let Some(url) = attribute(tag, "HREF").map(|url| decode_entities(&url)) else {
+                    continue;
+                };
+                if !is_web_url(&url) {
+                    continue;
+                }
+                bookmarks.push(Bookmark {
+                    title: decode_entities(text.trim()),
+                    url: url,
+                    folders: folders.clone(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    bookmarks
+}
+
+/// 书签文件可能来自他人，只导入网页的网址
+fn is_web_url(url: &str) -> bool {
+    ALLOWED_SCHEMES.iter().any(|scheme| {
+        url.get(..scheme.len()).is_some_and(|prefix| prefix.eq_ignore_ascii_case(scheme))
+    })
+}
+
+/// 读取标签中用双引号包裹的属性，属性名不区分大小写
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    // 只改变 ASCII 字符，位置与原文相同
+    let upper = tag.to_ascii_uppercase();
+    let pattern = format!(" {}=\"", name.to_ascii_uppercase());
+    let start = upper.find(&pattern)? + pattern.len();
+    let end = tag[start..].find('"')? + start;
+    Some(tag[start..end].to_string())
+}
+
+/// 还原 HTML 实体
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest[1..].find(';').map(|end| &rest[1..end + 1]);
+        let character = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|decimal| decimal.parse().ok()))
+                .and_then(char::from_u32),
+        });
+
+        match (entity, character) {
+            (Some(entity), Some(character)) => {
+                decoded.push(character);
+                rest = &rest[entity.len() + 2..];
+            }
+            // 不认识的实体原样保留
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOOKMARKS: &str = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<META HTTP-EQUIV="Content-Type" CONTENT="text/html; charset=UTF-8">
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks</H1>
+<DL><p>
+    <DT><H3 ADD_DATE="1700000000">工具 &amp; 文档</H3>
+    <DL><p>
+        <DT><A HREF="https://doc.rust-lang.org/" ADD_DATE="1700000000">Rust 文档</A>
+        <DT><H3>搜索</H3>
+        <DL><p>
+            <DT><A href="https://www.google.com/search?q=a&amp;hl=en">Google</A>
+        </DL><p>
+    </DL><p>
+    <DT><A HREF="HTTP://EXAMPLE.COM/"></A>
+    <DT><A HREF="file:///C:/Windows/System32/calc.exe">计算器</A>
+    <DT><A HREF="javascript:alert(1)">脚本</A>
+    <DT><A HREF="ftp://example.com/">FTP</A>
+    <DT><A>没有网址</A>
+</DL><p>
+"#;
+
+    #[test]
+    fn parses_folders_as_tags() {
+        let bookmarks = parse_bookmarks(BOOKMARKS);
+        let urls: Vec<&str> = bookmarks.iter().map(|bookmark| bookmark.url.as_str()).collect();
+        assert_eq!(urls, vec![
+            "https://doc.rust-lang.org/",
+            "https://www.google.com/search?q=a&hl=en",
+            "HTTP://EXAMPLE.COM/",
+        ]);
+
+        assert_eq!(bookmarks[0].title, "Rust 文档");
+        assert_eq!(bookmarks[0].folders, vec!["工具 & 文档".to_string()]);
+        assert_eq!(bookmarks[1].folders, vec!["工具 & 文档".to_string(), "搜索".to_string()]);
+        // 离开文件夹后不再带有标签
+        assert!(bookmarks[2].folders.is_empty());
+    }
+
+    #[test]
+    fn only_imports_web_urls() {
+        let bookmarks = parse_bookmarks(BOOKMARKS);
+        assert!(bookmarks.iter().all(|bookmark| is_web_url(&bookmark.url)));
+        assert!(!is_web_url("file:///C:/Windows/System32/calc.exe"));
+        assert!(!is_web_url("javascript:alert(1)"));
+        assert!(!is_web_url("http:"));
+    }
+
+    #[test]
+    fn untitled_bookmark_uses_url_as_name() {
+        let bookmarks = parse_bookmarks(BOOKMARKS);
+        let program_link = bookmarks[2].to_program_link();
+        assert_eq!(program_link.name, "HTTP://EXAMPLE.COM/");
+        assert_eq!(program_link.run_command, "HTTP://EXAMPLE.COM/");
+    }
+
+    #[test]
+    fn decodes_entities() {
+        let cases = [
+            ("a &amp; b", "a & b"),
+            ("&lt;tag&gt;", "<tag>"),
+            ("&quot;x&quot; &apos;y&apos;", "\"x\" 'y'"),
+            ("&#65;&#x42;&#X43;", "ABC"),
+            ("&#x4E2D;&#25991;", "中文"),
+            ("a&nbsp;b", "a b"),
+            // 不认识或不完整的实体原样保留
+            ("&unknown; &amp", "&unknown; &amp"),
+            ("AT&T", "AT&T"),
+            ("&#xZZ;", "&#xZZ;"),
+            ("&#1114112;", "&#1114112;"),
+            ("&", "&"),
+            ("", ""),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(decode_entities(text), expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn reads_attributes_case_insensitively() {
+        assert_eq!(attribute(r#"A href="https://a/" ADD_DATE="1""#, "HREF"), Some("https://a/".to_string()));
+        assert_eq!(attribute(r#"A HREF="https://a/"#, "HREF"), None);
+        assert_eq!(attribute("A", "HREF"), None);
+    }
+}
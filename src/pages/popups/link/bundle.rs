@@ -0,0 +1,467 @@
+//! 快捷方式的导入导出包
+//!
+//! 导出包是一个 zip 文件，其中的 `bundle.json` 与 `links.json` 格式相同，
+//! 图标保存在 `icons/` 中，快捷方式的图标路径改为包内的相对路径。
+//! 确认导入后图标才会解压到缓存文件夹中，按内容的摘要命名，并改写为新的绝对路径。
+//! 导入的图标只能来自包内，其他图标路径会被清除
+
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use log::{debug, info, warn};
+use zip::write::SimpleFileOptions;
+
+use crate::my_structs::ProgramLink;
+use super::{migrate, save::LinkConfigSchema};
+
+
+/// 包中的配置文件
+const BUNDLE_FILE_NAME: &str = "bundle.json";
+/// 包中的图标文件夹
+const ICON_DIR_NAME: &str = "icons";
+/// 导入的图标在缓存文件夹中的位置
+const IMPORTED_ICON_DIR_NAME: &str = "bundle_icons";
+/// 包中配置文件的大小上限
+const MAX_BUNDLE_FILE_SIZE: u64 = 16 * 1024 * 1024;
+/// 包中单个图标的大小上限
+const MAX_ICON_SIZE: u64 = 4 * 1024 * 1024;
+
+
+/// 导入时对单个快捷方式的处理
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportAction {
+    Add,
+    Skip,
+    // 替换 uuid 或名称相同的快捷方式
+    Replace,
+    // 使用新的 uuid，名称重复时加上序号
+    KeepBoth,
+}
+
+impl ImportAction {
+    /// 有冲突时可以选择的处理方式
+    pub const CONFLICT: [ImportAction; 3] = [
+        ImportAction::KeepBoth,
+        ImportAction::Replace,
+        ImportAction::Skip,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ImportAction::Add => "导入",
+            ImportAction::Skip => "跳过",
+            ImportAction::Replace => "替换",
+            ImportAction::KeepBoth => "保留两者",
+        }
+    }
+}
+
+/// 导入的快捷方式与已有快捷方式的冲突
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportConflict {
+    None,
+    Uuid,
+    Name,
+}
+
+impl ImportConflict {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ImportConflict::None => "",
+            ImportConflict::Uuid => "已存在同一个快捷方式",
+            ImportConflict::Name => "已存在同名的快捷方式",
+        }
+    }
+}
+
+/// 待导入的快捷方式
+#[derive(Debug, Clone)]
+pub struct ImportEntry {
+    pub program_link: ProgramLink,
+    pub conflict: ImportConflict,
+    pub action: ImportAction,
+}
+
+/// 读取到的导入包
+#[derive(Debug, Clone, Default)]
+pub struct Bundle {
+    pub path: PathBuf,
+    pub entries: Vec<ImportEntry>,
+    pub tags: HashSet<String>,
+}
+
+
+/// 图标文件的扩展名，带有前面的 .
+fn icon_extension(icon_path: &str) -> String {
+    Path::new(icon_path)
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default()
+}
+
+/// 图标在包中的路径，用原路径的摘要避免重名
+fn bundle_icon_name(icon_path: &str) -> String {
+    format!("{}/{:x}{}", ICON_DIR_NAME, md5::compute(icon_path.as_bytes()), icon_extension(icon_path))
+}
+
+/// 导出快捷方式及其图标，返回导出的数量
+pub fn export_bundle(program_links: &[ProgramLink], path: &Path) -> Result<usize, std::io::Error> {
+    let mut writer = zip::ZipWriter::new(std::fs::File::create(path)?);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    let mut written_icons = HashSet::new();
+    let mut bundle = LinkConfigSchema::default();
+
+    for program_link in program_links {
+        let mut program_link = program_link.clone();
+        program_link.catalog = None;
+
+        if !program_link.icon_path.is_empty() {
+            match std::fs::read(&program_link.icon_path) {
+                Ok(icon) => {
+                    let icon_name = bundle_icon_name(&program_link.icon_path);
+                    if written_icons.insert(icon_name.clone()) {
+                        writer.start_file(icon_name.as_str(), options)?;
+                        writer.write_all(&icon)?;
+                    }
+                    program_link.icon_path = icon_name;
+                }
+                // 导入时只使用包内的图标，原路径没有意义
+                Err(e) => {
+                    warn!("无法读取图标 {}: {}", program_link.icon_path, e);
+                    program_link.icon_path.clear();
+                }
+            }
+        }
+
+        bundle.tags.extend(program_link.tags.iter().cloned());
+        bundle.program_links.push(program_link);
+    }
+
+    writer.start_file(BUNDLE_FILE_NAME, options)?;
+    writer.write_all(serde_json::to_string_pretty(&bundle)?.as_bytes())?;
+    writer.finish()?;
+
+    info!("导出 {} 个快捷方式到 {}", bundle.program_links.len(), path.display());
+    Ok(bundle.program_links.len())
+}
+
+
+/// 读取导入包并检查与已有快捷方式的冲突
+///
+/// 此时图标仍是包内的相对路径，确认导入时才会解压
+pub fn read_bundle(path: &Path, existing: &[ProgramLink]) -> Result<Bundle, std::io::Error> {
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(path)?)?;
+
+    let mut contents = String::new();
+    let bundle_file = archive.by_name(BUNDLE_FILE_NAME)?;
+    if bundle_file.size() > MAX_BUNDLE_FILE_SIZE {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{} 过大", BUNDLE_FILE_NAME)));
+    }
+    bundle_file.take(MAX_BUNDLE_FILE_SIZE).read_to_string(&mut contents)?;
+    let mut links_config: serde_json::Value = serde_json::from_str(&contents)?;
+    migrate::migrate(&mut links_config);
+    let bundle: LinkConfigSchema = serde_json::from_value(links_config)?;
+
+    let entries = bundle.program_links
+        .into_iter()
+        .map(|program_link| {
+            let conflict = if existing.iter().any(|link| link.uuid == program_link.uuid) {
+                ImportConflict::Uuid
+            } else if existing.iter().any(|link| link.name == program_link.name) {
+                ImportConflict::Name
+            } else {
+                ImportConflict::None
+            };
+
+            ImportEntry {
+                program_link: program_link,
+                conflict: conflict,
+                action: match conflict {
+                    ImportConflict::None => ImportAction::Add,
+                    _ => ImportAction::KeepBoth,
+                },
+            }
+        })
+        .collect();
+
+    Ok(Bundle {
+        path: path.to_path_buf(),
+        entries: entries,
+        tags: bundle.tags,
+    })
+}
+
+/// 将快捷方式用到的图标解压到缓存文件夹中，返回包内路径与解压后路径的对应关系
+///
+/// 解压后的文件按内容的摘要命名，不同导入包中的图标不会互相覆盖，相同的图标只保存一份。
+/// 包中缺少或过大的图标会被跳过
+fn extract_icons(path: &Path, program_links: &[&ProgramLink], icon_dir: &Path) -> Result<HashMap<String, PathBuf>, std::io::Error> {
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(path)?)?;
+    std::fs::create_dir_all(icon_dir)?;
+
+    let mut icons = HashMap::new();
+    for program_link in program_links {
+        let icon_name = &program_link.icon_path;
+        if !icon_name.starts_with(ICON_DIR_NAME) || icons.contains_key(icon_name) {
+            continue;
+        }
+        let Ok(icon_file) = archive.by_name(icon_name) else {
+            warn!("导入包中缺少图标 {}", icon_name);
+            continue;
+        };
+        if icon_file.size() > MAX_ICON_SIZE {
+            warn!("导入包中的图标 {} 过大", icon_name);
+            continue;
+        }
+
+        let mut icon = Vec::new();
+        icon_file.take(MAX_ICON_SIZE).read_to_end(&mut icon)?;
+        // 只保留扩展名，不信任包中的路径
+        let target = icon_dir.join(format!("{:x}{}", md5::compute(&icon), icon_extension(icon_name)));
+        if !target.exists() {
+            std::fs::write(&target, icon)?;
+        }
+
+        debug!("解压图标 {} 到 {}", icon_name, target.display());
+        icons.insert(icon_name.clone(), target);
+    }
+    Ok(icons)
+}
+
+
+/// 在已有名称后加上序号，直到不重复
+fn unique_name(name: &str, program_links: &[ProgramLink]) -> String {
+    (2..)
+        .map(|index| format!("{} ({})", name, index))
+        .find(|candidate| program_links.iter().all(|link| &link.name != candidate))
+        .unwrap()
+}
+
+/// 按每个快捷方式选择的处理方式合并到已有的快捷方式中，返回导入的数量
+///
+/// 先解压要导入的快捷方式用到的图标，解压失败时不会修改已有的快捷方式。
+/// 只会加入导入的快捷方式用到的标签
+pub fn merge_bundle(bundle: Bundle, program_links: &mut Vec<ProgramLink>, tags: &mut HashSet<String>) -> Result<usize, std::io::Error> {
+    let icon_dir = crate::paths::cache_dir().join(IMPORTED_ICON_DIR_NAME);
+    merge_bundle_in(&icon_dir, bundle, program_links, tags)
+}
+
+fn merge_bundle_in(icon_dir: &Path, bundle: Bundle, program_links: &mut Vec<ProgramLink>, tags: &mut HashSet<String>) -> Result<usize, std::io::Error> {
+    let imported_links: Vec<&ProgramLink> = bundle.entries
+        .iter()
+        .filter(|entry| entry.action != ImportAction::Skip)
+        .map(|entry| &entry.program_link)
+        .collect();
+    let icons = extract_icons(&bundle.path, &imported_links, icon_dir)?;
+
+    let mut imported = 0;
+    for entry in bundle.entries {
+        let mut program_link = entry.program_link;
+        // 不使用包外的图标路径，删除快捷方式时会清理它的图标
+        program_link.icon_path = match icons.get(&program_link.icon_path) {
+            Some(icon_path) => icon_path.display().to_string(),
+            None => String::new(),
+        };
+        let link_tags: Vec<String> = program_link.tags.intersection(&bundle.tags).cloned().collect();
+
+        match entry.action {
+            ImportAction::Skip => continue,
+            ImportAction::Add => program_links.push(program_link),
+            ImportAction::Replace => {
+                let position = match entry.conflict {
+                    ImportConflict::Name => program_links.iter().position(|link| link.name == program_link.name),
+                    _ => program_links.iter().position(|link| link.uuid == program_link.uuid),
+                };
+                match position {
+                    Some(position) => {
+                        // 保留原有的 uuid，共享目录中的快捷方式会成为覆盖
+                        program_link.uuid = program_links[position].uuid.clone();
+                        program_links[position] = program_link;
+                    }
+                    None => program_links.push(program_link),
+                }
+            }
+            ImportAction::KeepBoth => {
+                if program_links.iter().any(|link| link.uuid == program_link.uuid) {
+                    program_link.uuid = uuid::Uuid::new_v4().to_string();
+                }
+                if program_links.iter().any(|link| link.name == program_link.name) {
+                    program_link.name = unique_name(&program_link.name, program_links);
+                }
+                program_links.push(program_link);
+            }
+        }
+        tags.extend(link_tags);
+        imported += 1;
+    }
+
+    info!("从 {} 导入 {} 个快捷方式", bundle.path.display(), imported);
+    Ok(imported)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("baroboard-bundle-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn link(uuid: &str, name: &str, run_command: &str, tags: &[&str], icon_path: &str) -> ProgramLink {
+        ProgramLink {
+            name: name.to_string(),
+            run_command: run_command.to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            icon_path: icon_path.to_string(),
+            uuid: uuid.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn set(items: &[&str]) -> HashSet<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    fn existing() -> Vec<ProgramLink> {
+        vec![
+            link("a", "浏览器", "old", &["旧"], ""),
+            link("b", "编辑器", "old", &["旧"], ""),
+        ]
+    }
+
+    /// 导出三个快捷方式：与 a 的 uuid 冲突、与 b 的名称冲突、没有冲突
+    fn export(root: &Path) -> PathBuf {
+        let icon = root.join("icon.png");
+        std::fs::write(&icon, "png").unwrap();
+        let program_links = [
+            link("a", "浏览器", "new", &["常用"], &icon.display().to_string()),
+            link("n", "编辑器", "new", &["工具"], ""),
+            link("x", "新程序", "new", &[], &root.join("missing.png").display().to_string()),
+        ];
+
+        let path = root.join("links.zip");
+        assert_eq!(export_bundle(&program_links, &path).unwrap(), 3);
+        path
+    }
+
+    #[test]
+    fn reads_conflicts() {
+        let root = temp_dir();
+        let bundle = read_bundle(&export(&root), &existing()).unwrap();
+
+        let conflicts: Vec<_> = bundle.entries.iter().map(|entry| (entry.conflict, entry.action)).collect();
+        assert_eq!(conflicts, [
+            (ImportConflict::Uuid, ImportAction::KeepBoth),
+            (ImportConflict::Name, ImportAction::KeepBoth),
+            (ImportConflict::None, ImportAction::Add),
+        ]);
+        assert_eq!(bundle.tags, set(&["常用", "工具"]));
+        // 图标改为包内的路径，无法读取的图标不会导出
+        assert!(bundle.entries[0].program_link.icon_path.starts_with(ICON_DIR_NAME));
+        assert_eq!(bundle.entries[2].program_link.icon_path, "");
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn resolves_conflicts() {
+        use ImportAction::*;
+
+        // (两个冲突的快捷方式的处理方式, 合并后的名称与命令, 合并后的标签)
+        let cases: &[([ImportAction; 2], &[(&str, &str)], &[&str])] = &[
+            (
+                [Skip, Skip],
+                &[("浏览器", "old"), ("编辑器", "old"), ("新程序", "new")],
+                &["旧"],
+            ),
+            (
+                [Replace, Replace],
+                &[("浏览器", "new"), ("编辑器", "new"), ("新程序", "new")],
+                &["旧", "常用", "工具"],
+            ),
+            (
+                [KeepBoth, KeepBoth],
+                &[("浏览器", "old"), ("编辑器", "old"), ("浏览器 (2)", "new"), ("编辑器 (2)", "new"), ("新程序", "new")],
+                &["旧", "常用", "工具"],
+            ),
+            // 跳过的快捷方式的标签不会加入
+            (
+                [Skip, Replace],
+                &[("浏览器", "old"), ("编辑器", "new"), ("新程序", "new")],
+                &["旧", "工具"],
+            ),
+        ];
+
+        for (actions, expected_links, expected_tags) in cases {
+            let root = temp_dir();
+            let mut bundle = read_bundle(&export(&root), &existing()).unwrap();
+            for (entry, action) in bundle.entries.iter_mut().zip(actions) {
+                entry.action = *action;
+            }
+
+            let mut program_links = existing();
+            let mut tags = set(&["旧"]);
+            let imported = merge_bundle_in(&root.join("imported"), bundle, &mut program_links, &mut tags).unwrap();
+
+            let links: Vec<_> = program_links.iter().map(|link| (link.name.as_str(), link.run_command.as_str())).collect();
+            assert_eq!(links, *expected_links, "{:?}", actions);
+            assert_eq!(tags, set(expected_tags), "{:?}", actions);
+            assert_eq!(imported, actions.iter().filter(|action| **action != Skip).count() + 1);
+
+            // uuid 不重复，替换时保留原有的 uuid
+            let uuids: HashSet<_> = program_links.iter().map(|link| link.uuid.as_str()).collect();
+            assert_eq!(uuids.len(), program_links.len(), "{:?}", actions);
+            assert!(uuids.contains("a") && uuids.contains("b"), "{:?}", actions);
+
+            std::fs::remove_dir_all(root).unwrap();
+        }
+    }
+
+    #[test]
+    fn extracts_only_bundled_icons() {
+        let root = temp_dir();
+        let path = root.join("links.zip");
+
+        let icon_paths = [
+            "icons/ok.png",
+            "icons/ok.png",
+            "icons/missing.png",
+            "icons/../../outside.png",
+            "/etc/hosts",
+            "C:\\Windows\\System32\\shell32.dll",
+            "",
+        ];
+        let bundle = LinkConfigSchema {
+            program_links: icon_paths.iter().enumerate()
+                .map(|(index, icon_path)| link(&index.to_string(), &index.to_string(), "new", &[], icon_path))
+                .collect(),
+            ..Default::default()
+        };
+        let mut writer = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        writer.start_file("icons/ok.png", SimpleFileOptions::default()).unwrap();
+        writer.write_all(b"png").unwrap();
+        writer.start_file(BUNDLE_FILE_NAME, SimpleFileOptions::default()).unwrap();
+        writer.write_all(serde_json::to_string(&bundle).unwrap().as_bytes()).unwrap();
+        writer.finish().unwrap();
+
+        let icon_dir = root.join("imported");
+        let mut program_links = Vec::new();
+        let bundle = read_bundle(&path, &program_links).unwrap();
+        merge_bundle_in(&icon_dir, bundle, &mut program_links, &mut HashSet::new()).unwrap();
+
+        // 相同的图标只保存一份
+        let extracted = icon_dir.join(format!("{:x}.png", md5::compute(b"png")));
+        assert_eq!(std::fs::read(&extracted).unwrap(), b"png");
+        assert_eq!(std::fs::read_dir(&icon_dir).unwrap().count(), 1);
+
+        let icons: Vec<_> = program_links.iter().map(|link| link.icon_path.clone()).collect();
+        let extracted = extracted.display().to_string();
+        assert_eq!(icons, [extracted.as_str(), extracted.as_str(), "", "", "", "", ""]);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
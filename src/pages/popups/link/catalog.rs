@@ -0,0 +1,240 @@
+//! 共享的快捷方式目录
+//!
+//! 快捷方式按层叠加，从下到上依次为：系统范围的 `catalog.json`、设置中的 `catalogs`、
+//! 配置文件夹中的 `catalog.d`，最上层为用户自己的 `links.json`。
+//! 每一层可以是单个配置文件，也可以是包含多个配置文件的文件夹，格式与 `links.json` 相同。
+//!
+//! 下层的快捷方式是只读的，用户层中 uuid 相同的快捷方式会覆盖它，也可以在用户层中隐藏。
+//! 保存时只会写入用户层
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use log::{debug, info, warn};
+
+use crate::my_structs::ProgramLink;
+use super::{migrate, save, watch::LinkSnapshot};
+
+
+/// 配置文件夹中的共享目录
+const CATALOG_DIR_NAME: &str = "catalog.d";
+/// 系统范围的共享目录文件
+const SYSTEM_CATALOG_FILE_NAME: &str = "catalog.json";
+
+
+/// 所有只读层合并后的快捷方式与标签
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    pub program_links: Vec<ProgramLink>,
+    pub tags: HashSet<String>,
+}
+
+impl Catalog {
+    pub fn find(&self, uuid: &str) -> Option<&ProgramLink> {
+        self.program_links.iter().find(|link| link.uuid == uuid)
+    }
+
+    pub fn contains(&self, uuid: &str) -> bool {
+        self.find(uuid).is_some()
+    }
+
+    /// 在只读层上叠加用户层，返回显示的快捷方式与所有标签
+    ///
+    /// 共享的快捷方式在前并保持原有顺序，被覆盖的快捷方式使用用户层的版本，其余用户的快捷方式在后
+    pub fn compose(&self, user: &LinkSnapshot) -> (Vec<ProgramLink>, HashSet<String>) {
+        let mut program_links = Vec::new();
+
+        for catalog_link in self.program_links.iter() {
+            if user.hidden.contains(&catalog_link.uuid) {
+                continue;
+            }
+            match user.program_links.iter().find(|link| link.uuid == catalog_link.uuid) {
+                Some(user_link) => program_links.push(user_link.clone()),
+                None => program_links.push(catalog_link.clone()),
+            }
+        }
+
+        for user_link in user.program_links.iter() {
+            if !self.contains(&user_link.uuid) {
+                program_links.push(user_link.clone());
+            }
+        }
+
+        let tags = self.tags.union(&user.tags).cloned().collect();
+        (program_links, tags)
+    }
+}
+
+
+#[cfg(target_os = "windows")]
+fn system_catalog_dir() -> Option<PathBuf> {
+    std::env::var_os("PROGRAMDATA").map(|dir| PathBuf::from(dir).join("BaroBoard"))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn system_catalog_dir() -> Option<PathBuf> {
+    Some(PathBuf::from("/etc/xdg/BaroBoard"))
+}
+
+/// 所有只读层，从下到上排列
+pub fn catalog_sources(extra: &[String]) -> Vec<PathBuf> {
+    let mut sources = Vec::new();
+    if let Some(dir) = system_catalog_dir() {
+        sources.push(dir.join(SYSTEM_CATALOG_FILE_NAME));
+    }
+    sources.extend(extra.iter().filter(|path| !path.trim().is_empty()).map(PathBuf::from));
+    sources.push(crate::paths::config_dir().join(CATALOG_DIR_NAME));
+    sources
+}
+
+/// 一层中的所有配置文件，文件夹中的文件按文件名排序
+fn fragment_files(source: &Path) -> Vec<PathBuf> {
+    if source.is_file() {
+        return vec![source.to_path_buf()];
+    }
+
+    let Ok(entries) = std::fs::read_dir(source) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|extension| extension == "json"))
+        .collect();
+    files.sort();
+    files
+}
+
+/// 读取一个只读的配置文件，旧版本的配置只在内存中迁移
+fn load_fragment(path: &Path) -> Result<save::LinkConfigSchema, std::io::Error> {
+    let mut links_config = save::load_conf(path)?;
+    migrate::migrate(&mut links_config);
+    Ok(serde_json::from_value(links_config)?)
+}
+
+/// 读取所有只读层，无法读取的文件会被跳过
+pub fn load_catalog(extra: &[String]) -> Catalog {
+    load_catalog_from(&catalog_sources(extra))
+}
+
+fn load_catalog_from(sources: &[PathBuf]) -> Catalog {
+    let mut catalog = Catalog::default();
+
+    for path in sources.iter().flat_map(|source| fragment_files(source)) {
+        let fragment = match load_fragment(&path) {
+            Ok(fragment) => fragment,
+            Err(e) => {
+                warn!("无法读取共享目录 {}: {}", path.display(), e);
+                continue;
+            }
+        };
+
+        let name = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        debug!("共享目录 {} 中有 {} 个快捷方式", path.display(), fragment.program_links.len());
+
+        catalog.tags.extend(fragment.tags);
+        for mut program_link in fragment.program_links {
+            program_link.catalog = Some(name.clone());
+            // 上层的同一个快捷方式覆盖下层
+            match catalog.program_links.iter_mut().find(|link| link.uuid == program_link.uuid) {
+                Some(link) => *link = program_link,
+                None => catalog.program_links.push(program_link),
+            }
+        }
+    }
+
+    if !catalog.program_links.is_empty() {
+        info!("从共享目录读取了 {} 个快捷方式", catalog.program_links.len());
+    }
+    catalog
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(uuid: &str, name: &str) -> ProgramLink {
+        ProgramLink {
+            name: name.to_string(),
+            uuid: uuid.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn set(items: &[&str]) -> HashSet<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    fn names(program_links: &[ProgramLink]) -> Vec<&str> {
+        program_links.iter().map(|link| link.name.as_str()).collect()
+    }
+
+    fn write_fragment(path: &Path, links: &[(&str, &str)], tags: &[&str]) {
+        let fragment = save::LinkConfigSchema {
+            program_links: links.iter().map(|(uuid, name)| link(uuid, name)).collect(),
+            tags: set(tags),
+            ..Default::default()
+        };
+        std::fs::write(path, serde_json::to_string(&fragment).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn composes_user_layer() {
+        let catalog = Catalog {
+            program_links: vec![link("a", "共享A"), link("b", "共享B"), link("c", "共享C")],
+            tags: set(&["共享"]),
+        };
+
+        // (用户层的快捷方式, 隐藏的 uuid, 显示的快捷方式)
+        let cases: &[(&[(&str, &str)], &[&str], &[&str])] = &[
+            // 共享的快捷方式在前
+            (&[("x", "我的X"), ("y", "我的Y")], &[], &["共享A", "共享B", "共享C", "我的X", "我的Y"]),
+            // 覆盖后保持共享目录中的位置
+            (&[("x", "我的X"), ("b", "我的B")], &[], &["共享A", "我的B", "共享C", "我的X"]),
+            // 隐藏共享的快捷方式
+            (&[("x", "我的X")], &["a", "c"], &["共享B", "我的X"]),
+            // 隐藏优先于覆盖
+            (&[("b", "我的B")], &["b"], &["共享A", "共享C"]),
+            // 不存在的 uuid 不影响显示
+            (&[], &["z"], &["共享A", "共享B", "共享C"]),
+        ];
+        for (links, hidden, expected) in cases {
+            let user = LinkSnapshot::new(
+                links.iter().map(|(uuid, name)| link(uuid, name)).collect(),
+                set(&["我的"]),
+                set(hidden),
+                Vec::new(),
+            );
+            let (program_links, tags) = catalog.compose(&user);
+            assert_eq!(names(&program_links), *expected, "{:?} {:?}", links, hidden);
+            assert_eq!(tags, set(&["共享", "我的"]));
+        }
+    }
+
+    #[test]
+    fn later_sources_override_earlier() {
+        let root = std::env::temp_dir().join(format!("baroboard-catalog-{}", uuid::Uuid::new_v4()));
+        let dir = root.join(CATALOG_DIR_NAME);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let system = root.join(SYSTEM_CATALOG_FILE_NAME);
+        write_fragment(&system, &[("a", "系统A"), ("b", "系统B")], &["系统"]);
+        // 文件夹中的文件按文件名排序
+        write_fragment(&dir.join("20-team.json"), &[("b", "团队B2")], &[]);
+        write_fragment(&dir.join("10-team.json"), &[("b", "团队B"), ("c", "团队C")], &["团队"]);
+        // 无法读取的文件与其他扩展名的文件会被跳过
+        std::fs::write(dir.join("broken.json"), "{").unwrap();
+        write_fragment(&dir.join("notes.txt"), &[("d", "笔记")], &[]);
+
+        let sources = [system, root.join("missing.json"), dir];
+        let catalog = load_catalog_from(&sources);
+        assert_eq!(names(&catalog.program_links), ["系统A", "团队B2", "团队C"]);
+        assert_eq!(catalog.tags, set(&["系统", "团队"]));
+
+        // 记录来自哪个文件
+        let sources: Vec<_> = catalog.program_links.iter().map(|link| link.catalog.as_deref()).collect();
+        assert_eq!(sources, [Some("catalog"), Some("20-team"), Some("10-team")]);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -1,9 +1,10 @@
 use egui;
 use std::collections::HashSet;
 use rfd;
-use log::debug;
+use log::{debug, warn};
 
 use crate::my_structs::*;
+use crate::settings::LaunchSettings;
 
 /// 表示参数在列表中的索引位置
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -14,10 +15,12 @@ struct ArgumentIndex(usize);
 pub struct LinkConfig {
     is_new_link: bool,
     
-    index_of_the_link: usize,
+    // 正在编辑的快捷方式，显示的列表经过筛选，不能使用索引
+    uuid_of_the_link: String,
 
     // 临时变量们
     pub name: String,
+    pub keywords: Vec<String>,
     pub icon_path: Option<String>,
     pub run_command: String,
     pub arguments: Vec<String>,
@@ -35,8 +38,9 @@ impl LinkConfig {
     pub fn new() -> Self {
         Self {
             is_new_link: false,
-            index_of_the_link: 0,
+            uuid_of_the_link: "".to_string(),
             name: "".to_string(),
+            keywords: Vec::new(),
             icon_path: None,
             run_command: "".to_string(),
             arguments: Vec::new(),
@@ -51,11 +55,12 @@ impl LinkConfig {
     }
 
 
-    pub fn config_existing_link(&mut self, position: LinkPosition, link: &ProgramLink) {
+    pub fn config_existing_link(&mut self, link: &ProgramLink) {
         self.is_new_link = false;
-        self.index_of_the_link = position.link_index;
+        self.uuid_of_the_link = link.uuid.clone();
 
-        self.name = link.name.clone().join("/");
+        self.name = link.name.clone();
+        self.keywords = link.keywords.clone();
         self.icon_path = Some(link.icon_path.clone());
         self.run_command = link.run_command.clone();
         self.arguments = link.arguments.clone();
@@ -65,9 +70,21 @@ impl LinkConfig {
     }
 
     
-    pub fn config_new_link(&mut self) {
+    /// 新建快捷方式，高级选项使用设置中的默认值
+    pub fn config_new_link(&mut self, defaults: &LaunchSettings) {
         *self = Self::new();
         self.is_new_link = true;
+        self.is_admin = defaults.admin;
+        self.is_new_window = defaults.new_window;
+    }
+
+    /// 去掉空白的别名
+    fn filled_keywords(&self) -> Vec<String> {
+        self.keywords
+            .iter()
+            .map(|keyword| keyword.trim().to_string())
+            .filter(|keyword| !keyword.is_empty())
+            .collect()
     }
 }
 
@@ -87,7 +104,7 @@ impl MyApp {
         })
         .collapsible(false)
         .resizable(false)
-        .default_pos(egui::pos2(crate::WINDOW_SIZE.0 / 2.0, crate::WINDOW_SIZE.1 / 2.0))
+        .default_pos((self.settings.window.size() / 2.0).to_pos2())
         // .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
         
         .fade_in(true)
@@ -138,10 +155,35 @@ impl MyApp {
 
             ui.horizontal(|ui| {
                 ui.label("名称");
-                ui.add(egui::TextEdit::singleline(&mut self.popups.link_config.name).hint_text("e.g. 记事本/notepad"));
+                ui.add(egui::TextEdit::singleline(&mut self.popups.link_config.name).hint_text("e.g. 记事本"));
                 
             });
 
+            ui.collapsing(if self.popups.link_config.keywords.is_empty() {
+                "别名".to_string()
+            } else {
+                format!("别名 ({})", self.popups.link_config.keywords.len())
+            }, |ui| {
+                let mut index_should_remove: Option<usize> = None;
+
+                for (index, keyword) in self.popups.link_config.keywords.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(keyword).hint_text("e.g. notepad"));
+                        if ui.button("➖").clicked() {
+                            index_should_remove = Some(index);
+                        }
+                    });
+                }
+
+                if let Some(index) = index_should_remove {
+                    self.popups.link_config.keywords.remove(index);
+                }
+
+                if ui.button("➕ 添加别名").clicked() {
+                    self.popups.link_config.keywords.push("".to_string());
+                }
+            });
+
             ui.horizontal(|ui| {
                 ui.label("命令");
                 ui.add(
@@ -164,7 +206,7 @@ impl MyApp {
             egui::Window::new("参数配置")
             .collapsible(false)
             .resizable(false)
-            .default_pos(egui::pos2(crate::WINDOW_SIZE.0 / 2.0, crate::WINDOW_SIZE.1 / 2.0))
+            .default_pos((self.settings.window.size() / 2.0).to_pos2())
             .open(&mut self.popups.link_config.show_args_config)
             .show(ui.ctx(), |ui| {
                 let mut has_empty_argument = false;
@@ -292,7 +334,7 @@ impl MyApp {
             });
 
             ui.label(
-                egui::RichText::new("tip: 名称可以使用 / 来创建别名，也可以只输入一个名称。右键命令输入框可以打开路径选择器")
+                egui::RichText::new("tip: 别名只用于搜索，不会显示。右键命令输入框可以打开路径选择器")
                     .weak()
             );
 
@@ -350,7 +392,7 @@ impl MyApp {
             egui::Window::new("高级选项")
             .collapsible(false)
             .resizable(false)
-            .default_pos(egui::pos2(crate::WINDOW_SIZE.0 / 2.0, crate::WINDOW_SIZE.1 / 2.0))
+            .default_pos((self.settings.window.size() / 2.0).to_pos2())
             .open(&mut self.popups.link_config.show_advanced_config)
             .max_width(256.)
             .show(ui.ctx(), |ui| {
@@ -429,17 +471,17 @@ impl MyApp {
                         
                         if clicked {
                             // 创建不需要清除之前的图片缓存
-                            self.program_links.push(
-                                ProgramLink::new(
-                                    self.popups.link_config.name.clone().split("/").map(|s| s.to_string()).collect(),
-                                    self.popups.link_config.icon_path.clone().unwrap_or("".to_string()),
-                                    self.popups.link_config.run_command.clone(),
-                                    self.popups.link_config.arguments.clone(),
-                                    self.popups.link_config.tags.clone().into_iter().collect(),
-                                    self.popups.link_config.is_admin,
-                                    self.popups.link_config.is_new_window
-                                )
+                            let mut program_link = ProgramLink::new(
+                                self.popups.link_config.name.clone(),
+                                self.popups.link_config.icon_path.clone().unwrap_or("".to_string()),
+                                self.popups.link_config.run_command.clone(),
+                                self.popups.link_config.arguments.clone(),
+                                self.popups.link_config.tags.clone().into_iter().collect(),
+                                self.popups.link_config.is_admin,
+                                self.popups.link_config.is_new_window
                             );
+                            program_link.keywords = self.popups.link_config.filled_keywords();
+                            self.program_links.push(program_link);
                             
                             should_save = true;
                             should_close = true;
@@ -448,7 +490,13 @@ impl MyApp {
                     
                 } else {
                     if ui.button("保存").clicked() {
-                        let current_link = &mut self.program_links[self.popups.link_config.index_of_the_link];
+                        let uuid = &self.popups.link_config.uuid_of_the_link;
+                        // 编辑期间快捷方式可能已经被外部修改删除
+                        let Some(current_link) = self.program_links.iter_mut().find(|link| &link.uuid == uuid) else {
+                            warn!("要保存的快捷方式已不存在");
+                            should_close = true;
+                            return;
+                        };
                         // 尝试移除之前的缓存标记
                         // 我的意思是原本的快捷方式图片而不是设置中的预览
                         if let Some(icon_path) = self.cached_icon.get_mut(&current_link.icon_path) {
@@ -461,13 +509,16 @@ impl MyApp {
                         // 如果缓存为空，则删除缓存
                         self.icon_will_clean.push(current_link.icon_path.clone());
 
-                        current_link.name = self.popups.link_config.name.clone().split("/").map(|s| s.to_string()).collect();
+                        current_link.name = self.popups.link_config.name.clone();
+                        current_link.keywords = self.popups.link_config.filled_keywords();
                         current_link.icon_path = self.popups.link_config.icon_path.clone().unwrap_or("".to_string());
                         current_link.run_command = self.popups.link_config.run_command.clone();
                         current_link.arguments = self.popups.link_config.arguments.clone();
                         current_link.tags = self.popups.link_config.tags.clone().into_iter().collect();
                         current_link.is_admin = self.popups.link_config.is_admin;
                         current_link.is_new_window = self.popups.link_config.is_new_window;
+                        // 修改共享目录中的快捷方式会在用户层中覆盖它
+                        current_link.catalog = None;
 
 
                         should_save = true;
@@ -0,0 +1,359 @@
+//! 将快捷方式导出为其他启动方式
+//!
+//! 支持 freedesktop 的 `.desktop` 启动器、bash / zsh 与 fish 的别名文件，以及 PowerShell 配置文件片段。
+//! 命令都由 `run_command` 与 `arguments` 组成，每个参数按目标格式的规则单独加引号
+
+use std::collections::HashSet;
+use std::path::Path;
+use log::info;
+
+use crate::my_structs::ProgramLink;
+use crate::search::index::to_pinyin;
+use crate::search::provider::is_url;
+
+
+/// 导出的格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LauncherFormat {
+    Desktop,
+    PosixAliases,
+    FishAliases,
+    PowerShell,
+}
+
+impl LauncherFormat {
+    pub const ALL: [LauncherFormat; 4] = [
+        LauncherFormat::Desktop,
+        LauncherFormat::PosixAliases,
+        LauncherFormat::FishAliases,
+        LauncherFormat::PowerShell,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LauncherFormat::Desktop => ".desktop 启动器",
+            LauncherFormat::PosixAliases => "Bash / Zsh 别名",
+            LauncherFormat::FishAliases => "Fish 别名",
+            LauncherFormat::PowerShell => "PowerShell 配置片段",
+        }
+    }
+
+    /// 保存时默认的文件名，`.desktop` 启动器会保存到文件夹中
+    pub fn file_name(&self) -> Option<&'static str> {
+        match self {
+            LauncherFormat::Desktop => None,
+            LauncherFormat::PosixAliases => Some("baroboard_aliases.sh"),
+            LauncherFormat::FishAliases => Some("baroboard_aliases.fish"),
+            LauncherFormat::PowerShell => Some("baroboard_profile.ps1"),
+        }
+    }
+}
+
+
+/// 导出到文件或文件夹，返回导出的快捷方式数量
+pub fn export_launchers(format: LauncherFormat, program_links: &[ProgramLink], path: &Path) -> Result<usize, std::io::Error> {
+    let program_links: Vec<&ProgramLink> = program_links
+        .iter()
+        .filter(|link| !link.run_command.trim().is_empty())
+        .collect();
+    let names = alias_names(&program_links);
+
+    match format {
+        LauncherFormat::Desktop => {
+            std::fs::create_dir_all(path)?;
+            for (program_link, name) in program_links.iter().zip(names.iter()) {
+                let file = path.join(format!("baroboard-{}.desktop", name));
+                std::fs::write(file, desktop_entry(program_link))?;
+            }
+        }
+        LauncherFormat::PosixAliases => std::fs::write(path, posix_aliases(&program_links, &names))?,
+        LauncherFormat::FishAliases => std::fs::write(path, fish_aliases(&program_links, &names))?,
+        LauncherFormat::PowerShell => std::fs::write(path, powershell_functions(&program_links, &names))?,
+    }
+
+    info!("以 {} 格式导出 {} 个快捷方式到 {}", format.label(), program_links.len(), path.display());
+    Ok(program_links.len())
+}
+
+
+/// 命令与参数
+fn argv(program_link: &ProgramLink) -> Vec<&str> {
+    std::iter::once(program_link.run_command.as_str())
+        .chain(program_link.arguments.iter().map(|argument| argument.as_str()))
+        .collect()
+}
+
+/// 别名只使用小写字母、数字、- 与 _，汉字会转为拼音
+fn sanitize_alias(name: &str) -> String {
+    let mut alias = String::new();
+    for c in to_pinyin(name, false).to_lowercase().chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            alias.push(c);
+        } else if !alias.is_empty() && !alias.ends_with('-') {
+            alias.push('-');
+        }
+    }
+    alias.trim_end_matches('-').to_string()
+}
+
+/// 每个快捷方式的别名，优先使用第一个别名，其次是名称，重复时加上序号
+fn alias_names(program_links: &[&ProgramLink]) -> Vec<String> {
+    let mut used = HashSet::new();
+
+    program_links.iter().map(|program_link| {
+        let base = program_link.keywords
+            .iter()
+            .chain(std::iter::once(&program_link.name))
+            .map(|name| sanitize_alias(name))
+            .find(|alias| !alias.is_empty())
+            .unwrap_or_else(|| "link".to_string());
+
+        let alias = std::iter::once(base.clone())
+            .chain((2..).map(|index| format!("{}-{}", base, index)))
+            .find(|alias| !used.contains(alias))
+            .unwrap();
+        used.insert(alias.clone());
+        alias
+    }).collect()
+}
+
+
+/// `.desktop` 文件中字符串的转义
+fn desktop_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t")
+        .replace('\r', "\\r")
+}
+
+/// `Exec` 中的单个参数，包含保留字符时用双引号包裹
+fn desktop_exec_arg(arg: &str) -> String {
+    const RESERVED: &[char] = &[
+        ' ', '\t', '\n', '"', '\'', '\\', '>', '<', '~', '|', '&', ';', '$', '*', '?', '#', '(', ')', '`',
+    ];
+
+    // % 是字段代码的前缀
+    let arg = arg.replace('%', "%%");
+    if !arg.is_empty() && !arg.contains(RESERVED) {
+        return arg;
+    }
+
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn desktop_entry(program_link: &ProgramLink) -> String {
+    let mut entry = String::from("[Desktop Entry]\n");
+
+    if is_url(&program_link.run_command) {
+        entry.push_str("Type=Link\n");
+        entry.push_str(&format!("URL={}\n", desktop_escape(&program_link.run_command)));
+    } else {
+        let exec = argv(program_link).iter().map(|arg| desktop_exec_arg(arg)).collect::<Vec<_>>().join(" ");
+        entry.push_str("Type=Application\n");
+        entry.push_str(&format!("Exec={}\n", desktop_escape(&exec)));
+        entry.push_str(&format!("Terminal={}\n", program_link.is_new_window));
+    }
+
+    entry.push_str(&format!("Name={}\n", desktop_escape(&program_link.name)));
+    if !program_link.icon_path.is_empty() {
+        entry.push_str(&format!("Icon={}\n", desktop_escape(&program_link.icon_path)));
+    }
+    if !program_link.keywords.is_empty() {
+        // 列表中的 ; 需要转义
+        let keywords: Vec<String> = program_link.keywords
+            .iter()
+            .map(|keyword| desktop_escape(keyword).replace(';', "\\;"))
+            .collect();
+        entry.push_str(&format!("Keywords={};\n", keywords.join(";")));
+    }
+    entry.push_str(&format!("X-BaroBoard-UUID={}\n", program_link.uuid));
+    entry
+}
+
+
+/// POSIX shell 的单引号，内部的单引号写作 '\''
+fn posix_quote(arg: &str) -> String {
+    if !arg.is_empty() && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c)) {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+fn posix_aliases(program_links: &[&ProgramLink], names: &[String]) -> String {
+    let mut script = String::from("# 由 BaroBoard 生成，在 ~/.bashrc 或 ~/.zshrc 中 source 此文件\n");
+    for (program_link, name) in program_links.iter().zip(names) {
+        let command = if is_url(&program_link.run_command) {
+            format!("xdg-open {}", posix_quote(&program_link.run_command))
+        } else {
+            argv(program_link).iter().map(|arg| posix_quote(arg)).collect::<Vec<_>>().join(" ")
+        };
+        script.push_str(&format!("# {}\nalias {}={}\n", program_link.name.replace('\n', " "), name, posix_quote(&command)));
+    }
+    script
+}
+
+/// fish 的单引号，只有 \ 与 ' 需要转义
+fn fish_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn fish_aliases(program_links: &[&ProgramLink], names: &[String]) -> String {
+    let mut script = String::from("# 由 BaroBoard 生成，在 ~/.config/fish/config.fish 中 source 此文件\n");
+    for (program_link, name) in program_links.iter().zip(names) {
+        let command = if is_url(&program_link.run_command) {
+            format!("xdg-open {}", fish_quote(&program_link.run_command))
+        } else {
+            argv(program_link).iter().map(|arg| fish_quote(arg)).collect::<Vec<_>>().join(" ")
+        };
+        script.push_str(&format!("# {}\nalias {} {}\n", program_link.name.replace('\n', " "), name, fish_quote(&command)));
+    }
+    script
+}
+
+/// PowerShell 的单引号，内部的单引号写作 ''
+///
+/// PowerShell 也把 U+2018 到 U+201B 的弯引号当作单引号，同样需要重复一次
+fn powershell_quote(arg: &str) -> String {
+    let mut quoted = String::from("'");
+    for c in arg.chars() {
+        if matches!(c, '\'' | '\u{2018}'..='\u{201B}') {
+            quoted.push(c);
+        }
+        quoted.push(c);
+    }
+    quoted.push('\'');
+    quoted
+}
+
+fn powershell_functions(program_links: &[&ProgramLink], names: &[String]) -> String {
+    let mut script = String::from("# 由 BaroBoard 生成，可以粘贴到 $PROFILE 中或用 . 引入此文件\n");
+    for (program_link, name) in program_links.iter().zip(names) {
+        let command = program_link.run_command.as_str();
+        let arguments: Vec<String> = program_link.arguments.iter().map(|arg| powershell_quote(arg)).collect();
+
+        let body = if is_url(command) {
+            format!("Start-Process {}", powershell_quote(command))
+        } else if program_link.is_admin {
+            let mut body = format!("Start-Process -FilePath {} -Verb RunAs", powershell_quote(command));
+            if !arguments.is_empty() {
+                body.push_str(&format!(" -ArgumentList @({})", arguments.join(", ")));
+            }
+            body
+        } else {
+            let mut body = format!("& {}", powershell_quote(command));
+            for argument in arguments.iter() {
+                body.push(' ');
+                body.push_str(argument);
+            }
+            body.push_str(" @args");
+            body
+        };
+        script.push_str(&format!("# {}\nfunction {} {{ {} }}\n", program_link.name.replace('\n', " "), name, body));
+    }
+    script
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_posix_arguments() {
+        let cases = [
+            ("firefox", "firefox"),
+            ("--size=10", "--size=10"),
+            ("/usr/bin/code", "/usr/bin/code"),
+            ("", "''"),
+            ("a b", "'a b'"),
+            ("$HOME", "'$HOME'"),
+            ("a;rm -rf ~", "'a;rm -rf ~'"),
+            ("it's", r"'it'\''s'"),
+            ("`date`", "'`date`'"),
+        ];
+        for (arg, expected) in cases {
+            assert_eq!(posix_quote(arg), expected, "{:?}", arg);
+        }
+    }
+
+    #[test]
+    fn quotes_fish_arguments() {
+        let cases = [
+            ("firefox", "'firefox'"),
+            ("", "''"),
+            ("a b", "'a b'"),
+            ("$HOME", "'$HOME'"),
+            ("it's", r"'it\'s'"),
+            (r"C:\dir", r"'C:\\dir'"),
+            (r"\'", r"'\\\''"),
+        ];
+        for (arg, expected) in cases {
+            assert_eq!(fish_quote(arg), expected, "{:?}", arg);
+        }
+    }
+
+    #[test]
+    fn quotes_desktop_exec_arguments() {
+        let cases = [
+            ("firefox", "firefox"),
+            ("--size=10", "--size=10"),
+            // % 是字段代码的前缀
+            ("100%", "100%%"),
+            ("%u", "%%u"),
+            ("", r#""""#),
+            ("a b", r#""a b""#),
+            ("it's", r#""it's""#),
+            (r#"say "hi""#, r#""say \"hi\"""#),
+            ("$HOME", r#""\$HOME""#),
+            ("`date`", r#""\`date\`""#),
+            (r"a\b", r#""a\\b""#),
+        ];
+        for (arg, expected) in cases {
+            assert_eq!(desktop_exec_arg(arg), expected, "{:?}", arg);
+        }
+    }
+
+    #[test]
+    fn escapes_desktop_exec_line() {
+        let program_link = ProgramLink {
+            name: "Echo".to_string(),
+            run_command: "/usr/bin/echo".to_string(),
+            arguments: vec![r"a\b".to_string(), "50%".to_string()],
+            ..Default::default()
+        };
+
+        // Exec 中的引号转义之后，整个字符串还要再按 .desktop 的规则转义一次
+        let entry = desktop_entry(&program_link);
+        assert!(entry.contains(r#"Exec=/usr/bin/echo "a\\\\b" 50%%"#), "{}", entry);
+        assert!(entry.contains("Type=Application\n"));
+    }
+
+    #[test]
+    fn quotes_powershell_arguments() {
+        let cases = [
+            ("firefox", "'firefox'"),
+            ("", "''"),
+            ("a b", "'a b'"),
+            ("$env:PATH", "'$env:PATH'"),
+            ("it's", "'it''s'"),
+            // 弯引号也会结束单引号字符串
+            ("\u{2018}a\u{2019}", "'\u{2018}\u{2018}a\u{2019}\u{2019}'"),
+            ("\u{201A}\u{201B}", "'\u{201A}\u{201A}\u{201B}\u{201B}'"),
+            ("a\u{2019}; Remove-Item C:\\", "'a\u{2019}\u{2019}; Remove-Item C:\\'"),
+            // 双引号不需要转义
+            ("\u{201C}a\u{201D}", "'\u{201C}a\u{201D}'"),
+        ];
+        for (arg, expected) in cases {
+            assert_eq!(powershell_quote(arg), expected, "{:?}", arg);
+        }
+    }
+}
@@ -0,0 +1,251 @@
+//! 配置文件的版本迁移
+//!
+//! 每个旧版本都有一个迁移函数，把配置升级到下一个版本。读取时从文件中记录的版本开始依次执行，
+//! 直到 [`crate::CONFIG_FILE_VERSION`]。各版本的变化：
+//!
+//! - v1: 名称是一个字符串
+//! - v2: 名称改为列表，第一个为显示名称，其余为别名
+//! - v3: 新增标签，包括全局的标签列表与快捷方式的标签
+//! - v4: 快捷方式新增参数
+//! - v5: 快捷方式新增以管理员权限运行、在新的命令行中运行
+//! - v6: 别名从名称列表移到 `keywords` 中
+
+use serde_json::{Map, Value, json};
+use log::info;
+
+
+/// 迁移函数，将配置升级到下一个版本
+type Migration = fn(&mut Value);
+
+/// `MIGRATIONS[n]` 将版本 n+1 升级到 n+2
+const MIGRATIONS: [Migration; 5] = [
+    v1_to_v2,
+    v2_to_v3,
+    v3_to_v4,
+    v4_to_v5,
+    v5_to_v6,
+];
+
+
+/// 配置文件中记录的版本，没有版本号的配置视为 v1
+pub fn config_version(links_config: &Value) -> u32 {
+    links_config.get("version")
+        .and_then(|v| v.as_u64())
+        .map_or(1, |version| version.max(1) as u32)
+}
+
+/// 是否需要迁移
+pub fn needs_migration(links_config: &Value) -> bool {
+    config_version(links_config) < crate::CONFIG_FILE_VERSION
+}
+
+/// 将配置依次升级到当前版本，返回迁移前的版本
+///
+/// 比当前版本更新的配置不会被修改
+pub fn migrate(links_config: &mut Value) -> u32 {
+    let from = config_version(links_config);
+
+    for version in from..crate::CONFIG_FILE_VERSION {
+        MIGRATIONS[version as usize - 1](links_config);
+        set_version(links_config, version + 1);
+        info!("配置文件已从 v{} 迁移到 v{}", version, version + 1);
+    }
+
+    from
+}
+
+
+fn set_version(links_config: &mut Value, version: u32) {
+    if let Some(links_config) = links_config.as_object_mut() {
+        links_config.insert("version".to_string(), json!(version));
+    }
+}
+
+/// 配置中的所有快捷方式
+fn program_links_mut(links_config: &mut Value) -> impl Iterator<Item = &mut Map<String, Value>> {
+    links_config.get_mut("program_links")
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object_mut)
+}
+
+/// 字符串列表，单个字符串会成为只有一项的列表，其余内容视为空列表
+fn string_list(value: Option<&Value>) -> Value {
+    match value {
+        Some(Value::Array(items)) => Value::Array(items.iter().filter(|item| item.is_string()).cloned().collect()),
+        Some(Value::String(item)) => json!([item]),
+        _ => json!([]),
+    }
+}
+
+/// 旧版本的名称，可能是列表或以 / 分隔的字符串，返回显示名称与别名
+pub fn split_legacy_names(name: Option<&Value>) -> (String, Vec<String>) {
+    let mut names: Vec<String> = match name {
+        Some(Value::Array(names)) => names
+            .iter()
+            .filter_map(|name| name.as_str())
+            .map(|name| name.to_string())
+            .collect(),
+        Some(Value::String(name)) => name.split('/').map(|name| name.to_string()).collect(),
+        _ => Vec::new(),
+    };
+    names.retain(|name| !name.trim().is_empty());
+
+    if names.is_empty() {
+        return ("".to_string(), Vec::new());
+    }
+    let name = names.remove(0);
+    (name, names)
+}
+
+
+/// v1 还没有别名，名称原样成为列表的唯一一项
+fn v1_to_v2(links_config: &mut Value) {
+    for program_link in program_links_mut(links_config) {
+        let name = string_list(program_link.get("name"));
+        program_link.insert("name".to_string(), name);
+    }
+}
+
+fn v2_to_v3(links_config: &mut Value) {
+    let tags = string_list(links_config.get("tags"));
+    if let Some(links_config) = links_config.as_object_mut() {
+        links_config.insert("tags".to_string(), tags);
+    }
+
+    for program_link in program_links_mut(links_config) {
+        let tags = string_list(program_link.get("tags"));
+        program_link.insert("tags".to_string(), tags);
+    }
+}
+
+fn v3_to_v4(links_config: &mut Value) {
+    for program_link in program_links_mut(links_config) {
+        let arguments = string_list(program_link.get("arguments"));
+        program_link.insert("arguments".to_string(), arguments);
+    }
+}
+
+fn v4_to_v5(links_config: &mut Value) {
+    for program_link in program_links_mut(links_config) {
+        let is_admin = program_link.get("is_admin").and_then(Value::as_bool).unwrap_or(false);
+        let is_new_window = program_link.get("is_new_window").and_then(Value::as_bool).unwrap_or(true);
+        program_link.insert("is_admin".to_string(), json!(is_admin));
+        program_link.insert("is_new_window".to_string(), json!(is_new_window));
+    }
+}
+
+fn v5_to_v6(links_config: &mut Value) {
+    for program_link in program_links_mut(links_config) {
+        let (name, keywords) = split_legacy_names(program_link.get("name"));
+        program_link.insert("name".to_string(), json!(name));
+        program_link.insert("keywords".to_string(), json!(keywords));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pages::popups::link::save::LinkConfigSchema;
+
+    /// 迁移并反序列化测试用的配置文件
+    fn load_fixture(contents: &str) -> (u32, LinkConfigSchema) {
+        let mut links_config: Value = serde_json::from_str(contents).unwrap();
+        let from = migrate(&mut links_config);
+        assert_eq!(config_version(&links_config), crate::CONFIG_FILE_VERSION);
+        (from, serde_json::from_value(links_config).unwrap())
+    }
+
+    macro_rules! fixture {
+        ($name:literal) => {
+            include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config/", $name))
+        };
+    }
+
+    #[test]
+    fn every_version_has_a_migration() {
+        assert_eq!(MIGRATIONS.len() as u32, crate::CONFIG_FILE_VERSION - 1);
+    }
+
+    #[test]
+    fn migrates_v1() {
+        let (from, config) = load_fixture(fixture!("v1.json"));
+        assert_eq!(from, 1);
+
+        let link = &config.program_links[0];
+        // v1 没有别名，名称中的 / 需要原样保留
+        assert_eq!(link.name, "AC/DC");
+        assert!(link.keywords.is_empty());
+        assert!(link.tags.is_empty());
+        assert!(link.arguments.is_empty());
+        assert!(!link.is_admin);
+        assert!(link.is_new_window);
+        assert!(config.tags.is_empty());
+    }
+
+    #[test]
+    fn migrates_v2() {
+        let (from, config) = load_fixture(fixture!("v2.json"));
+        assert_eq!(from, 2);
+
+        let link = &config.program_links[0];
+        assert_eq!(link.name, "记事本");
+        assert_eq!(link.keywords, vec!["notepad".to_string()]);
+        assert!(link.tags.is_empty());
+    }
+
+    #[test]
+    fn migrates_v3() {
+        let (from, config) = load_fixture(fixture!("v3.json"));
+        assert_eq!(from, 3);
+
+        assert!(config.tags.contains("工具"));
+        let link = &config.program_links[0];
+        assert!(link.tags.contains("工具"));
+        assert!(link.arguments.is_empty());
+    }
+
+    #[test]
+    fn migrates_v4() {
+        let (from, config) = load_fixture(fixture!("v4.json"));
+        assert_eq!(from, 4);
+
+        let link = &config.program_links[0];
+        assert_eq!(link.arguments, vec!["--new-window".to_string()]);
+        assert!(!link.is_admin);
+        assert!(link.is_new_window);
+    }
+
+    #[test]
+    fn migrates_v5() {
+        let (from, config) = load_fixture(fixture!("v5.json"));
+        assert_eq!(from, 5);
+
+        let link = &config.program_links[0];
+        assert_eq!(link.name, "终端");
+        assert_eq!(link.keywords, vec!["terminal".to_string(), "cmd".to_string()]);
+        assert!(link.is_admin);
+        assert!(!link.is_new_window);
+    }
+
+    #[test]
+    fn current_version_is_unchanged() {
+        let contents = fixture!("v6.json");
+        let mut links_config: Value = serde_json::from_str(contents).unwrap();
+        let original = links_config.clone();
+
+        assert!(!needs_migration(&links_config));
+        assert_eq!(migrate(&mut links_config), crate::CONFIG_FILE_VERSION);
+        assert_eq!(links_config, original);
+
+        let (_, config) = load_fixture(contents);
+        assert_eq!(config.program_links[0].name, "AC/DC");
+    }
+
+    #[test]
+    fn missing_version_is_v1() {
+        assert_eq!(config_version(&json!({ "program_links": [] })), 1);
+    }
+}
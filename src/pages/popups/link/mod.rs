@@ -1,2 +1,12 @@
 pub mod config;
 pub mod save;
+pub mod backup;
+pub mod bookmarks;
+pub mod bundle;
+pub mod catalog;
+pub mod launchers;
+pub mod migrate;
+pub mod profile;
+pub mod trash;
+pub mod undo;
+pub mod watch;
@@ -0,0 +1,395 @@
+//! 工作区
+//!
+//! 每个工作区有自己的快捷方式、标签、回收站与标题，保存在单独的配置文件中。
+//! 默认工作区使用配置文件夹中的 `links.json`，其余工作区保存在 `profiles` 文件夹中，文件名即工作区的名称。
+//!
+//! 当前的工作区记录在设置中，读取与保存快捷方式配置时都通过 [`save::config_path`] 使用当前工作区的文件。
+//! 工作区之间不共享撤销记录，切换时会清空
+
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use log::{info, warn};
+
+use crate::my_structs::{load_links, ProgramLink};
+use super::save;
+use super::watch::LinkSnapshot;
+
+
+/// 配置文件夹中保存工作区的文件夹
+const PROFILE_DIR_NAME: &str = "profiles";
+/// 工作区名称中不能使用的字符
+const INVALID_CHARS: [char; 9] = ['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+/// Windows 中不能用作文件名的设备名称，另外还有 COM1 到 COM9 与 LPT1 到 LPT9
+const RESERVED_NAMES: [&str; 4] = ["CON", "PRN", "AUX", "NUL"];
+/// 没有设置标题时默认工作区的标题
+pub const DEFAULT_TITLE: &str = "BaroBoard 工具箱";
+
+
+/// 当前的工作区，None 表示默认工作区
+static CURRENT: RwLock<Option<String>> = RwLock::new(None);
+
+
+pub fn current() -> Option<String> {
+    CURRENT.read().unwrap().clone()
+}
+
+pub fn set_current(name: Option<String>) {
+    info!("当前工作区: {}", display_name(name.as_deref()));
+    *CURRENT.write().unwrap() = name;
+}
+
+/// 工作区的显示名称
+pub fn display_name(name: Option<&str>) -> &str {
+    name.unwrap_or("默认")
+}
+
+/// 保存工作区的文件夹，不包含默认工作区
+pub fn profile_dir() -> PathBuf {
+    crate::paths::config_dir().join(PROFILE_DIR_NAME)
+}
+
+/// 工作区的配置文件
+pub fn profile_path(name: Option<&str>) -> PathBuf {
+    profile_path_in(crate::paths::config_dir(), name)
+}
+
+fn profile_path_in(config_dir: &Path, name: Option<&str>) -> PathBuf {
+    match name {
+        Some(name) => config_dir.join(PROFILE_DIR_NAME).join(format!("{}.json", name)),
+        None => config_dir.join(crate::CONFIG_FILE_NAME),
+    }
+}
+
+/// 所有工作区，默认工作区在最前，其余按名称排序
+pub fn list_profiles() -> Vec<Option<String>> {
+    list_profiles_in(crate::paths::config_dir())
+}
+
+fn list_profiles_in(config_dir: &Path) -> Vec<Option<String>> {
+    let mut names: Vec<String> = std::fs::read_dir(config_dir.join(PROFILE_DIR_NAME))
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| {
+                    let path = entry.path();
+                    if path.extension().is_some_and(|extension| extension == "json") {
+                        path.file_stem().map(|stem| stem.to_string_lossy().to_string())
+                    } else {
+                        None
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+
+    std::iter::once(None).chain(names.into_iter().map(Some)).collect()
+}
+
+/// 列表中的下一个工作区，到末尾后回到默认工作区
+pub fn next_profile(current: Option<&str>) -> Option<String> {
+    let profiles = list_profiles();
+    let index = profiles.iter().position(|name| name.as_deref() == current).unwrap_or(0);
+    profiles[(index + 1) % profiles.len()].clone()
+}
+
+/// Windows 的设备名称，带有扩展名时同样不能使用
+fn is_reserved_name(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or(name).trim_end().to_ascii_uppercase();
+    let numbered = stem.len() == 4
+        && (stem.starts_with("COM") || stem.starts_with("LPT"))
+        && matches!(stem.as_bytes()[3], b'1'..=b'9');
+    numbered || RESERVED_NAMES.contains(&stem.as_str())
+}
+
+/// 检查新的工作区名称，不可用时返回原因
+pub fn validate_name(name: &str) -> Result<(), String> {
+    validate_name_in(crate::paths::config_dir(), name)
+}
+
+fn validate_name_in(config_dir: &Path, name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("名称不能为空".to_string());
+    }
+    if name != name.trim() || name.starts_with('.') || name.ends_with('.') {
+        return Err("名称不能以空格或 . 开头或结尾".to_string());
+    }
+    if name.contains(INVALID_CHARS) {
+        return Err(format!("名称不能包含 {}", INVALID_CHARS.iter().collect::<String>()));
+    }
+    // 备份按文件名区分，不能与默认工作区的配置文件同名
+    let default_stem = Path::new(crate::CONFIG_FILE_NAME).file_stem().and_then(|stem| stem.to_str());
+    if default_stem.is_some_and(|stem| stem.eq_ignore_ascii_case(name)) || is_reserved_name(name) {
+        return Err(format!("{} 是保留的名称", name));
+    }
+    if profile_path_in(config_dir, Some(name)).exists() {
+        return Err(format!("工作区“{}”已存在", name));
+    }
+    Ok(())
+}
+
+/// 创建新的工作区，`links` 为其中的初始内容
+pub fn create_profile(name: &str, links: LinkSnapshot) -> Result<(), std::io::Error> {
+    std::fs::create_dir_all(profile_dir())?;
+    save::save_conf_to_path(links, &profile_path(Some(name)))?;
+    info!("创建工作区: {}", name);
+    Ok(())
+}
+
+pub fn rename_profile(from: &str, to: &str) -> Result<(), std::io::Error> {
+    std::fs::rename(profile_path(Some(from)), profile_path(Some(to)))?;
+    info!("重命名工作区: {} -> {}", from, to);
+    Ok(())
+}
+
+/// 删除工作区的配置文件，之前的自动备份会保留
+pub fn delete_profile(name: &str) -> Result<(), std::io::Error> {
+    std::fs::remove_file(profile_path(Some(name)))?;
+    info!("删除工作区: {}", name);
+    Ok(())
+}
+
+/// 将快捷方式复制到另一个工作区的末尾，连同使用的标签一起加入
+///
+/// 目标中已有相同 uuid 的快捷方式时使用新的 uuid
+pub fn copy_links_to(name: Option<&str>, program_links: Vec<ProgramLink>) -> Result<(), std::io::Error> {
+    let path = profile_path(name);
+    let mut links = load_links(&path)?;
+    append_links(&mut links, program_links, name);
+    save::save_conf_to_path(links, &path)
+}
+
+fn append_links(links: &mut LinkSnapshot, program_links: Vec<ProgramLink>, name: Option<&str>) {
+    for mut program_link in program_links {
+        if links.program_links.iter().any(|link| link.uuid == program_link.uuid) {
+            program_link.uuid = uuid::Uuid::new_v4().to_string();
+        }
+        program_link.catalog = None;
+        links.tags.extend(program_link.tags.iter().cloned());
+        info!("复制快捷方式“{}”到工作区 {}", program_link.name, display_name(name));
+        links.program_links.push(program_link);
+    }
+}
+
+/// 启动时恢复设置中记录的工作区，配置文件已经不存在时回到默认工作区
+pub fn restore(name: Option<String>) -> Option<String> {
+    restore_in(crate::paths::config_dir(), name)
+}
+
+fn restore_in(config_dir: &Path, name: Option<String>) -> Option<String> {
+    let name = name.filter(|name| {
+        let exists = profile_path_in(config_dir, Some(name)).exists();
+        if !exists {
+            warn!("工作区“{}”不存在，使用默认工作区", name);
+        }
+        exists
+    });
+    set_current(name.clone());
+    name
+}
+
+/// 其他工作区的快捷方式或回收站中是否使用这个图标
+///
+/// 程序图标的缓存按程序路径命名，不同工作区中的同一个程序使用同一个文件。无法读取的工作区视为正在使用
+#[cfg(target_os = "windows")]
+pub fn icon_used_by_other_profiles(icon_path: &str) -> bool {
+    icon_used_by_other_profiles_in(crate::paths::config_dir(), current().as_deref(), icon_path)
+}
+
+// 只有 Windows 会删除图标文件
+#[cfg(any(target_os = "windows", test))]
+fn icon_used_by_other_profiles_in(config_dir: &Path, current: Option<&str>, icon_path: &str) -> bool {
+    list_profiles_in(config_dir)
+        .into_iter()
+        .filter(|name| name.as_deref() != current)
+        .any(|name| match load_links(&profile_path_in(config_dir, name.as_deref())) {
+            Ok(links) => links.program_links
+                .iter()
+                .chain(links.trash.iter().map(|trashed| &trashed.program_link))
+                .any(|link| link.icon_path == icon_path),
+            Err(e) => {
+                warn!("无法读取工作区 {}: {}", display_name(name.as_deref()), e);
+                true
+            }
+        })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use super::super::trash::TrashedLink;
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("baroboard-profile-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join(PROFILE_DIR_NAME)).unwrap();
+        dir
+    }
+
+    fn link(uuid: &str, name: &str, tags: &[&str]) -> ProgramLink {
+        ProgramLink {
+            name: name.to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            uuid: uuid.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn set(items: &[&str]) -> HashSet<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    /// 直接写入配置文件，不产生备份
+    fn write_profile(config_dir: &Path, name: Option<&str>, links: LinkSnapshot) {
+        let links_config = save::LinkConfigSchema {
+            tags: links.tags,
+            program_links: links.program_links,
+            trash: links.trash,
+            ..Default::default()
+        };
+        std::fs::write(profile_path_in(config_dir, name), serde_json::to_string(&links_config).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn validates_names() {
+        let dir = temp_dir();
+        write_profile(&dir, Some("工作"), LinkSnapshot::default());
+
+        // (名称, 是否可用)
+        let cases = [
+            ("游戏", true),
+            ("my profile", true),
+            ("v1.2", true),
+            ("", false),
+            ("   ", false),
+            (" a", false),
+            ("a ", false),
+            (".a", false),
+            ("a.", false),
+            ("a/b", false),
+            ("a\\b", false),
+            ("a:b", false),
+            ("a?", false),
+            // 已存在
+            ("工作", false),
+            // 与默认工作区的配置文件同名
+            ("links", false),
+            ("LINKS", false),
+            // Windows 的设备名称
+            ("CON", false),
+            ("con", false),
+            ("Aux", false),
+            ("nul.txt", false),
+            ("PRN.tar.gz", false),
+            ("COM1", false),
+            ("lpt9", false),
+            ("com5.json", false),
+            ("COM0", true),
+            ("COM10", true),
+            ("LPT", true),
+            ("console", true),
+            ("CONFIG", true),
+            ("auxiliary", true),
+        ];
+        for (name, valid) in cases {
+            assert_eq!(validate_name_in(&dir, name).is_ok(), valid, "{:?}", name);
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn lists_profiles() {
+        let dir = temp_dir();
+        assert_eq!(list_profiles_in(&dir), [None]);
+
+        write_profile(&dir, Some("b"), LinkSnapshot::default());
+        write_profile(&dir, Some("a"), LinkSnapshot::default());
+        // 其他文件与文件夹不是工作区
+        std::fs::write(dir.join(PROFILE_DIR_NAME).join("notes.txt"), "").unwrap();
+        std::fs::create_dir_all(dir.join(PROFILE_DIR_NAME).join("backup")).unwrap();
+
+        assert_eq!(list_profiles_in(&dir), [None, Some("a".to_string()), Some("b".to_string())]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn appends_links() {
+        let mut links = LinkSnapshot::new(vec![link("a", "浏览器", &["网络"])], set(&["网络"]), HashSet::new(), Vec::new());
+
+        let mut shared = link("b", "共享", &[]);
+        shared.catalog = Some("team".to_string());
+        append_links(&mut links, vec![link("a", "浏览器", &["常用"]), shared], Some("工作"));
+
+        let names: Vec<&str> = links.program_links.iter().map(|link| link.name.as_str()).collect();
+        assert_eq!(names, ["浏览器", "浏览器", "共享"]);
+        // uuid 重复时使用新的 uuid
+        assert_eq!(links.program_links[0].uuid, "a");
+        assert_ne!(links.program_links[1].uuid, "a");
+        assert_eq!(links.program_links[2].uuid, "b");
+        // 复制后成为用户自己的快捷方式
+        assert_eq!(links.program_links[2].catalog, None);
+        // 使用的标签一起加入
+        assert_eq!(links.tags, set(&["网络", "常用"]));
+    }
+
+    #[test]
+    fn restores_existing_profile() {
+        let dir = temp_dir();
+        write_profile(&dir, Some("工作"), LinkSnapshot::default());
+
+        // (设置中记录的工作区, 恢复的工作区)
+        let cases = [
+            (Some("工作"), Some("工作")),
+            (Some("已删除"), None),
+            (None, None),
+        ];
+        for (name, expected) in cases {
+            let restored = restore_in(&dir, name.map(String::from));
+            assert_eq!(restored.as_deref(), expected);
+            assert_eq!(current().as_deref(), expected);
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn finds_icons_in_other_profiles() {
+        let dir = temp_dir();
+        let with_icon = |uuid: &str, icon_path: &str| {
+            let mut program_link = link(uuid, uuid, &[]);
+            program_link.icon_path = icon_path.to_string();
+            program_link
+        };
+
+        write_profile(&dir, None, LinkSnapshot::new(vec![with_icon("a", "/cache/a.png")], HashSet::new(), HashSet::new(), Vec::new()));
+        write_profile(&dir, Some("工作"), LinkSnapshot::new(
+            vec![with_icon("b", "/cache/b.png")],
+            HashSet::new(),
+            HashSet::new(),
+            vec![TrashedLink::new(with_icon("c", "/cache/c.png"))],
+        ));
+
+        // (当前工作区, 图标, 是否在其他工作区中使用)
+        let cases = [
+            (None, "/cache/a.png", false),
+            (None, "/cache/b.png", true),
+            // 回收站中的快捷方式还原后仍会用到
+            (None, "/cache/c.png", true),
+            (Some("工作"), "/cache/a.png", true),
+            (Some("工作"), "/cache/b.png", false),
+            (None, "/cache/other.png", false),
+        ];
+        for (current, icon_path, used) in cases {
+            assert_eq!(icon_used_by_other_profiles_in(&dir, current, icon_path), used, "{:?} {}", current, icon_path);
+        }
+
+        // 无法读取的工作区视为正在使用
+        std::fs::write(profile_path_in(&dir, Some("损坏")), "{").unwrap();
+        assert!(icon_used_by_other_profiles_in(&dir, None, "/cache/other.png"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,15 +1,29 @@
 use serde::{Deserialize, Serialize};
-use std::{fs::File, io::Read, io::Write};
+use std::{fs::File, io::Read};
+use std::path::{Path, PathBuf};
 use std::collections::HashSet;
-use log::info;
+use log::{info, warn};
 
 use crate::my_structs::*;
+use crate::utils::file_utils::write_atomic;
+use super::{backup, profile};
+use super::trash::TrashedLink;
+use super::watch::LinkSnapshot;
 
 #[derive(Serialize, Deserialize)]
 pub struct LinkConfigSchema {
     pub version: u32,
     pub tags: HashSet<String>,
     pub program_links: Vec<ProgramLink>,
+    // 隐藏的共享快捷方式的uuid
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub hidden: HashSet<String>,
+    // 回收站中的快捷方式
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trash: Vec<TrashedLink>,
+    // 工作区的标题，None 时使用默认标题
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
 }
 
 impl Default for LinkConfigSchema {
@@ -18,30 +32,46 @@ impl Default for LinkConfigSchema {
             version: crate::CONFIG_FILE_VERSION,
             tags: HashSet::new(),
             program_links: Vec::new(),
+            hidden: HashSet::new(),
+            trash: Vec::new(),
+            title: None,
         }
     }
 }
 
-pub fn save_conf(program_links: Vec<ProgramLink>, tags: HashSet<String>) -> Result<(), std::io::Error> {
-    save_conf_to_path(program_links, tags, format!("{}/{}", crate::CONFIG_SAVE_PATH, crate::CONFIG_FILE_NAME).as_str())
+/// 当前工作区的快捷方式配置文件的路径
+pub fn config_path() -> PathBuf {
+    profile::profile_path(profile::current().as_deref())
+}
+
+/// 保存用户层，共享目录中的快捷方式不会写入
+pub fn save_conf(links: LinkSnapshot) -> Result<(), std::io::Error> {
+    save_conf_to_path(links, &config_path())
 }
 
 
-pub fn save_conf_to_path(program_links: Vec<ProgramLink>, tags: HashSet<String>, path: &str) -> Result<(), std::io::Error> {
+pub fn save_conf_to_path(links: LinkSnapshot, path: &Path) -> Result<(), std::io::Error> {
     let links_config = LinkConfigSchema {
         version: crate::CONFIG_FILE_VERSION,
-        tags: tags,
-        program_links: program_links,
+        tags: links.tags,
+        program_links: links.program_links,
+        hidden: links.hidden,
+        trash: links.trash,
+        title: links.title,
     };
 
     let serialized = serde_json::to_string_pretty(&links_config)?;
-    let mut file = File::create(path)?;
-    file.write_all(serialized.as_bytes())?;
-    info!("保存配置文件: {}", path);
+
+    // 备份失败时仍然保存，写入本身是原子的，不会损坏原文件
+    if let Err(e) = backup::create_backup(path) {
+        warn!("备份配置文件失败: {}", e);
+    }
+    write_atomic(path, serialized.as_bytes())?;
+    info!("保存配置文件: {}", path.display());
     Ok(())
 }
 
-pub fn load_conf(path: &str) -> Result<serde_json::Value, std::io::Error> {
+pub fn load_conf(path: &Path) -> Result<serde_json::Value, std::io::Error> {
     let mut file = File::open(path)?;
     let mut buffer = String::new();
     file.read_to_string(&mut buffer)?;
@@ -49,6 +79,6 @@ pub fn load_conf(path: &str) -> Result<serde_json::Value, std::io::Error> {
 
     let links_config: serde_json::Value = serde_json::from_str(&buffer)?;
 
-    info!("加载配置文件: {}", path);
+    info!("加载配置文件: {}", path.display());
     Ok(links_config)
 }
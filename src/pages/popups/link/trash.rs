@@ -0,0 +1,72 @@
+//! 回收站
+//!
+//! 删除的快捷方式会连同删除时间保存在配置文件的 `trash` 中，可以还原或彻底删除，
+//! 超过设置的天数后会被自动清理
+
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Local};
+use log::info;
+
+use crate::my_structs::ProgramLink;
+
+
+/// 回收站的设置
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct TrashSettings {
+    // 自动清理的天数，为 0 时不会自动清理
+    pub retention_days: u32,
+}
+
+impl Default for TrashSettings {
+    fn default() -> Self {
+        Self {
+            retention_days: 30,
+        }
+    }
+}
+
+
+/// 回收站中的快捷方式
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TrashedLink {
+    pub program_link: ProgramLink,
+    pub deleted_at: DateTime<Local>,
+}
+
+impl TrashedLink {
+    pub fn new(program_link: ProgramLink) -> Self {
+        Self {
+            program_link: program_link,
+            deleted_at: Local::now(),
+        }
+    }
+
+    /// 距离自动清理还有几天，不会自动清理时为 None
+    pub fn days_left(&self, settings: &TrashSettings) -> Option<i64> {
+        if settings.retention_days == 0 {
+            return None;
+        }
+        let elapsed = (Local::now() - self.deleted_at).num_days();
+        Some((settings.retention_days as i64 - elapsed).max(0))
+    }
+}
+
+
+/// 移除超过保留天数的快捷方式，返回被移除的部分
+pub fn purge_expired(trash: &mut Vec<TrashedLink>, settings: &TrashSettings) -> Vec<TrashedLink> {
+    if settings.retention_days == 0 {
+        return Vec::new();
+    }
+
+    let deadline = Local::now() - chrono::Duration::days(settings.retention_days as i64);
+    let (expired, kept): (Vec<TrashedLink>, Vec<TrashedLink>) = std::mem::take(trash)
+        .into_iter()
+        .partition(|trashed| trashed.deleted_at < deadline);
+    *trash = kept;
+
+    if !expired.is_empty() {
+        info!("自动清理回收站中 {} 个超过 {} 天的快捷方式", expired.len(), settings.retention_days);
+    }
+    expired
+}
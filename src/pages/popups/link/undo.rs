@@ -0,0 +1,368 @@
+//! 快捷方式与标签修改的撤销与重做
+//!
+//! 每次保存时将修改前后的用户层记录为一条命令，撤销与重做时直接应用对应的一边。
+//! 命令保存完整的快照而不是具体的操作，新增的修改方式不需要单独实现撤销。
+//! 命令的说明由前后两边的差异生成，记录只保存在内存中，并限制最大条数
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use log::debug;
+
+use super::watch::LinkSnapshot;
+
+
+/// 最多保留的撤销步数
+pub const MAX_UNDO_STEPS: usize = 50;
+/// 撤销提示显示的时长
+pub const TOAST_DURATION: Duration = Duration::from_secs(6);
+
+
+/// 一次修改
+#[derive(Debug, Clone)]
+pub struct EditCommand {
+    pub label: String,
+    before: LinkSnapshot,
+    after: LinkSnapshot,
+}
+
+/// 删除后显示的撤销提示
+#[derive(Debug, Clone)]
+pub struct UndoToast {
+    pub label: String,
+    pub expires: Instant,
+}
+
+#[derive(Debug, Default)]
+pub struct UndoStack {
+    undo: VecDeque<EditCommand>,
+    redo: Vec<EditCommand>,
+    // 最近一次记录或应用后的用户层
+    current: LinkSnapshot,
+    pub toast: Option<UndoToast>,
+}
+
+impl UndoStack {
+    /// 从磁盘重新读取配置后，之前的记录不再适用
+    pub fn reset(&mut self, current: LinkSnapshot) {
+        self.undo.clear();
+        self.redo.clear();
+        self.current = current;
+        self.toast = None;
+    }
+
+    /// 记录从上一次状态到 `after` 的修改，没有变化时忽略
+    pub fn record(&mut self, after: LinkSnapshot) {
+        if after == self.current {
+            return;
+        }
+
+        let (label, destructive) = describe(&self.current, &after);
+        self.push(after, label, destructive);
+    }
+
+    /// 使用指定的说明记录修改，用于无法从差异中看出的操作，不显示撤销提示
+    pub fn record_as(&mut self, after: LinkSnapshot, label: String) {
+        if after == self.current {
+            return;
+        }
+        self.push(after, label, false);
+    }
+
+    fn push(&mut self, after: LinkSnapshot, label: String, destructive: bool) {
+        let before = std::mem::replace(&mut self.current, after.clone());
+        debug!("记录修改: {}", label);
+
+        if destructive {
+            self.toast = Some(UndoToast {
+                label: label.clone(),
+                expires: Instant::now() + TOAST_DURATION,
+            });
+        }
+
+        self.undo.push_back(EditCommand {
+            label: label,
+            before: before,
+            after: after,
+        });
+        if self.undo.len() > MAX_UNDO_STEPS {
+            self.undo.pop_front();
+        }
+        self.redo.clear();
+    }
+
+    /// 撤销最近一次修改，返回修改的说明与需要应用的用户层
+    pub fn undo(&mut self) -> Option<(String, LinkSnapshot)> {
+        let command = self.undo.pop_back()?;
+        let result = (command.label.clone(), command.before.clone());
+        self.current = command.before.clone();
+        self.redo.push(command);
+        self.toast = None;
+        Some(result)
+    }
+
+    /// 重做最近一次撤销的修改
+    pub fn redo(&mut self) -> Option<(String, LinkSnapshot)> {
+        let command = self.redo.pop()?;
+        let result = (command.label.clone(), command.after.clone());
+        self.current = command.after.clone();
+        self.undo.push_back(command);
+        self.toast = None;
+        Some(result)
+    }
+
+    pub fn undo_label(&self) -> Option<&str> {
+        self.undo.back().map(|command| command.label.as_str())
+    }
+
+    pub fn redo_label(&self) -> Option<&str> {
+        self.redo.last().map(|command| command.label.as_str())
+    }
+
+    /// 撤销记录中是否还有快捷方式使用这个图标，这样的图标不能被清理
+    pub fn references_icon(&self, icon_path: &str) -> bool {
+        self.undo
+            .iter()
+            .chain(self.redo.iter())
+            .flat_map(|command| command.before.program_links.iter().chain(command.after.program_links.iter()))
+            .any(|link| link.icon_path == icon_path)
+    }
+}
+
+
+/// 快捷方式的显示名称
+fn link_name(name: &str) -> &str {
+    if name.is_empty() { "未命名" } else { name }
+}
+
+/// 根据前后两边的差异生成修改的说明，并判断是否删除了内容
+fn describe(before: &LinkSnapshot, after: &LinkSnapshot) -> (String, bool) {
+    let added: Vec<&str> = after.program_links
+        .iter()
+        .filter(|link| before.program_links.iter().all(|old| old.uuid != link.uuid))
+        .map(|link| link_name(&link.name))
+        .collect();
+    let removed: Vec<&str> = before.program_links
+        .iter()
+        .filter(|link| after.program_links.iter().all(|new| new.uuid != link.uuid))
+        .map(|link| link_name(&link.name))
+        .collect();
+    let edited: Vec<&str> = after.program_links
+        .iter()
+        .filter(|link| before.program_links.iter().any(|old| old.uuid == link.uuid && old != *link))
+        .map(|link| link_name(&link.name))
+        .collect();
+    // 从回收站中还原的快捷方式
+    let restored = !added.is_empty() && after.program_links
+        .iter()
+        .filter(|link| before.program_links.iter().all(|old| old.uuid != link.uuid))
+        .all(|link| before.trash.iter().any(|trashed| trashed.program_link.uuid == link.uuid));
+    // 从回收站中彻底删除的快捷方式
+    let purged: Vec<&str> = before.trash
+        .iter()
+        .map(|trashed| &trashed.program_link)
+        .filter(|link| after.trash.iter().all(|trashed| trashed.program_link.uuid != link.uuid))
+        .filter(|link| after.program_links.iter().all(|new| new.uuid != link.uuid))
+        .map(|link| link_name(&link.name))
+        .collect();
+    let added_tags: Vec<&String> = after.tags.difference(&before.tags).collect();
+    let removed_tags: Vec<&String> = before.tags.difference(&after.tags).collect();
+    let hidden = after.hidden.difference(&before.hidden).count();
+    let unhidden = before.hidden.difference(&after.hidden).count();
+
+    let plural = |names: &[&str], verb: &str| match names {
+        [name] => format!("{}“{}”", verb, name),
+        _ => format!("{} {} 个快捷方式", verb, names.len()),
+    };
+
+    if !removed_tags.is_empty() {
+        return (format!("删除标签“{}”", removed_tags[0]), true);
+    }
+    if !removed.is_empty() {
+        return (plural(&removed, "删除"), true);
+    }
+    if !purged.is_empty() {
+        return (plural(&purged, "彻底删除"), true);
+    }
+    if hidden > 0 {
+        return (format!("隐藏 {} 个共享快捷方式", hidden), true);
+    }
+    let label = if restored {
+        plural(&added, "还原")
+    } else if !added.is_empty() {
+        plural(&added, "添加")
+    } else if !added_tags.is_empty() {
+        format!("新建标签“{}”", added_tags[0])
+    } else if unhidden > 0 {
+        format!("显示 {} 个共享快捷方式", unhidden)
+    } else if !edited.is_empty() {
+        plural(&edited, "编辑")
+    } else if before.title != after.title {
+        "修改标题".to_string()
+    } else {
+        "调整快捷方式的顺序".to_string()
+    };
+    (label, false)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use crate::my_structs::ProgramLink;
+    use super::super::trash::TrashedLink;
+    use super::*;
+
+    fn link(uuid: &str, name: &str) -> ProgramLink {
+        ProgramLink {
+            name: name.to_string(),
+            uuid: uuid.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn set(items: &[&str]) -> HashSet<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    /// 每个快捷方式写作 "uuid:名称"
+    fn snapshot(links: &[&str]) -> LinkSnapshot {
+        let program_links = links.iter().map(|item| {
+            let (uuid, name) = item.split_once(':').unwrap();
+            link(uuid, name)
+        }).collect();
+        LinkSnapshot::new(program_links, HashSet::new(), HashSet::new(), Vec::new())
+    }
+
+    fn with_tags(mut snapshot: LinkSnapshot, tags: &[&str]) -> LinkSnapshot {
+        snapshot.tags = set(tags);
+        snapshot
+    }
+
+    fn with_hidden(mut snapshot: LinkSnapshot, hidden: &[&str]) -> LinkSnapshot {
+        snapshot.hidden = set(hidden);
+        snapshot
+    }
+
+    fn with_trash(mut snapshot: LinkSnapshot, trash: &[&str]) -> LinkSnapshot {
+        snapshot.trash = snapshot_links(trash).into_iter().map(TrashedLink::new).collect();
+        snapshot
+    }
+
+    fn snapshot_links(links: &[&str]) -> Vec<ProgramLink> {
+        snapshot(links).program_links
+    }
+
+    #[test]
+    fn describes_changes() {
+        let base = snapshot(&["a:浏览器", "b:编辑器"]);
+
+        // (修改前, 修改后, 说明, 是否显示撤销提示)
+        let cases = [
+            (base.clone(), snapshot(&["a:浏览器", "b:编辑器", "c:终端"]), "添加“终端”", false),
+            (base.clone(), snapshot(&["a:浏览器", "b:编辑器", "c:终端", "d:"]), "添加 2 个快捷方式", false),
+            (base.clone(), snapshot(&["a:浏览器", "b:编辑器", "d:"]), "添加“未命名”", false),
+            (base.clone(), snapshot(&["b:编辑器"]), "删除“浏览器”", true),
+            (base.clone(), snapshot(&[]), "删除 2 个快捷方式", true),
+            (base.clone(), snapshot(&["a:网页", "b:编辑器"]), "编辑“网页”", false),
+            (base.clone(), snapshot(&["b:编辑器", "a:浏览器"]), "调整快捷方式的顺序", false),
+            (base.clone(), base.clone().with_title(Some("工作".to_string())), "修改标题", false),
+            // 回收站
+            (with_trash(snapshot(&["a:浏览器"]), &["b:编辑器"]), base.clone(), "还原“编辑器”", false),
+            (with_trash(base.clone(), &["c:终端"]), base.clone(), "彻底删除“终端”", true),
+            // 标签
+            (base.clone(), with_tags(base.clone(), &["游戏"]), "新建标签“游戏”", false),
+            (with_tags(base.clone(), &["游戏"]), base.clone(), "删除标签“游戏”", true),
+            // 删除标签优先于其他修改
+            (with_tags(base.clone(), &["游戏"]), snapshot(&["a:浏览器"]), "删除标签“游戏”", true),
+            // 共享的快捷方式
+            (base.clone(), with_hidden(base.clone(), &["x"]), "隐藏 1 个共享快捷方式", true),
+            (with_hidden(base.clone(), &["x", "y"]), base.clone(), "显示 2 个共享快捷方式", false),
+        ];
+        for (before, after, label, destructive) in cases {
+            assert_eq!(describe(&before, &after), (label.to_string(), destructive));
+        }
+    }
+
+    #[test]
+    fn undoes_and_redoes() {
+        let mut stack = UndoStack::default();
+        stack.reset(snapshot(&["a:浏览器"]));
+
+        stack.record(snapshot(&["a:浏览器", "b:编辑器"]));
+        stack.record(snapshot(&["a:网页", "b:编辑器"]));
+        // 没有变化时不记录
+        stack.record(snapshot(&["a:网页", "b:编辑器"]));
+        assert_eq!(stack.undo_label(), Some("编辑“网页”"));
+        assert_eq!(stack.redo_label(), None);
+
+        assert_eq!(stack.undo(), Some(("编辑“网页”".to_string(), snapshot(&["a:浏览器", "b:编辑器"]))));
+        assert_eq!(stack.undo(), Some(("添加“编辑器”".to_string(), snapshot(&["a:浏览器"]))));
+        assert_eq!(stack.undo(), None);
+        assert_eq!(stack.redo_label(), Some("添加“编辑器”"));
+
+        assert_eq!(stack.redo(), Some(("添加“编辑器”".to_string(), snapshot(&["a:浏览器", "b:编辑器"]))));
+        assert_eq!(stack.redo_label(), Some("编辑“网页”"));
+
+        // 新的修改会清除重做记录，并以当前状态为修改前的状态
+        stack.record_as(snapshot(&["b:编辑器"]), "移动到工作区".to_string());
+        assert_eq!(stack.redo_label(), None);
+        assert_eq!(stack.redo(), None);
+        assert_eq!(stack.undo(), Some(("移动到工作区".to_string(), snapshot(&["a:浏览器", "b:编辑器"]))));
+    }
+
+    #[test]
+    fn shows_toast_for_destructive_changes() {
+        let mut stack = UndoStack::default();
+        stack.reset(snapshot(&["a:浏览器"]));
+
+        stack.record(snapshot(&["a:浏览器", "b:编辑器"]));
+        assert!(stack.toast.is_none());
+        stack.record(snapshot(&["a:浏览器"]));
+        assert_eq!(stack.toast.as_ref().map(|toast| toast.label.as_str()), Some("删除“编辑器”"));
+        stack.undo();
+        assert!(stack.toast.is_none());
+
+        // 指定说明的修改不显示提示
+        stack.record_as(snapshot(&[]), "移动到工作区".to_string());
+        assert!(stack.toast.is_none());
+    }
+
+    #[test]
+    fn limits_steps() {
+        let mut stack = UndoStack::default();
+        for index in 0..MAX_UNDO_STEPS + 5 {
+            let item = format!("{}:{}", index, index);
+            stack.record(snapshot(&[item.as_str()]));
+        }
+
+        let mut steps = 0;
+        while stack.undo().is_some() {
+            steps += 1;
+        }
+        assert_eq!(steps, MAX_UNDO_STEPS);
+
+        // 重新读取配置后清空记录
+        stack.reset(LinkSnapshot::default());
+        assert_eq!(stack.undo_label(), None);
+        assert_eq!(stack.redo_label(), None);
+    }
+
+    #[test]
+    fn keeps_icons_referenced_by_history() {
+        let mut stack = UndoStack::default();
+        let mut with_icon = link("a", "浏览器");
+        with_icon.icon_path = "/icons/browser.png".to_string();
+
+        stack.reset(LinkSnapshot::new(vec![with_icon], HashSet::new(), HashSet::new(), Vec::new()));
+        stack.record(LinkSnapshot::default());
+        assert!(stack.references_icon("/icons/browser.png"));
+        assert!(!stack.references_icon("/icons/other.png"));
+
+        // 重做记录中的图标同样保留
+        stack.undo();
+        assert!(stack.references_icon("/icons/browser.png"));
+
+        stack.reset(LinkSnapshot::default());
+        assert!(!stack.references_icon("/icons/browser.png"));
+    }
+}
@@ -0,0 +1,284 @@
+//! 监视快捷方式配置文件的外部修改
+//!
+//! 后台定时检查配置文件的修改时间与大小，发生变化时通知窗口重新读取。
+//! 自己保存时同样会触发通知，读取到的内容与最近一次同步的内容相同时会被忽略
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use log::debug;
+
+use crate::my_structs::ProgramLink;
+use super::save;
+use super::trash::TrashedLink;
+use crate::window::event::UserEvent;
+
+
+/// 检查配置文件的间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+
+/// 用户层的快捷方式、标签、隐藏的共享快捷方式、回收站与标题，用于比较内存与磁盘上的配置
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LinkSnapshot {
+    pub program_links: Vec<ProgramLink>,
+    pub tags: HashSet<String>,
+    pub hidden: HashSet<String>,
+    pub trash: Vec<TrashedLink>,
+    pub title: Option<String>,
+}
+
+impl LinkSnapshot {
+    /// 快捷方式中不存在的标签会被移除，与保存时的内容一致
+    ///
+    /// 回收站中的快捷方式保留原有的标签，还原时重新创建
+    pub fn new(program_links: Vec<ProgramLink>, tags: HashSet<String>, hidden: HashSet<String>, trash: Vec<TrashedLink>) -> Self {
+        let program_links = program_links.into_iter().map(|mut link| {
+            link.tags.retain(|tag| tags.contains(tag));
+            link
+        }).collect();
+
+        Self {
+            program_links: program_links,
+            tags: tags,
+            hidden: hidden,
+            trash: trash,
+            title: None,
+        }
+    }
+
+    pub fn with_title(mut self, title: Option<String>) -> Self {
+        self.title = title;
+        self
+    }
+
+    fn find(&self, uuid: &str) -> Option<&ProgramLink> {
+        self.program_links.iter().find(|link| link.uuid == uuid)
+    }
+}
+
+
+/// 文件的修改时间与大小
+fn fingerprint(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// 在后台监视当前工作区的配置文件，发生变化时发送 [`UserEvent::ConfigFileChanged`]
+///
+/// 切换工作区后改为监视新的配置文件，切换本身不会发送通知
+pub fn spawn_watcher(proxy: winit::event_loop::EventLoopProxy<UserEvent>) {
+    tokio::spawn(async move {
+        let mut path = save::config_path();
+        let mut last = fingerprint(&path);
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let current_path = save::config_path();
+            if current_path != path {
+                last = fingerprint(&current_path);
+                path = current_path;
+                continue;
+            }
+
+            let current = fingerprint(&path);
+            if current == last {
+                continue;
+            }
+            last = current;
+
+            debug!("配置文件 {} 发生变化", path.display());
+            // 事件循环已经退出
+            if proxy.send_event(UserEvent::ConfigFileChanged).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+
+/// 三方合并：以磁盘上的配置为基础，加入内存中相对上次同步的修改
+///
+/// 快捷方式按 uuid 对应，两边都修改了同一个快捷方式时以内存中的为准；
+/// 一边删除而另一边修改过的快捷方式会被保留。
+/// 顺序以磁盘上的为准，磁盘上的顺序没有变化时使用内存中的顺序
+pub fn merge_snapshots(base: &LinkSnapshot, mine: &LinkSnapshot, theirs: &LinkSnapshot) -> LinkSnapshot {
+    let mut program_links = Vec::new();
+
+    for theirs_link in theirs.program_links.iter() {
+        let base_link = base.find(&theirs_link.uuid);
+        match (mine.find(&theirs_link.uuid), base_link) {
+            (Some(mine_link), _) if Some(mine_link) != base_link => program_links.push(mine_link.clone()),
+            (Some(_), _) => program_links.push(theirs_link.clone()),
+            // 内存中删除了，磁盘上也没有修改
+            (None, Some(base_link)) if base_link == theirs_link => {}
+            (None, _) => program_links.push(theirs_link.clone()),
+        }
+    }
+
+    // 只存在于内存中的快捷方式
+    for mine_link in mine.program_links.iter() {
+        if theirs.find(&mine_link.uuid).is_some() {
+            continue;
+        }
+        match base.find(&mine_link.uuid) {
+            // 磁盘上删除了，内存中也没有修改
+            Some(base_link) if base_link == mine_link => {}
+            _ => program_links.push(mine_link.clone()),
+        }
+    }
+
+    // 只有内存中调整了顺序
+    if uuids(theirs) == uuids(base) && uuids(mine) != uuids(base) {
+        let order = uuids(mine);
+        program_links.sort_by_key(|link: &ProgramLink| {
+            order.iter().position(|uuid| *uuid == link.uuid).unwrap_or(usize::MAX)
+        });
+    }
+
+    LinkSnapshot::new(
+        program_links,
+        merge_sets(&base.tags, &mine.tags, &theirs.tags),
+        merge_sets(&base.hidden, &mine.hidden, &theirs.hidden),
+        merge_trash(&base.trash, &mine.trash, &theirs.trash),
+    )
+    .with_title(if mine.title != base.title { mine.title.clone() } else { theirs.title.clone() })
+}
+
+fn uuids(snapshot: &LinkSnapshot) -> Vec<&str> {
+    snapshot.program_links.iter().map(|link| link.uuid.as_str()).collect()
+}
+
+/// 在磁盘上的集合中加入内存中新增的项，并移除内存中删除的项
+fn merge_sets(base: &HashSet<String>, mine: &HashSet<String>, theirs: &HashSet<String>) -> HashSet<String> {
+    let mut merged = theirs.clone();
+    merged.extend(mine.difference(base).cloned());
+    for item in base.difference(mine) {
+        merged.remove(item);
+    }
+    merged
+}
+
+/// 按 uuid 合并回收站，规则与 [`merge_sets`] 相同
+fn merge_trash(base: &[TrashedLink], mine: &[TrashedLink], theirs: &[TrashedLink]) -> Vec<TrashedLink> {
+    let contains = |trash: &[TrashedLink], item: &TrashedLink| {
+        trash.iter().any(|trashed| trashed.program_link.uuid == item.program_link.uuid)
+    };
+
+    let mut merged: Vec<TrashedLink> = theirs
+        .iter()
+        .filter(|item| !contains(base, item) || contains(mine, item))
+        .cloned()
+        .collect();
+    for item in mine.iter() {
+        if !contains(base, item) && !contains(&merged, item) {
+            merged.push(item.clone());
+        }
+    }
+    merged
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(uuid: &str, name: &str) -> ProgramLink {
+        ProgramLink {
+            name: name.to_string(),
+            uuid: uuid.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// 每个快捷方式写作 "uuid:名称"
+    fn snapshot(links: &[&str]) -> LinkSnapshot {
+        let program_links = links.iter().map(|item| {
+            let (uuid, name) = item.split_once(':').unwrap();
+            link(uuid, name)
+        }).collect();
+        LinkSnapshot::new(program_links, HashSet::new(), HashSet::new(), Vec::new())
+    }
+
+    fn names(snapshot: &LinkSnapshot) -> Vec<String> {
+        snapshot.program_links.iter().map(|link| format!("{}:{}", link.uuid, link.name)).collect()
+    }
+
+    #[test]
+    fn merges_links() {
+        // (上次同步, 内存, 磁盘, 合并结果)
+        let cases: &[(&[&str], &[&str], &[&str], &[&str])] = &[
+            // 都没有修改
+            (&["a:A", "b:B"], &["a:A", "b:B"], &["a:A", "b:B"], &["a:A", "b:B"]),
+            // 只有内存中修改
+            (&["a:A", "b:B"], &["a:A2", "b:B", "c:C"], &["a:A", "b:B"], &["a:A2", "b:B", "c:C"]),
+            (&["a:A", "b:B"], &["b:B"], &["a:A", "b:B"], &["b:B"]),
+            // 只有磁盘上修改
+            (&["a:A", "b:B"], &["a:A", "b:B"], &["a:A", "b:B2", "c:C"], &["a:A", "b:B2", "c:C"]),
+            (&["a:A", "b:B"], &["a:A", "b:B"], &["a:A"], &["a:A"]),
+            // 两边修改不同的快捷方式
+            (&["a:A", "b:B"], &["a:A2", "b:B"], &["a:A", "b:B2"], &["a:A2", "b:B2"]),
+            // 两边修改同一个快捷方式时以内存中的为准
+            (&["a:A", "b:B"], &["a:Mine", "b:B"], &["a:Theirs", "b:B"], &["a:Mine", "b:B"]),
+            // 内存中删除，磁盘上修改
+            (&["a:A", "b:B"], &["b:B"], &["a:A2", "b:B"], &["a:A2", "b:B"]),
+            // 磁盘上删除，内存中修改
+            (&["a:A", "b:B"], &["a:A2", "b:B"], &["b:B"], &["b:B", "a:A2"]),
+            // 两边都删除
+            (&["a:A", "b:B"], &["b:B"], &["b:B"], &["b:B"]),
+            // 两边新增
+            (&["a:A"], &["a:A", "b:B"], &["a:A", "c:C"], &["a:A", "c:C", "b:B"]),
+            // 只有内存中调整顺序
+            (&["a:A", "b:B", "c:C"], &["c:C", "a:A", "b:B"], &["a:A", "b:B", "c:C"], &["c:C", "a:A", "b:B"]),
+            (&["a:A", "b:B", "c:C"], &["c:C", "a:A2", "b:B"], &["a:A", "b:B", "c:C2"], &["c:C2", "a:A2", "b:B"]),
+            // 只有磁盘上调整顺序
+            (&["a:A", "b:B", "c:C"], &["a:A", "b:B2", "c:C"], &["b:B", "c:C", "a:A"], &["b:B2", "c:C", "a:A"]),
+            // 两边都调整顺序时以磁盘上的为准
+            (&["a:A", "b:B", "c:C"], &["c:C", "b:B", "a:A"], &["b:B", "a:A", "c:C"], &["b:B", "a:A", "c:C"]),
+        ];
+        for (base, mine, theirs, expected) in cases {
+            let merged = merge_snapshots(&snapshot(base), &snapshot(mine), &snapshot(theirs));
+            assert_eq!(names(&merged), *expected, "base {:?} mine {:?} theirs {:?}", base, mine, theirs);
+        }
+    }
+
+    #[test]
+    fn merges_tags_and_hidden() {
+        let set = |items: &[&str]| items.iter().map(|item| item.to_string()).collect::<HashSet<_>>();
+
+        let base = LinkSnapshot::new(Vec::new(), set(&["游戏", "工具"]), set(&["x"]), Vec::new());
+        let mine = LinkSnapshot::new(Vec::new(), set(&["游戏", "办公"]), set(&["x", "y"]), Vec::new());
+        let theirs = LinkSnapshot::new(Vec::new(), set(&["游戏", "工具", "音乐"]), set(&[]), Vec::new());
+
+        let merged = merge_snapshots(&base, &mine, &theirs);
+        assert_eq!(merged.tags, set(&["游戏", "办公", "音乐"]));
+        assert_eq!(merged.hidden, set(&["y"]));
+    }
+
+    #[test]
+    fn merges_trash_and_title() {
+        let trashed = |uuid: &str| TrashedLink::new(link(uuid, uuid));
+        let trash_uuids = |snapshot: &LinkSnapshot| {
+            snapshot.trash.iter().map(|item| item.program_link.uuid.clone()).collect::<Vec<_>>()
+        };
+
+        let base = LinkSnapshot::new(Vec::new(), HashSet::new(), HashSet::new(), vec![trashed("a"), trashed("b")])
+            .with_title(Some("旧标题".to_string()));
+        // 内存中清除了 a，新增了 c
+        let mine = LinkSnapshot::new(Vec::new(), HashSet::new(), HashSet::new(), vec![trashed("b"), trashed("c")])
+            .with_title(Some("旧标题".to_string()));
+        // 磁盘上新增了 d，修改了标题
+        let theirs = LinkSnapshot::new(Vec::new(), HashSet::new(), HashSet::new(), vec![trashed("a"), trashed("b"), trashed("d")])
+            .with_title(Some("新标题".to_string()));
+
+        let merged = merge_snapshots(&base, &mine, &theirs);
+        assert_eq!(trash_uuids(&merged), ["b", "d", "c"]);
+        assert_eq!(merged.title.as_deref(), Some("新标题"));
+
+        // 两边都修改标题时以内存中的为准
+        let mine = mine.with_title(None);
+        assert_eq!(merge_snapshots(&base, &mine, &theirs).title, None);
+    }
+}
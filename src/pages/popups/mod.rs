@@ -1,20 +1,29 @@
 pub mod link;
+mod search_settings;
+mod app_settings;
+mod backups;
+mod config_conflict;
+mod bundle;
+mod bookmarks;
+mod undo;
+mod trash;
+mod profiles;
 
 use std::collections::HashSet;
 
 use link::save;
-use log::{debug, warn};
+use log::debug;
 use crate::my_structs::*;
 
 #[derive(Debug)]
 pub struct LinkToDelete {
-    index_of_the_link: usize,
+    uuid_of_the_link: String,
 }
 
 impl LinkToDelete {
     pub fn new() -> Self {
         Self {
-            index_of_the_link: 0
+            uuid_of_the_link: "".to_string()
         }
     }
 }
@@ -26,11 +35,19 @@ pub enum PopupType {
     CannotSave,
     TagDelete,
     TagNew,
+    SearchSettings,
+    AppSettings,
+    Backups,
+    ExportBundle,
+    ImportBundle,
+    ImportBookmarks,
+    Trash,
+    Profiles,
 
     // 配置文件错误
-    ConfigTooOld,
     ConfigFormatError,
     ConfigNotAJson,
+    ConfigConflict,
 }
 
 #[derive(Debug)]
@@ -44,6 +61,35 @@ pub struct Popups {
     link_to_delete: LinkToDelete,
     tag_to_delete: String,
     tag_new: String,
+    backups: Vec<link::backup::Backup>,
+    // 被外部修改的配置
+    external_links: Option<link::watch::LinkSnapshot>,
+    // 导出时选中的快捷方式的uuid
+    export_selection: HashSet<String>,
+    // 读取到的导入包，读取失败时为错误信息
+    import_bundle: Option<Result<link::bundle::Bundle, String>>,
+    // 导出的格式，None 表示导入包
+    export_format: Option<link::launchers::LauncherFormat>,
+    // 导出失败的提示
+    bundle_message: Option<String>,
+    // 读取到的书签，读取失败时为错误信息
+    bookmarks: Option<Result<Vec<bookmarks::BookmarkEntry>, String>>,
+    // 打开回收站时的自动清理天数
+    trash_retention_days: u32,
+    // 打开设置窗口时的设置，关闭时只应用修改过的部分
+    settings_before: Option<crate::settings::AppSettings>,
+    // 所有工作区
+    profiles: Vec<Option<String>>,
+    // 新建工作区时输入的名称
+    profile_name: String,
+    // 正在重命名的工作区与输入的新名称
+    profile_rename: Option<(String, String)>,
+    // 等待确认删除的工作区
+    profile_to_delete: Option<String>,
+    // 当前工作区的标题
+    profile_title: String,
+    // 工作区操作失败的提示
+    profile_message: Option<String>,
     // pub info: info::Info,
 }
 
@@ -56,6 +102,21 @@ impl Popups {
             link_to_delete: LinkToDelete::new(),
             tag_to_delete: "".to_string(),
             tag_new: "".to_string(),
+            backups: Vec::new(),
+            external_links: None,
+            export_selection: HashSet::new(),
+            import_bundle: None,
+            export_format: None,
+            bundle_message: None,
+            bookmarks: None,
+            trash_retention_days: 0,
+            settings_before: None,
+            profiles: Vec::new(),
+            profile_name: "".to_string(),
+            profile_rename: None,
+            profile_to_delete: None,
+            profile_title: "".to_string(),
+            profile_message: None,
             // info: info::Info::new(),
         }
     }
@@ -66,25 +127,25 @@ impl Popups {
         self.popup_type = Some(PopupType::CannotSave);
     }
 
-    pub fn delete_link(&mut self, position: LinkPosition) {
-        debug!("请求删除快捷方式弹窗，位置: {:?}", position);
+    pub fn delete_link(&mut self, uuid: String) {
+        debug!("请求删除快捷方式弹窗，uuid: {}", uuid);
         self.called = true;
         self.popup_type = Some(PopupType::LinkDelete);
-        self.link_to_delete.index_of_the_link = position.link_index;
+        self.link_to_delete.uuid_of_the_link = uuid;
     }
 
-    pub fn config_existing_link(&mut self, position: LinkPosition, link: &ProgramLink) {
-        debug!("请求配置快捷方式弹窗，位置: {:?}", position);
+    pub fn config_existing_link(&mut self, link: &ProgramLink) {
+        debug!("请求配置快捷方式弹窗，uuid: {}", link.uuid);
         self.called = true;
         self.popup_type = Some(PopupType::LinkConfig);
-        self.link_config.config_existing_link(position, link);
+        self.link_config.config_existing_link(link);
     }
 
-    pub fn config_new_link(&mut self) {
+    pub fn config_new_link(&mut self, defaults: &crate::settings::LaunchSettings) {
         debug!("请求配置新快捷方式弹窗");
         self.called = true;
         self.popup_type = Some(PopupType::LinkConfig);
-        self.link_config.config_new_link();
+        self.link_config.config_new_link(defaults);
     }
 
     pub fn delete_tag(&mut self, tag: String) {
@@ -101,10 +162,24 @@ impl Popups {
         self.popup_type = Some(PopupType::TagNew);
     }
 
-    pub fn config_file_too_old(&mut self) {
-        debug!("请求配置文件过旧弹窗");
+    pub fn search_settings(&mut self) {
+        debug!("请求搜索设置弹窗");
         self.called = true;
-        self.popup_type = Some(PopupType::ConfigTooOld);
+        self.popup_type = Some(PopupType::SearchSettings);
+    }
+
+    pub fn app_settings(&mut self, settings: crate::settings::AppSettings) {
+        debug!("请求设置弹窗");
+        self.called = true;
+        self.popup_type = Some(PopupType::AppSettings);
+        self.settings_before = Some(settings);
+    }
+
+    pub fn backups(&mut self) {
+        debug!("请求恢复备份弹窗");
+        self.called = true;
+        self.popup_type = Some(PopupType::Backups);
+        self.backups = link::backup::list_backups(&save::config_path());
     }
 
     pub fn config_file_format_error(&mut self) {
@@ -113,6 +188,54 @@ impl Popups {
         self.popup_type = Some(PopupType::ConfigFormatError);
     }
 
+    pub fn export_bundle(&mut self, selection: HashSet<String>) {
+        debug!("请求导出快捷方式弹窗");
+        self.called = true;
+        self.popup_type = Some(PopupType::ExportBundle);
+        self.export_selection = selection;
+        self.bundle_message = None;
+    }
+
+    pub fn import_bundle(&mut self, bundle: Result<link::bundle::Bundle, String>) {
+        debug!("请求导入快捷方式弹窗");
+        self.called = true;
+        self.popup_type = Some(PopupType::ImportBundle);
+        self.import_bundle = Some(bundle);
+    }
+
+    pub fn import_bookmarks(&mut self, bookmarks: Result<Vec<bookmarks::BookmarkEntry>, String>) {
+        debug!("请求导入书签弹窗");
+        self.called = true;
+        self.popup_type = Some(PopupType::ImportBookmarks);
+        self.bookmarks = Some(bookmarks);
+    }
+
+    pub fn trash(&mut self, retention_days: u32) {
+        debug!("请求回收站弹窗");
+        self.called = true;
+        self.popup_type = Some(PopupType::Trash);
+        self.trash_retention_days = retention_days;
+    }
+
+    pub fn profiles(&mut self, title: String, message: Option<String>) {
+        debug!("请求工作区弹窗");
+        self.called = true;
+        self.popup_type = Some(PopupType::Profiles);
+        self.profiles = link::profile::list_profiles();
+        self.profile_name = "".to_string();
+        self.profile_rename = None;
+        self.profile_to_delete = None;
+        self.profile_title = title;
+        self.profile_message = message;
+    }
+
+    pub fn config_conflict(&mut self, external_links: link::watch::LinkSnapshot) {
+        debug!("请求配置文件冲突弹窗");
+        self.called = true;
+        self.popup_type = Some(PopupType::ConfigConflict);
+        self.external_links = Some(external_links);
+    }
+
     fn config_not_a_json(&mut self) {
         debug!("请求配置文件不是JSON弹窗");
         self.called = true;
@@ -130,9 +253,17 @@ impl MyApp {
                     PopupType::LinkDelete => self.show_delete_link(ui),
                     PopupType::TagDelete => self.show_delete_tag(ui),
                     PopupType::TagNew => self.show_new_tag(ui),
-                    PopupType::ConfigTooOld => self.show_config_file_too_old(ui),
+                    PopupType::SearchSettings => self.show_search_settings(ui),
+                    PopupType::AppSettings => self.show_app_settings(ui),
+                    PopupType::Backups => self.show_backups(ui),
+                    PopupType::ExportBundle => self.show_export_bundle(ui),
+                    PopupType::ImportBundle => self.show_import_bundle(ui),
+                    PopupType::ImportBookmarks => self.show_import_bookmarks(ui),
+                    PopupType::Trash => self.show_trash(ui),
+                    PopupType::Profiles => self.show_profiles(ui),
                     PopupType::ConfigFormatError => self.show_config_file_format_error(ui),
                     PopupType::ConfigNotAJson => self.show_config_not_a_json(ui),
+                    PopupType::ConfigConflict => self.show_config_conflict(ui),
                     // PopupType::Info => self.popups.info.show(ui),
                     _ => {}
                 }
@@ -242,64 +373,6 @@ impl MyApp {
         }
     }
 
-    fn show_config_file_too_old(&mut self, ui: &mut egui::Ui) {
-        let mut show = self.popups.called.clone();
-        let mut should_close = false;
-        let mut should_force_read = false;
-
-        // 删除快捷方式弹窗
-        egui::Window::new("配置文件版本过旧")
-        .title_bar(false)
-        .collapsible(false)
-        .resizable(false)
-        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
-        .fade_in(true)
-        .fade_out(true)
-        .open(&mut show)
-
-        .show(ui.ctx(), |ui| {
-            ui.vertical_centered(|ui| {
-                ui.heading("配置文件版本过旧");
-                ui.label(
-                    "仍然尝试读取？"
-                );
-                
-                ui.separator();
-                
-                ui.with_layout(egui::Layout {
-                    cross_align: egui::Align::RIGHT,
-                    ..Default::default()
-                }, |ui| {
-                    ui.horizontal(|ui| {
-                        if ui.button(egui::RichText::new("是").color(egui::Color32::RED))
-                        .clicked() {
-                            should_force_read = true;
-                            should_close = true;
-                        }
-                        if ui.button("否").clicked() {
-                            self.wont_save = true;
-                            should_close = true;
-                        }
-                    });
-                });
-            });
-        });
-
-
-        if (!show && !should_close && self.popups.called) || should_close {
-            debug!("配置文件过旧弹窗关闭");
-            // debug!("*你* 关闭了对吧？");
-            // 用户关闭
-            
-            self.popups.called = false;
-
-            if should_force_read {
-                warn!("尝试强制读取配置文件");
-                self.force_read_config();
-            }
-        }
-    }
-
     fn show_new_tag(&mut self, ui: &mut egui::Ui) {
         let mut show = self.popups.called.clone();
         let mut should_close = false;
@@ -444,18 +517,19 @@ impl MyApp {
         .open(&mut show)
 
         .show(ui.ctx(), |ui| {
-            let current_index = self.popups.link_to_delete.index_of_the_link;
+            let uuid = &self.popups.link_to_delete.uuid_of_the_link;
+            let current_index = self.program_links.iter().position(|link| &link.uuid == uuid);
 
             ui.vertical_centered(|ui| {
                 ui.heading("你确定要删除这个快捷方式吗？");
                 ui.label(format!(
-                    "“{}”将会永久消失！（真的很久！）", 
-                    self
+                    "“{}”将会被移到回收站，可以在回收站中还原", 
+                    current_index
                     // 这里不能unwarp的原因是
                     // egui关闭窗口的动画效果会延迟关闭，这段时间内仍然会被使用
-                    .program_links.get(current_index).unwrap_or(&ProgramLink::default())
+                    .map_or(&ProgramLink::default(), |index| &self.program_links[index])
                     //(ProgramLink { name: "已删除".to_string(), ..Default::default()})
-                    .name.get(0).unwrap_or(&"已删除".to_string())
+                    .name
                 ));
                 
                 ui.separator();
@@ -467,6 +541,11 @@ impl MyApp {
                     ui.horizontal(|ui| {
                         if ui.button(egui::RichText::new("确定").color(egui::Color32::RED))
                         .clicked() {
+                            // 快捷方式可能已经被外部修改删除
+                            let Some(current_index) = current_index else {
+                                should_close = true;
+                                return;
+                            };
                             let program_links = &mut self.program_links;
 
                             if let Some(icon_path) = self.cached_icon.get_mut(&program_links[current_index].icon_path) {
@@ -477,9 +556,13 @@ impl MyApp {
                             }
                             self.icon_will_clean.push(program_links[current_index].icon_path.clone());
 
-                            let name = program_links[current_index].name.clone();
-                            program_links.remove(current_index);
-                            debug!("删除成功: {:?}", name);
+                            let program_link = program_links.remove(current_index);
+                            // 删除覆盖时同时隐藏共享目录中的版本，否则它会重新出现
+                            if self.catalog.contains(&program_link.uuid) {
+                                self.hidden_links.insert(program_link.uuid.clone());
+                            }
+                            debug!("删除成功: {:?}", program_link.name);
+                            self.trash.push(link::trash::TrashedLink::new(program_link));
 
                             should_save = true;
                             should_close = true;
@@ -509,12 +592,12 @@ impl MyApp {
 
 impl MyApp {
     fn config_auto_fix(&mut self) {
-        let config_file = crate::pages::popups::link::save::load_conf(crate::CONFIG_FILE_NAME);
+        let config_file = save::load_conf(&save::config_path());
 
         match config_file {
             Ok(links_config) => {
                 // 开始尝试修复
-                let mut new_links_config = crate::pages::popups::link::save::LinkConfigSchema::default();
+                let mut new_links_config = save::LinkConfigSchema::default();
 
                 // 尝试获取版本号
                 if let Some(version) = links_config.get("version") {
@@ -542,16 +625,19 @@ impl MyApp {
                         for program_link in program_links_list {
                             let mut new_program_link = ProgramLink::default();
 
-                            // 尝试获取name
-                            if let Some(name) = program_link.get("name") {
-                                if let Some(name_list) = name.as_array() {
-                                    for name_item in name_list {
-                                        if let Some(name_str) = name_item.as_str() {
-                                            new_program_link.name.push(name_str.to_string());
+                            // 尝试获取name，旧版本的别名也存放在其中
+                            let (name, keywords) = link::migrate::split_legacy_names(program_link.get("name"));
+                            new_program_link.name = name;
+                            new_program_link.keywords = keywords;
+
+                            // 尝试获取keywords
+                            if let Some(keywords) = program_link.get("keywords") {
+                                if let Some(keywords_list) = keywords.as_array() {
+                                    for keyword in keywords_list {
+                                        if let Some(keyword_str) = keyword.as_str() {
+                                            new_program_link.keywords.push(keyword_str.to_string());
                                         }
                                     }
-                                } else if let Some(name_str) = name.as_str() {
-                                    new_program_link.name.push(name_str.to_string());
                                 }
                             }
 
@@ -621,8 +707,24 @@ impl MyApp {
                     }
                 }
 
-                self.program_links = new_links_config.program_links;
-                self.tags = new_links_config.tags;
+                // 尝试获取回收站，无法解析时放弃
+                if let Some(trash) = links_config.get("trash") {
+                    new_links_config.trash = serde_json::from_value(trash.clone()).unwrap_or_default();
+                }
+
+                // 尝试获取标题
+                if let Some(title) = links_config.get("title") {
+                    if let Some(title_str) = title.as_str() {
+                        new_links_config.title = Some(title_str.to_string());
+                    }
+                }
+
+                self.apply_links(link::watch::LinkSnapshot::new(
+                    new_links_config.program_links,
+                    new_links_config.tags,
+                    new_links_config.hidden,
+                    new_links_config.trash,
+                ).with_title(new_links_config.title));
 
                 self.save_conf();
             }
@@ -632,31 +734,21 @@ impl MyApp {
         }
     }
 
-    fn force_read_config(&mut self) {
-        let (program_links, tags) = match serde_json::from_value::<crate::pages::popups::link::save::LinkConfigSchema>(crate::pages::popups::link::save::load_conf(crate::CONFIG_FILE_NAME).unwrap()) {
-            Ok(links_config) => (links_config.program_links, links_config.tags),
-            Err(e) => {
-                debug!("读取配置文件失败: {}", e);
-                self.popups.config_file_format_error();
-                (Vec::new(), HashSet::new())
-            }
-        };
-
-        self.program_links = program_links;
-        self.tags = tags;
+    /// 记录这次修改以便撤销，再保存到磁盘
+    pub fn save_conf(&mut self) {
+        self.undo.record(self.links_snapshot());
+        self.write_conf();
     }
 
-    pub fn save_conf(&mut self) {
+    /// 保存到磁盘，不记录修改
+    pub fn write_conf(&mut self) {
         if !self.wont_save {
-            match save::save_conf(
-                self.program_links.clone().into_iter().map(|mut link| {
-                    link.tags = link.tags.clone().into_iter().filter(|tag| self.tags.contains(tag)).collect();
-                    link
-                }).collect()
-                , 
-                self.tags.clone()
-            ) {
-                Ok(_) => debug!("保存成功"),
+            let links = self.links_snapshot();
+            match save::save_conf(links.clone()) {
+                Ok(_) => {
+                    debug!("保存成功");
+                    self.synced_links = links;
+                },
                 Err(e) => {
                     debug!("保存失败: {}", e);
                     self.popups.cannot_save();
@@ -0,0 +1,339 @@
+use egui;
+use log::{debug, error, info};
+
+use crate::my_structs::*;
+use crate::window::event::UserEvent;
+use super::link::{profile, watch::LinkSnapshot};
+
+
+/// 工作区窗口中的操作
+#[derive(Debug, Clone)]
+enum ProfileAction {
+    Switch(Option<String>),
+    // 新建工作区，是否复制当前工作区的快捷方式
+    Create(bool),
+    Rename(String, String),
+    Delete(String),
+}
+
+
+impl MyApp {
+    /// 标题栏显示的标题，没有设置时使用工作区的名称
+    pub fn display_title(&self) -> String {
+        match (&self.title, profile::current()) {
+            (Some(title), _) => title.clone(),
+            (None, Some(name)) => name,
+            (None, None) => profile::DEFAULT_TITLE.to_string(),
+        }
+    }
+
+    pub fn open_profiles(&mut self) {
+        self.popups.profiles(self.display_title(), None);
+    }
+
+    /// 切换到另一个工作区，返回是否切换成功
+    ///
+    /// 弹窗打开时可能正在编辑快捷方式，不会切换。新的工作区无法读取时保留当前的工作区
+    pub fn switch_profile(&mut self, name: Option<String>) -> bool {
+        if name == profile::current() {
+            return true;
+        }
+        if self.popups.called {
+            debug!("已有弹窗，不切换工作区");
+            return false;
+        }
+
+        let links = match load_links(&profile::profile_path(name.as_deref())) {
+            Ok(links) => links,
+            Err(e) => {
+                error!("无法读取工作区 {}: {}", profile::display_name(name.as_deref()), e);
+                self.popups.profiles(self.display_title(), Some(format!(
+                    "无法读取工作区“{}”: {}", profile::display_name(name.as_deref()), e
+                )));
+                return false;
+            }
+        };
+
+        info!("切换到工作区: {}", profile::display_name(name.as_deref()));
+        profile::set_current(name.clone());
+        self.settings.profile = name;
+        self.save_settings();
+
+        self.current_tag = None;
+        self.search_text.clear();
+        self.config_changed = false;
+        // 之前的工作区可能因为无法读取而禁止保存，新的工作区已经成功读取
+        self.wont_save = false;
+        self.apply_links(links);
+        self.synced_links = self.links_snapshot();
+        if self.purge_expired_trash() {
+            self.write_conf();
+        }
+        self.undo.reset(self.links_snapshot());
+
+        self.proxy.send_event(UserEvent::ProfilesChanged).unwrap();
+        true
+    }
+
+    /// Ctrl+Tab 切换到下一个工作区，Ctrl+1 到 Ctrl+9 切换到对应的工作区
+    pub fn handle_profile_shortcuts(&mut self, ctx: &egui::Context) {
+        if self.popups.called {
+            return;
+        }
+
+        const NUMBER_KEYS: [egui::Key; 9] = [
+            egui::Key::Num1, egui::Key::Num2, egui::Key::Num3,
+            egui::Key::Num4, egui::Key::Num5, egui::Key::Num6,
+            egui::Key::Num7, egui::Key::Num8, egui::Key::Num9,
+        ];
+
+        let next = ctx.input_mut(|i| i.consume_shortcut(&egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Tab)));
+        let number = NUMBER_KEYS.iter().position(|key| {
+            ctx.input_mut(|i| i.consume_shortcut(&egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, *key)))
+        });
+
+        if next {
+            self.switch_profile(profile::next_profile(profile::current().as_deref()));
+        } else if let Some(index) = number {
+            if let Some(name) = profile::list_profiles().into_iter().nth(index) {
+                self.switch_profile(name);
+            }
+        }
+    }
+
+    /// 将快捷方式复制或移动到另一个工作区，移动时从当前工作区中移除
+    pub fn send_link_to_profile(&mut self, uuid: &str, target: Option<String>, should_move: bool) {
+        let Some(program_link) = self.program_links.iter().find(|link| link.uuid == uuid).cloned() else {
+            return;
+        };
+        let target_name = profile::display_name(target.as_deref()).to_string();
+
+        if let Err(e) = profile::copy_links_to(target.as_deref(), vec![program_link.clone()]) {
+            error!("复制快捷方式到工作区 {} 失败: {}", target_name, e);
+            self.popups.profiles(self.display_title(), Some(format!(
+                "无法将“{}”复制到工作区“{}”: {}", program_link.name, target_name, e
+            )));
+            return;
+        }
+        if !should_move || program_link.catalog.is_some() {
+            return;
+        }
+
+        // 移动后目标工作区中已经有了副本，撤销时只会还原当前工作区
+        self.program_links.retain(|link| link.uuid != uuid);
+        let links = self.links_snapshot();
+        self.apply_links(links);
+        self.undo.record_as(self.links_snapshot(), format!("将“{}”移到工作区“{}”", program_link.name, target_name));
+        self.write_conf();
+    }
+
+    pub fn show_profiles(&mut self, ui: &mut egui::Ui) {
+        let mut show = self.popups.called.clone();
+        let mut should_close = false;
+        let mut action = None;
+        let current = profile::current();
+
+        egui::Window::new("工作区")
+        .collapsible(false)
+        .resizable(false)
+        .default_pos((self.settings.window.size() / 2.0).to_pos2())
+        .fade_in(true)
+        .fade_out(true)
+        .open(&mut show)
+
+        .show(ui.ctx(), |ui| {
+            ui.label(egui::RichText::new("每个工作区有自己的快捷方式、标签与标题，Ctrl+Tab 或 Ctrl+数字键可以快速切换").weak());
+            ui.horizontal(|ui| {
+                ui.label("当前工作区的标题");
+                ui.text_edit_singleline(&mut self.popups.profile_title);
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+            .max_height(256.)
+            .show(ui, |ui| {
+                egui::Grid::new("profiles")
+                .num_columns(4)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (index, name) in self.popups.profiles.iter().enumerate() {
+                        let is_current = *name == current;
+                        // 前九个工作区可以用 Ctrl+数字键切换
+                        let label = if index < 9 {
+                            format!("{}  Ctrl+{}", profile::display_name(name.as_deref()), index + 1)
+                        } else {
+                            profile::display_name(name.as_deref()).to_string()
+                        };
+
+                        match (&mut self.popups.profile_rename, name) {
+                            (Some((from, to)), Some(name)) if from == name => {
+                                ui.text_edit_singleline(to);
+                                if ui.button("确定").clicked() {
+                                    action = Some(ProfileAction::Rename(from.clone(), to.trim().to_string()));
+                                }
+                            }
+                            _ => {
+                                ui.label(if is_current { egui::RichText::new(label).strong() } else { egui::RichText::new(label) });
+                                if ui.add_enabled(!is_current, egui::Button::new("切换")).clicked() {
+                                    action = Some(ProfileAction::Switch(name.clone()));
+                                }
+                            }
+                        }
+
+                        // 默认工作区不能重命名或删除
+                        if let Some(name) = name {
+                            if ui.button("重命名").clicked() {
+                                self.popups.profile_rename = Some((name.clone(), name.clone()));
+                            }
+                            if self.popups.profile_to_delete.as_ref() == Some(name) {
+                                if ui.button(egui::RichText::new("确认删除").color(egui::Color32::RED)).clicked() {
+                                    action = Some(ProfileAction::Delete(name.clone()));
+                                }
+                            } else if ui.add_enabled(!is_current, egui::Button::new(
+                                egui::RichText::new("删除").color(egui::Color32::RED)
+                            )).on_disabled_hover_text("不能删除当前的工作区").clicked() {
+                                self.popups.profile_to_delete = Some(name.clone());
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.popups.profile_name).hint_text("新工作区的名称"));
+                if ui.button("新建").clicked() {
+                    action = Some(ProfileAction::Create(false));
+                }
+                if ui.button("复制当前工作区").clicked() {
+                    action = Some(ProfileAction::Create(true));
+                }
+            });
+
+            if let Some(message) = &self.popups.profile_message {
+                ui.label(egui::RichText::new(message).color(egui::Color32::LIGHT_RED));
+            }
+
+            ui.separator();
+            ui.with_layout(egui::Layout {
+                cross_align: egui::Align::RIGHT,
+                ..Default::default()
+            }, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("完成").clicked() {
+                        should_close = true;
+                    }
+                    if ui.button("打开工作区文件夹").clicked() {
+                        // 还没有新建过工作区时文件夹不存在
+                        if let Err(e) = std::fs::create_dir_all(profile::profile_dir()) {
+                            error!("创建工作区文件夹失败: {}", e);
+                        }
+                        crate::search::provider::open_path(&profile::profile_dir());
+                    }
+                });
+            });
+        });
+
+        if let Some(action) = action {
+            debug!("工作区操作: {:?}", action);
+            self.popups.profile_message = None;
+            match action {
+                ProfileAction::Switch(name) => {
+                    // 先关闭弹窗并保存标题，切换后再重新打开
+                    self.popups.called = false;
+                    self.apply_profile_title();
+                    if self.switch_profile(name) {
+                        self.open_profiles();
+                    }
+                    return;
+                }
+                ProfileAction::Create(copy) => self.create_profile(copy),
+                ProfileAction::Rename(from, to) => self.rename_profile(&from, &to),
+                ProfileAction::Delete(name) => {
+                    self.popups.profile_to_delete = None;
+                    if let Err(e) = profile::delete_profile(&name) {
+                        error!("删除工作区 {} 失败: {}", name, e);
+                        self.popups.profile_message = Some(format!("删除失败: {}", e));
+                    }
+                }
+            }
+            self.popups.profiles = profile::list_profiles();
+            self.proxy.send_event(UserEvent::ProfilesChanged).unwrap();
+        }
+
+        if (!show && !should_close && self.popups.called) || should_close {
+            debug!("工作区弹窗关闭");
+            self.popups.called = false;
+            self.apply_profile_title();
+        }
+    }
+
+    /// 保存修改过的标题，清空或与默认标题相同时不再单独保存
+    fn apply_profile_title(&mut self) {
+        let title = self.popups.profile_title.trim().to_string();
+        if title == self.display_title() {
+            return;
+        }
+
+        let default_title = profile::current().unwrap_or_else(|| profile::DEFAULT_TITLE.to_string());
+        self.title = if title.is_empty() || title == default_title { None } else { Some(title) };
+        info!("修改工作区标题: {}", self.display_title());
+        self.save_conf();
+    }
+
+    fn create_profile(&mut self, copy: bool) {
+        let name = self.popups.profile_name.trim().to_string();
+        if let Err(message) = profile::validate_name(&name) {
+            self.popups.profile_message = Some(message);
+            return;
+        }
+
+        // 复制时不包含回收站，标题使用新工作区的名称
+        let links = if copy {
+            LinkSnapshot {
+                trash: Vec::new(),
+                title: None,
+                ..self.links_snapshot()
+            }
+        } else {
+            LinkSnapshot::default()
+        };
+        match profile::create_profile(&name, links) {
+            Ok(_) => self.popups.profile_name.clear(),
+            Err(e) => {
+                error!("创建工作区 {} 失败: {}", name, e);
+                self.popups.profile_message = Some(format!("创建失败: {}", e));
+            }
+        }
+    }
+
+    fn rename_profile(&mut self, from: &str, to: &str) {
+        if from == to {
+            self.popups.profile_rename = None;
+            return;
+        }
+        if let Err(message) = profile::validate_name(to) {
+            self.popups.profile_message = Some(message);
+            return;
+        }
+
+        if let Err(e) = profile::rename_profile(from, to) {
+            error!("重命名工作区 {} 失败: {}", from, e);
+            self.popups.profile_message = Some(format!("重命名失败: {}", e));
+            return;
+        }
+        self.popups.profile_rename = None;
+
+        // 重命名当前工作区时同时更新设置
+        if profile::current().as_deref() == Some(from) {
+            let title_unchanged = self.popups.profile_title == from;
+            profile::set_current(Some(to.to_string()));
+            self.settings.profile = Some(to.to_string());
+            self.save_settings();
+            if title_unchanged {
+                self.popups.profile_title = to.to_string();
+            }
+        }
+    }
+}
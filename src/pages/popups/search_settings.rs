@@ -0,0 +1,214 @@
+use egui;
+use log::debug;
+
+use crate::my_structs::*;
+use crate::search::{Scorer, SearchSettings, WebTemplate};
+
+
+impl MyApp {
+    pub fn show_search_settings(&mut self, ui: &mut egui::Ui) {
+        let mut show = self.popups.called.clone();
+        let mut should_close = false;
+
+        egui::Window::new("搜索设置")
+        .collapsible(false)
+        .resizable(false)
+        .default_pos((self.settings.window.size() / 2.0).to_pos2())
+        .fade_in(true)
+        .fade_out(true)
+        .open(&mut show)
+
+        .show(ui.ctx(), |ui| {
+            let search = &mut self.settings.search;
+
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_label("匹配算法")
+                .selected_text(search.scorer.label())
+                .show_ui(ui, |ui| {
+                    for scorer in Scorer::ALL {
+                        ui.selectable_value(&mut search.scorer, scorer, scorer.label());
+                    }
+                });
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("相似度阈值");
+                ui.add(egui::Slider::new(&mut search.threshold, 0.0..=1.0).step_by(0.05));
+            });
+
+            ui.separator();
+            ui.label("字段权重");
+            ui.label(egui::RichText::new("权重为 0 的字段不参与匹配").weak());
+
+            egui::Grid::new("search_weights")
+            .num_columns(2)
+            .show(ui, |ui| {
+                let weights = &mut search.weights;
+                for (label, weight) in [
+                    ("名称", &mut weights.name),
+                    ("别名", &mut weights.alias),
+                    ("拼音", &mut weights.pinyin),
+                    ("命令", &mut weights.command),
+                    ("参数", &mut weights.arguments),
+                ] {
+                    ui.label(label);
+                    ui.add(egui::Slider::new(weight, 0.0..=2.0).step_by(0.1));
+                    ui.end_row();
+                }
+            });
+
+            ui.separator();
+            ui.checkbox(&mut search.fold_chinese, "繁简通搜")
+                .on_hover_text("搜索时将繁体字与简体字视为同一个字");
+            ui.checkbox(&mut search.show_score, "在搜索结果上显示得分（调试）");
+
+            ui.separator();
+            ui.collapsing("文件搜索", |ui| {
+                let files = &mut search.files;
+                ui.label(egui::RichText::new("以下目录中的文件与文件夹会出现在搜索结果中").weak());
+
+                let mut remove_directory = None;
+                for (index, directory) in files.directories.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("🗑").clicked() {
+                            remove_directory = Some(index);
+                        }
+                        ui.label(directory);
+                    });
+                }
+                if let Some(index) = remove_directory {
+                    files.directories.remove(index);
+                }
+                if ui.button("添加目录").clicked() {
+                    if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                        let directory = path.display().to_string();
+                        if !files.directories.contains(&directory) {
+                            files.directories.push(directory);
+                        }
+                    }
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("最大深度");
+                    ui.add(egui::Slider::new(&mut files.max_depth, 1..=10));
+                });
+
+                ui.label("忽略的文件名")
+                    .on_hover_text("支持 * 与 ? 通配符，包含 / 时匹配相对路径");
+                let mut remove_pattern = None;
+                for (index, pattern) in files.ignore_patterns.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("🗑").clicked() {
+                            remove_pattern = Some(index);
+                        }
+                        ui.text_edit_singleline(pattern);
+                    });
+                }
+                if let Some(index) = remove_pattern {
+                    files.ignore_patterns.remove(index);
+                }
+                if ui.button("添加规则").clicked() {
+                    files.ignore_patterns.push("".to_string());
+                }
+            });
+
+            ui.collapsing("网页搜索", |ui| {
+                let web = &mut search.web;
+                ui.label(egui::RichText::new("输入 关键字 + 空格 + 搜索内容，网址中的 {query} 会被替换为搜索内容").weak());
+
+                let mut remove_template = None;
+                egui::Grid::new("web_templates")
+                .num_columns(4)
+                .show(ui, |ui| {
+                    ui.label("");
+                    ui.label("关键字");
+                    ui.label("名称");
+                    ui.label("网址");
+                    ui.end_row();
+
+                    for (index, template) in web.templates.iter_mut().enumerate() {
+                        if ui.small_button("🗑").clicked() {
+                            remove_template = Some(index);
+                        }
+                        ui.add(egui::TextEdit::singleline(&mut template.keyword).desired_width(48.));
+                        ui.add(egui::TextEdit::singleline(&mut template.name).desired_width(80.));
+                        ui.add(egui::TextEdit::singleline(&mut template.url).desired_width(240.));
+                        ui.end_row();
+                    }
+                });
+                if let Some(index) = remove_template {
+                    web.templates.remove(index);
+                }
+                if ui.button("添加模板").clicked() {
+                    web.templates.push(WebTemplate::default());
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("后备模板")
+                        .on_hover_text("没有任何搜索结果时使用，留空则不启用");
+                    ui.add(egui::TextEdit::singleline(&mut web.fallback).desired_width(240.));
+                });
+            });
+
+            ui.collapsing("命令模式", |ui| {
+                let shell = &mut search.shell;
+                ui.label(egui::RichText::new("以 $ 开头的内容会作为命令交给 shell 执行").weak());
+
+                egui::Grid::new("shell_settings")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Shell")
+                        .on_hover_text("命令会作为最后一个参数传入");
+                    ui.add(egui::TextEdit::singleline(&mut shell.shell).desired_width(240.));
+                    ui.end_row();
+
+                    ui.label("终端")
+                        .on_hover_text("放在 shell 之前，用于在新的终端窗口中运行，留空则在后台运行");
+                    ui.add(egui::TextEdit::singleline(&mut shell.terminal).desired_width(240.));
+                    ui.end_row();
+                });
+                ui.checkbox(&mut shell.capture_output, "捕获输出并显示在结果面板中");
+            });
+
+            ui.collapsing("搜索历史", |ui| {
+                let history = &mut search.history;
+                ui.checkbox(&mut history.enabled, "记录搜索历史")
+                    .on_hover_text("关闭后不会再记录，已有的历史会被清空");
+                ui.add_enabled_ui(history.enabled, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("最多保留");
+                        ui.add(egui::Slider::new(&mut history.max_entries, 1..=200));
+                    });
+                });
+                if ui.button(format!("清空搜索历史 ({} 条)", self.search_history.entries().len())).clicked() {
+                    self.search_history.clear();
+                }
+            });
+
+            ui.separator();
+            ui.with_layout(egui::Layout {
+                cross_align: egui::Align::RIGHT,
+                ..Default::default()
+            }, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("完成").clicked() {
+                        should_close = true;
+                    }
+                    if ui.button("恢复默认").clicked() {
+                        *search = SearchSettings::default();
+                    }
+                });
+            });
+        });
+
+        if (!show && !should_close && self.popups.called) || should_close {
+            debug!("搜索设置弹窗关闭");
+            self.popups.called = false;
+
+            self.settings.search.files.ignore_patterns.retain(|pattern| !pattern.trim().is_empty());
+            self.settings.search.web.templates.retain(|template| !template.keyword.trim().is_empty() && !template.url.trim().is_empty());
+            self.search_history.apply_settings(&self.settings.search.history);
+            self.save_settings();
+        }
+    }
+}
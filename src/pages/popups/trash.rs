@@ -0,0 +1,171 @@
+use egui;
+use log::{debug, info};
+
+use crate::my_structs::*;
+use super::link::trash;
+
+
+/// 回收站中对快捷方式的操作
+#[derive(Debug, Clone)]
+enum TrashAction {
+    Restore(String),
+    Delete(String),
+    Empty,
+}
+
+
+impl MyApp {
+    /// 打开回收站，先清理过期的快捷方式
+    pub fn open_trash(&mut self) {
+        if self.purge_expired_trash() {
+            self.save_conf();
+        }
+        self.popups.trash(self.settings.trash.retention_days);
+    }
+
+    /// 按设置清理过期的快捷方式，返回是否有快捷方式被清理
+    pub fn purge_expired_trash(&mut self) -> bool {
+        let expired = trash::purge_expired(&mut self.trash, &self.settings.trash);
+        for trashed in expired.iter() {
+            self.icon_will_clean.push(trashed.program_link.icon_path.clone());
+        }
+        !expired.is_empty()
+    }
+
+    /// 将快捷方式从回收站中还原到末尾
+    fn restore_from_trash(&mut self, uuid: &str) {
+        let Some(index) = self.trash.iter().position(|trashed| trashed.program_link.uuid == uuid) else {
+            return;
+        };
+        let mut program_link = self.trash.remove(index).program_link;
+        info!("从回收站还原: {}", program_link.name);
+
+        // 回收站外已经有同一个快捷方式，例如又从导入包中导入了它
+        if self.program_links.iter().any(|link| link.uuid == program_link.uuid && link.catalog.is_none()) {
+            program_link.uuid = uuid::Uuid::new_v4().to_string();
+        }
+        // 还原共享快捷方式的覆盖时，共享的版本也不再隐藏
+        self.hidden_links.remove(&program_link.uuid);
+        self.tags.extend(program_link.tags.iter().cloned());
+        self.program_links.push(program_link);
+
+        // 重新叠加到共享目录上，覆盖会回到共享快捷方式的位置
+        let links = self.links_snapshot();
+        self.apply_links(links);
+    }
+
+    /// 彻底删除回收站中的快捷方式
+    fn delete_from_trash(&mut self, uuid: &str) {
+        let Some(index) = self.trash.iter().position(|trashed| trashed.program_link.uuid == uuid) else {
+            return;
+        };
+        let trashed = self.trash.remove(index);
+        info!("彻底删除: {}", trashed.program_link.name);
+        self.icon_will_clean.push(trashed.program_link.icon_path);
+    }
+
+    pub fn show_trash(&mut self, ui: &mut egui::Ui) {
+        let mut show = self.popups.called.clone();
+        let mut should_close = false;
+        let mut action = None;
+
+        egui::Window::new("回收站")
+        .collapsible(false)
+        .resizable(false)
+        .default_pos((self.settings.window.size() / 2.0).to_pos2())
+        .fade_in(true)
+        .fade_out(true)
+        .open(&mut show)
+
+        .show(ui.ctx(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label("自动清理超过");
+                ui.add(egui::DragValue::new(&mut self.settings.trash.retention_days).range(0..=365).suffix(" 天"));
+                ui.label("的快捷方式");
+            });
+            ui.label(egui::RichText::new("设为 0 时不会自动清理").weak());
+            ui.separator();
+
+            if self.trash.is_empty() {
+                ui.label(egui::RichText::new("回收站是空的").weak());
+            }
+
+            egui::ScrollArea::vertical()
+            .max_height(256.)
+            .show(ui, |ui| {
+                egui::Grid::new("trash")
+                .num_columns(4)
+                .striped(true)
+                .show(ui, |ui| {
+                    // 最近删除的在前
+                    for trashed in self.trash.iter().rev() {
+                        let program_link = &trashed.program_link;
+                        ui.label(if program_link.name.is_empty() {
+                            egui::RichText::new("未命名").weak()
+                        } else {
+                            egui::RichText::new(&program_link.name)
+                        }).on_hover_text(format!("{} {}", program_link.run_command, program_link.arguments.join(" ")));
+
+                        let deleted_at = ui.label(egui::RichText::new(trashed.deleted_at.format("%Y-%m-%d %H:%M").to_string()).weak());
+                        if let Some(days) = trashed.days_left(&self.settings.trash) {
+                            deleted_at.on_hover_text(format!("{} 天后自动清理", days));
+                        }
+
+                        if ui.button("还原").clicked() {
+                            action = Some(TrashAction::Restore(program_link.uuid.clone()));
+                        }
+                        if ui.button(egui::RichText::new("彻底删除").color(egui::Color32::RED)).clicked() {
+                            action = Some(TrashAction::Delete(program_link.uuid.clone()));
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+
+            ui.separator();
+            ui.with_layout(egui::Layout {
+                cross_align: egui::Align::RIGHT,
+                ..Default::default()
+            }, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("关闭").clicked() {
+                        should_close = true;
+                    }
+                    if ui.add_enabled(!self.trash.is_empty(), egui::Button::new(
+                        egui::RichText::new("清空回收站").color(egui::Color32::RED)
+                    )).clicked() {
+                        action = Some(TrashAction::Empty);
+                    }
+                });
+            });
+        });
+
+        if let Some(action) = action {
+            debug!("回收站操作: {:?}", action);
+            match action {
+                TrashAction::Restore(uuid) => self.restore_from_trash(&uuid),
+                TrashAction::Delete(uuid) => self.delete_from_trash(&uuid),
+                TrashAction::Empty => {
+                    let uuids: Vec<String> = self.trash.iter().map(|trashed| trashed.program_link.uuid.clone()).collect();
+                    for uuid in uuids {
+                        self.delete_from_trash(&uuid);
+                    }
+                }
+            }
+            self.save_conf();
+        }
+
+        if (!show && !should_close && self.popups.called) || should_close {
+            debug!("回收站弹窗关闭");
+            self.popups.called = false;
+
+            // 关闭时才应用新的天数，避免拖动过程中误清理
+            if self.settings.trash.retention_days != self.popups.trash_retention_days {
+                self.save_settings();
+                if self.purge_expired_trash() {
+                    self.save_conf();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use egui;
+use std::time::Instant;
+use log::{debug, info};
+
+use crate::my_structs::*;
+
+
+impl MyApp {
+    /// 撤销最近一次修改并保存
+    pub fn undo_edit(&mut self) {
+        let Some((label, links)) = self.undo.undo() else {
+            debug!("没有可以撤销的修改");
+            return;
+        };
+        info!("撤销: {}", label);
+        self.apply_links(links);
+        self.write_conf();
+    }
+
+    /// 重做最近一次撤销的修改并保存
+    pub fn redo_edit(&mut self) {
+        let Some((label, links)) = self.undo.redo() else {
+            debug!("没有可以重做的修改");
+            return;
+        };
+        info!("重做: {}", label);
+        self.apply_links(links);
+        self.write_conf();
+    }
+
+    /// Ctrl+Z 撤销，Ctrl+Shift+Z 或 Ctrl+Y 重做
+    ///
+    /// 弹窗打开时不处理，输入框中有内容时留给输入框自己撤销
+    pub fn handle_undo_shortcuts(&mut self, ctx: &egui::Context) {
+        if self.popups.called || (ctx.wants_keyboard_input() && !self.search_text.is_empty()) {
+            return;
+        }
+
+        // 不包含 Shift 的快捷键也会匹配按下 Shift 的情况，需要先检查重做
+        let redo = ctx.input_mut(|i| {
+            i.consume_shortcut(&egui::KeyboardShortcut::new(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::Z))
+                || i.consume_shortcut(&egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Y))
+        });
+        let undo = ctx.input_mut(|i| i.consume_shortcut(&egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z)));
+
+        if redo {
+            self.redo_edit();
+        } else if undo {
+            self.undo_edit();
+        }
+    }
+
+    /// 删除快捷方式或标签后在窗口底部显示撤销提示
+    pub fn show_undo_toast(&mut self, ctx: &egui::Context) {
+        let Some(toast) = self.undo.toast.clone() else {
+            return;
+        };
+        let now = Instant::now();
+        if now >= toast.expires {
+            self.undo.toast = None;
+            return;
+        }
+        // 到期时重新绘制以隐藏提示
+        ctx.request_repaint_after(toast.expires - now);
+
+        let mut should_undo = false;
+        let mut should_close = false;
+
+        egui::Area::new(egui::Id::new("undo_toast"))
+        .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -16.0))
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("已{}", toast.label));
+                    if ui.button("撤销").clicked() {
+                        should_undo = true;
+                    }
+                    if ui.small_button("✖").clicked() {
+                        should_close = true;
+                    }
+                });
+            });
+        });
+
+        if should_undo && !self.popups.called {
+            self.undo_edit();
+        } else if should_close {
+            self.undo.toast = None;
+        }
+    }
+}
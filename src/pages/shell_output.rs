@@ -0,0 +1,129 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use egui;
+use log::{debug, error};
+use tokio::sync::oneshot;
+
+use crate::my_structs::*;
+use crate::search::{ShellCommand, ShellOutput};
+
+
+/// 捕获输出的命令，以及显示输出的结果面板
+#[derive(Debug, Default)]
+pub struct ShellPanel {
+    // 正在运行的命令
+    pending: Option<(String, oneshot::Receiver<ShellOutput>)>,
+    // 设置后正在运行的命令会被终止
+    cancel: Arc<AtomicBool>,
+    output: Option<ShellOutput>,
+}
+
+impl ShellPanel {
+    /// 在后台运行命令，结束后显示输出
+    pub fn run(&mut self, ctx: &egui::Context, shell: ShellCommand) {
+        // 同一时间只显示一条命令的输出
+        self.close();
+
+        let (sender, receiver) = oneshot::channel();
+        self.pending = Some((shell.command.clone(), receiver));
+        let cancel = self.cancel.clone();
+
+        let ctx = ctx.clone();
+        tokio::task::spawn_blocking(move || {
+            if sender.send(shell.run_captured(&cancel)).is_ok() {
+                ctx.request_repaint();
+            }
+        });
+    }
+
+    /// 关闭面板，终止正在运行的命令并丢弃输出
+    fn close(&mut self) {
+        if let Some((command, _)) = self.pending.take() {
+            debug!("取消命令 {}", command);
+            self.cancel.store(true, Ordering::SeqCst);
+        }
+        self.cancel = Arc::new(AtomicBool::new(false));
+        self.output = None;
+    }
+
+    fn poll(&mut self) {
+        let Some((command, receiver)) = self.pending.as_mut() else {
+            return;
+        };
+
+        match receiver.try_recv() {
+            Ok(output) => {
+                self.output = Some(output);
+                self.pending = None;
+            }
+            Err(oneshot::error::TryRecvError::Empty) => {}
+            Err(oneshot::error::TryRecvError::Closed) => {
+                error!("命令 {} 意外终止", command);
+                self.pending = None;
+            }
+        }
+    }
+}
+
+
+impl MyApp {
+    pub fn show_shell_output(&mut self, ctx: &egui::Context) {
+        let panel = &mut self.shell_panel;
+        panel.poll();
+
+        let command = match (&panel.pending, &panel.output) {
+            (Some((command, _)), _) => command.clone(),
+            (None, Some(output)) => output.command.clone(),
+            (None, None) => return,
+        };
+        let mut open = true;
+
+        egui::Window::new("命令输出")
+        .collapsible(false)
+        .resizable(true)
+        .default_pos((self.settings.window.size() / 2.0).to_pos2())
+        .open(&mut open)
+
+        .show(ctx, |ui| {
+            ui.label(egui::RichText::new(format!("{} {}", crate::search::provider::SHELL_PREFIX, command)).monospace().strong());
+            ui.separator();
+
+            let Some(output) = &panel.output else {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("运行中…");
+                });
+                return;
+            };
+
+            match output.status {
+                Some(0) => ui.label("运行成功"),
+                Some(code) => ui.label(egui::RichText::new(format!("退出码 {}", code)).color(egui::Color32::LIGHT_RED)),
+                None => ui.label(egui::RichText::new("命令没有正常结束").color(egui::Color32::LIGHT_RED)),
+            };
+
+            egui::ScrollArea::vertical()
+            .max_height(240.)
+            .show(ui, |ui| {
+                if !output.stdout.is_empty() {
+                    ui.label(egui::RichText::new(&output.stdout).monospace());
+                }
+                if !output.stderr.is_empty() {
+                    ui.label(egui::RichText::new(&output.stderr).monospace().color(egui::Color32::LIGHT_RED));
+                }
+                if output.stdout.is_empty() && output.stderr.is_empty() {
+                    ui.label(egui::RichText::new("没有输出").weak());
+                }
+            });
+
+            ui.separator();
+            if ui.button("复制输出").clicked() {
+                ui.ctx().copy_text(format!("{}{}", output.stdout, output.stderr));
+            }
+        });
+
+        if !open {
+            panel.close();
+        }
+    }
+}
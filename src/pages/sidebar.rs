@@ -20,12 +20,21 @@ impl MyApp {
     
                     let is_selected = self.current_tag.as_ref().unwrap_or(&"".to_string()) == &tag;
     
-                    if ui.selectable_label(
+                    let response = ui.selectable_label(
                         is_selected,
                         tag.clone()
-                    )
-                    
-                    .clicked() && !self.popups.called {
+                    );
+
+                    response.context_menu(|ui| {
+                        if ui.button("导出此标签的快捷方式").clicked() {
+                            if !self.popups.called {
+                                self.open_export_bundle(Some(tag.as_str()));
+                            }
+                            ui.close_menu();
+                        }
+                    });
+
+                    if response.clicked() && !self.popups.called {
                         if self.edit_mode {
                             self.popups.delete_tag(tag.clone());
                         } else {
@@ -0,0 +1,375 @@
+//! 配置与缓存文件夹的位置
+//!
+//! 按以下顺序决定配置文件夹：
+//!
+//! 1. 命令行参数 `--config-dir <路径>`
+//! 2. 程序同目录下存在 `portable` 文件时为便携模式，使用程序同目录下的 `.baro`
+//! 3. 系统的配置目录：Linux 为 `$XDG_CONFIG_HOME/BaroBoard`（默认 `~/.config/BaroBoard`），
+//!    Windows 为 `%APPDATA%\BaroBoard`，macOS 为 `~/Library/Application Support/BaroBoard`
+//!
+//! 缓存（文件索引、程序图标）在前两种情况下放在配置文件夹的 `cache` 中，否则放在系统的缓存目录。
+//! 旧版本的配置保存在工作目录下的 `.baro` 中，第一次使用系统目录时会自动复制过来
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use log::{info, warn};
+
+
+/// 系统目录中的文件夹名称
+const APP_DIR_NAME: &str = "BaroBoard";
+/// 便携模式与旧版本使用的配置文件夹名称
+const PORTABLE_DIR_NAME: &str = ".baro";
+/// 程序同目录下存在此文件时为便携模式
+const PORTABLE_MARKER: &str = "portable";
+/// 指定配置文件夹的命令行参数
+const CONFIG_DIR_ARG: &str = "--config-dir";
+/// 旧版本配置文件夹中需要复制的文件
+const LEGACY_FILES: [&str; 3] = [
+    crate::CONFIG_FILE_NAME,
+    crate::SETTINGS_FILE_NAME,
+    crate::HISTORY_FILE_NAME,
+];
+
+
+static PATHS: OnceLock<Paths> = OnceLock::new();
+
+
+/// 配置文件夹的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PathsMode {
+    Override,
+    Portable,
+    System,
+}
+
+#[derive(Debug)]
+struct Paths {
+    config: PathBuf,
+    cache: PathBuf,
+    mode: PathsMode,
+}
+
+/// 决定文件夹时依赖的外部环境
+#[derive(Debug, Clone, Default)]
+struct Environment {
+    // 程序所在的文件夹
+    exe_dir: Option<PathBuf>,
+    // 工作目录
+    current_dir: Option<PathBuf>,
+    // 系统的配置与缓存目录，不包含程序的文件夹名称
+    system_config: Option<PathBuf>,
+    system_cache: Option<PathBuf>,
+}
+
+impl Environment {
+    fn current() -> Self {
+        Self {
+            exe_dir: exe_dir(),
+            current_dir: std::env::current_dir().ok(),
+            system_config: system_config_dir(),
+            system_cache: system_cache_dir(),
+        }
+    }
+}
+
+
+/// 根据命令行参数决定配置与缓存文件夹，需要在读取任何配置之前调用
+pub fn init(args: impl IntoIterator<Item = String>) {
+    let environment = Environment::current();
+    let paths = resolve(config_dir_arg(args), &environment);
+    info!("配置文件夹: {} ({:?})", paths.config.display(), paths.mode);
+    info!("缓存文件夹: {}", paths.cache.display());
+
+    if paths.mode == PathsMode::System {
+        copy_legacy_config(&paths.config, &environment);
+    }
+
+    if PATHS.set(paths).is_err() {
+        warn!("配置文件夹已经初始化");
+    }
+}
+
+fn paths() -> &'static Paths {
+    PATHS.get_or_init(|| resolve(None, &Environment::current()))
+}
+
+/// 配置文件夹
+pub fn config_dir() -> &'static Path {
+    &paths().config
+}
+
+/// 缓存文件夹，其中的内容可以随时删除
+pub fn cache_dir() -> &'static Path {
+    &paths().cache
+}
+
+
+/// 从命令行参数中读取配置文件夹，支持 `--config-dir <路径>` 与 `--config-dir=<路径>`
+fn config_dir_arg(args: impl IntoIterator<Item = String>) -> Option<PathBuf> {
+    let mut args = args.into_iter().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == CONFIG_DIR_ARG {
+            match args.next() {
+                Some(path) => return Some(PathBuf::from(path)),
+                None => warn!("{} 缺少路径", CONFIG_DIR_ARG),
+            }
+        } else if let Some(path) = arg.strip_prefix(CONFIG_DIR_ARG).and_then(|rest| rest.strip_prefix('=')) {
+            return Some(PathBuf::from(path));
+        }
+    }
+    None
+}
+
+fn resolve(override_dir: Option<PathBuf>, environment: &Environment) -> Paths {
+    if let Some(config) = override_dir {
+        return Paths {
+            cache: config.join("cache"),
+            config: config,
+            mode: PathsMode::Override,
+        };
+    }
+
+    if let Some(exe_dir) = &environment.exe_dir {
+        if exe_dir.join(PORTABLE_MARKER).exists() {
+            let config = exe_dir.join(PORTABLE_DIR_NAME);
+            return Paths {
+                cache: config.join("cache"),
+                config: config,
+                mode: PathsMode::Portable,
+            };
+        }
+    }
+
+    match (&environment.system_config, &environment.system_cache) {
+        (Some(config), Some(cache)) => Paths {
+            config: config.join(APP_DIR_NAME),
+            cache: cache.join(APP_DIR_NAME),
+            mode: PathsMode::System,
+        },
+        _ => {
+            // 找不到系统目录时退回到旧版本的行为
+            warn!("无法确定系统的配置目录，使用工作目录下的 {}", PORTABLE_DIR_NAME);
+            let config = PathBuf::from(PORTABLE_DIR_NAME);
+            Paths {
+                cache: config.join("cache"),
+                config: config,
+                mode: PathsMode::Portable,
+            }
+        }
+    }
+}
+
+/// 程序所在的文件夹
+fn exe_dir() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+}
+
+/// 非空的环境变量
+fn env_dir(key: &str) -> Option<PathBuf> {
+    std::env::var_os(key)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+#[cfg(target_os = "windows")]
+fn system_config_dir() -> Option<PathBuf> {
+    env_dir("APPDATA")
+}
+
+#[cfg(target_os = "windows")]
+fn system_cache_dir() -> Option<PathBuf> {
+    env_dir("LOCALAPPDATA").or_else(|| env_dir("APPDATA"))
+}
+
+#[cfg(target_os = "macos")]
+fn system_config_dir() -> Option<PathBuf> {
+    env_dir("HOME").map(|home| home.join("Library").join("Application Support"))
+}
+
+#[cfg(target_os = "macos")]
+fn system_cache_dir() -> Option<PathBuf> {
+    env_dir("HOME").map(|home| home.join("Library").join("Caches"))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn system_config_dir() -> Option<PathBuf> {
+    env_dir("XDG_CONFIG_HOME").or_else(|| env_dir("HOME").map(|home| home.join(".config")))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn system_cache_dir() -> Option<PathBuf> {
+    env_dir("XDG_CACHE_HOME").or_else(|| env_dir("HOME").map(|home| home.join(".cache")))
+}
+
+
+/// 旧版本把配置保存在工作目录下的 `.baro` 中
+///
+/// 系统目录中还没有快捷方式配置时，把旧的配置文件复制过来，原文件保持不变
+fn copy_legacy_config(config: &Path, environment: &Environment) {
+    if config.join(crate::CONFIG_FILE_NAME).exists() {
+        return;
+    }
+
+    let legacy = [&environment.current_dir, &environment.exe_dir]
+        .into_iter()
+        .flatten()
+        .map(|dir| dir.join(PORTABLE_DIR_NAME))
+        .find(|dir| dir.join(crate::CONFIG_FILE_NAME).exists());
+    let Some(legacy) = legacy else {
+        return;
+    };
+
+    info!("从旧的配置文件夹 {} 复制配置", legacy.display());
+    if let Err(e) = std::fs::create_dir_all(config) {
+        warn!("创建配置文件夹 {} 失败: {}", config.display(), e);
+        return;
+    }
+    for file_name in LEGACY_FILES {
+        let source = legacy.join(file_name);
+        if !source.exists() {
+            continue;
+        }
+        if let Err(e) = std::fs::copy(&source, config.join(file_name)) {
+            warn!("复制 {} 失败: {}", source.display(), e);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("baroboard-paths-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        std::iter::once("baroboard").chain(args.iter().copied()).map(String::from).collect()
+    }
+
+    /// 程序、工作目录与系统目录都在临时文件夹中
+    fn environment(root: &Path) -> Environment {
+        for dir in ["exe", "work", "config", "cache"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        Environment {
+            exe_dir: Some(root.join("exe")),
+            current_dir: Some(root.join("work")),
+            system_config: Some(root.join("config")),
+            system_cache: Some(root.join("cache")),
+        }
+    }
+
+    #[test]
+    fn reads_config_dir_arg() {
+        let cases: &[(&[&str], Option<&str>)] = &[
+            (&[], None),
+            (&["--config-dir", "/tmp/a"], Some("/tmp/a")),
+            (&["--config-dir=/tmp/b"], Some("/tmp/b")),
+            (&["--verbose", "--config-dir", "/tmp/c", "--config-dir", "/tmp/d"], Some("/tmp/c")),
+            // 缺少路径
+            (&["--config-dir"], None),
+            (&["--config-directory=/tmp/e"], None),
+        ];
+        for (arguments, expected) in cases {
+            assert_eq!(config_dir_arg(args(arguments)), expected.map(PathBuf::from), "{:?}", arguments);
+        }
+
+        // 第一个参数是程序本身
+        assert_eq!(config_dir_arg(vec!["--config-dir=/tmp/f".to_string()]), None);
+    }
+
+    #[test]
+    fn resolves_in_order() {
+        let root = temp_dir();
+        let environment = environment(&root);
+
+        // 系统目录
+        let paths = resolve(None, &environment);
+        assert_eq!(paths.mode, PathsMode::System);
+        assert_eq!(paths.config, root.join("config").join(APP_DIR_NAME));
+        assert_eq!(paths.cache, root.join("cache").join(APP_DIR_NAME));
+
+        // 程序同目录下有标记文件时为便携模式
+        std::fs::write(root.join("exe").join(PORTABLE_MARKER), "").unwrap();
+        let paths = resolve(None, &environment);
+        assert_eq!(paths.mode, PathsMode::Portable);
+        assert_eq!(paths.config, root.join("exe").join(PORTABLE_DIR_NAME));
+        assert_eq!(paths.cache, root.join("exe").join(PORTABLE_DIR_NAME).join("cache"));
+
+        // 命令行参数优先于便携模式
+        let paths = resolve(Some(root.join("custom")), &environment);
+        assert_eq!(paths.mode, PathsMode::Override);
+        assert_eq!(paths.config, root.join("custom"));
+        assert_eq!(paths.cache, root.join("custom").join("cache"));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn falls_back_without_system_dirs() {
+        let environment = Environment {
+            system_cache: Some(PathBuf::from("/tmp")),
+            ..Default::default()
+        };
+        let paths = resolve(None, &environment);
+        assert_eq!(paths.mode, PathsMode::Portable);
+        assert_eq!(paths.config, PathBuf::from(PORTABLE_DIR_NAME));
+    }
+
+    #[test]
+    fn copies_legacy_config() {
+        let root = temp_dir();
+        let environment = environment(&root);
+        let legacy = root.join("work").join(PORTABLE_DIR_NAME);
+        std::fs::create_dir_all(&legacy).unwrap();
+        for file_name in LEGACY_FILES {
+            std::fs::write(legacy.join(file_name), file_name).unwrap();
+        }
+        // 程序同目录下的旧配置优先级更低
+        let exe_legacy = root.join("exe").join(PORTABLE_DIR_NAME);
+        std::fs::create_dir_all(&exe_legacy).unwrap();
+        std::fs::write(exe_legacy.join(crate::CONFIG_FILE_NAME), "exe").unwrap();
+
+        let config = root.join("config").join(APP_DIR_NAME);
+        copy_legacy_config(&config, &environment);
+        for file_name in LEGACY_FILES {
+            assert_eq!(std::fs::read_to_string(config.join(file_name)).unwrap(), file_name);
+            // 原文件保持不变
+            assert!(legacy.join(file_name).exists());
+        }
+
+        // 已经有配置时不再复制
+        std::fs::write(legacy.join(crate::CONFIG_FILE_NAME), "newer").unwrap();
+        copy_legacy_config(&config, &environment);
+        assert_eq!(std::fs::read_to_string(config.join(crate::CONFIG_FILE_NAME)).unwrap(), crate::CONFIG_FILE_NAME);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn copies_only_existing_files() {
+        let root = temp_dir();
+        let environment = environment(&root);
+        let config = root.join("config").join(APP_DIR_NAME);
+
+        // 没有旧配置时不会创建配置文件夹
+        copy_legacy_config(&config, &environment);
+        assert!(!config.exists());
+
+        // 只有程序同目录下的旧配置
+        let legacy = root.join("exe").join(PORTABLE_DIR_NAME);
+        std::fs::create_dir_all(&legacy).unwrap();
+        std::fs::write(legacy.join(crate::CONFIG_FILE_NAME), "exe").unwrap();
+        copy_legacy_config(&config, &environment);
+        assert_eq!(std::fs::read_to_string(config.join(crate::CONFIG_FILE_NAME)).unwrap(), "exe");
+        assert!(!config.join(crate::SETTINGS_FILE_NAME).exists());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,662 @@
+//! 搜索栏中的计算器与单位换算
+//!
+//! 支持四则运算、取余、乘方、括号、十六进制/八进制/二进制字面量、常用函数，
+//! 以及形如 `5 km in mi` 的单位换算。全部在本地解析，不会启动任何进程
+
+
+/// 括号、负号与乘方的最大嵌套层数，每次按键都会求值，过深的嵌套会耗尽栈空间
+const MAX_DEPTH: usize = 64;
+
+
+/// 一次计算的结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct Calculation {
+    pub value: f64,
+    // 单位换算的目标单位
+    pub unit: Option<&'static str>,
+    // 用于展示的完整算式，例如 `5 km = 3.10686 mi`
+    pub description: String,
+    // 输入中使用了十六进制字面量，结果也附带十六进制
+    show_hex: bool,
+}
+
+impl Calculation {
+    /// 复制到剪贴板的内容
+    pub fn value_text(&self) -> String {
+        format_number(self.value)
+    }
+
+    /// 展示用的结果
+    pub fn result_text(&self) -> String {
+        let mut text = self.value_text();
+
+        if let Some(unit) = self.unit {
+            text.push(' ');
+            text.push_str(unit);
+        }
+
+        // 负数的十六进制会显示为补码，不附带
+        if self.show_hex && self.value.fract() == 0. && self.value >= 0. && self.value < i64::MAX as f64 {
+            text.push_str(&format!(" (0x{:x})", self.value as i64));
+        }
+
+        text
+    }
+}
+
+
+/// 尝试将搜索文本当作算式或单位换算求值
+///
+/// 单独的数字或常量不算作算式，因为它更可能是在搜索名称
+pub fn evaluate(input: &str) -> Option<Calculation> {
+    let input = input.trim();
+    if input.is_empty() {
+        return None;
+    }
+
+    if let Some(calculation) = convert(input) {
+        return Some(calculation);
+    }
+
+    let mut parser = Parser::new(input)?;
+    let value = parser.parse()?;
+
+    if parser.trivial || !value.is_finite() {
+        return None;
+    }
+
+    Some(Calculation {
+        value: value,
+        unit: None,
+        description: format!("{} =", input),
+        show_hex: parser.has_hex,
+    })
+}
+
+
+/// 将浮点数格式化为易读的形式，整数不带小数点，过大或过小的数使用科学计数法
+pub fn format_number(value: f64) -> String {
+    if value == 0. {
+        return "0".to_string();
+    }
+
+    if value.fract() == 0. && value.abs() < 1e15 {
+        return format!("{}", value as i64);
+    }
+
+    if value.abs() >= 1e15 || value.abs() < 1e-6 {
+        return format!("{:e}", value);
+    }
+
+    // 保留10位有效数字，并去掉末尾的0
+    let digits = (9 - value.abs().log10().floor() as i32).max(0) as usize;
+    let text = format!("{:.*}", digits, value);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(char),
+    LeftParen,
+    RightParen,
+}
+
+/// 简单的递归下降解析器
+///
+/// ```text
+/// expr    := term (('+' | '-') term)*
+/// term    := unary (('*' | '/' | '%') unary)*
+/// unary   := '-' unary | '+' unary | power
+/// power   := primary ('^' unary)?
+/// primary := number | constant | function '(' expr ')' | '(' expr ')'
+/// ```
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    // 输入中没有任何运算，例如单独的数字或常量
+    trivial: bool,
+    has_hex: bool,
+    // 当前的嵌套层数
+    depth: usize,
+}
+
+impl Parser {
+    fn new(input: &str) -> Option<Self> {
+        let (tokens, has_hex) = tokenize(input)?;
+        let trivial = !has_hex && !tokens
+            .iter()
+            .any(|token| matches!(token, Token::Op(_) | Token::LeftParen));
+
+        Some(Self {
+            tokens: tokens,
+            position: 0,
+            trivial: trivial,
+            has_hex: has_hex,
+            depth: 0,
+        })
+    }
+
+    fn parse(&mut self) -> Option<f64> {
+        let value = self.expr()?;
+        // 必须消耗掉所有的记号
+        if self.position != self.tokens.len() {
+            return None;
+        }
+        Some(value)
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expr(&mut self) -> Option<f64> {
+        let mut value = self.term()?;
+        while let Some(Token::Op(op @ ('+' | '-'))) = self.peek().cloned() {
+            self.next();
+            let rhs = self.term()?;
+            value = if op == '+' { value + rhs } else { value - rhs };
+        }
+        Some(value)
+    }
+
+    fn term(&mut self) -> Option<f64> {
+        let mut value = self.unary()?;
+        while let Some(Token::Op(op @ ('*' | '/' | '%'))) = self.peek().cloned() {
+            self.next();
+            let rhs = self.unary()?;
+            value = match op {
+                '*' => value * rhs,
+                '/' => value / rhs,
+                _ => value % rhs,
+            };
+        }
+        Some(value)
+    }
+
+    /// 所有的递归都会经过这里，在此限制嵌套层数
+    fn unary(&mut self) -> Option<f64> {
+        if self.depth >= MAX_DEPTH {
+            return None;
+        }
+        self.depth += 1;
+
+        let value = match self.peek() {
+            Some(Token::Op('-')) => {
+                self.next();
+                self.unary().map(|value| -value)
+            }
+            Some(Token::Op('+')) => {
+                self.next();
+                self.unary()
+            }
+            _ => self.power(),
+        };
+
+        self.depth -= 1;
+        value
+    }
+
+    fn power(&mut self) -> Option<f64> {
+        let base = self.primary()?;
+        if let Some(Token::Op('^')) = self.peek() {
+            self.next();
+            // 乘方是右结合的
+            let exponent = self.unary()?;
+            return Some(base.powf(exponent));
+        }
+        Some(base)
+    }
+
+    fn primary(&mut self) -> Option<f64> {
+        match self.next()? {
+            Token::Number(value) => Some(value),
+            Token::LeftParen => {
+                let value = self.expr()?;
+                match self.next()? {
+                    Token::RightParen => Some(value),
+                    _ => None,
+                }
+            }
+            Token::Ident(name) => {
+                if let Some(value) = constant(&name) {
+                    return Some(value);
+                }
+
+                let function = function(&name)?;
+                if self.next()? != Token::LeftParen {
+                    return None;
+                }
+                let argument = self.expr()?;
+                if self.next()? != Token::RightParen {
+                    return None;
+                }
+                Some(function(argument))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn constant(name: &str) -> Option<f64> {
+    match name {
+        "pi" | "π" => Some(std::f64::consts::PI),
+        "e" => Some(std::f64::consts::E),
+        "tau" => Some(std::f64::consts::TAU),
+        _ => None,
+    }
+}
+
+fn function(name: &str) -> Option<fn(f64) -> f64> {
+    let function: fn(f64) -> f64 = match name {
+        "sqrt" => f64::sqrt,
+        "cbrt" => f64::cbrt,
+        "abs" => f64::abs,
+        "round" => f64::round,
+        "floor" => f64::floor,
+        "ceil" => f64::ceil,
+        "ln" => f64::ln,
+        "log" | "lg" => f64::log10,
+        "log2" => f64::log2,
+        "exp" => f64::exp,
+        "sin" => f64::sin,
+        "cos" => f64::cos,
+        "tan" => f64::tan,
+        "asin" => f64::asin,
+        "acos" => f64::acos,
+        "atan" => f64::atan,
+        _ => return None,
+    };
+    Some(function)
+}
+
+/// 将输入拆分为记号，返回记号以及是否包含十六进制字面量
+fn tokenize(input: &str) -> Option<(Vec<Token>, bool)> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut has_hex = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() || c == '_' {
+            i += 1;
+            continue;
+        }
+
+        // 0x1f 0o17 0b101
+        if c == '0' && i + 1 < chars.len() && matches!(chars[i + 1], 'x' | 'X' | 'o' | 'O' | 'b' | 'B') {
+            let radix = match chars[i + 1].to_ascii_lowercase() {
+                'x' => 16,
+                'o' => 8,
+                _ => 2,
+            };
+            let start = i + 2;
+            let mut end = start;
+            while end < chars.len() && (chars[end].is_digit(radix) || chars[end] == '_') {
+                end += 1;
+            }
+            let digits: String = chars[start..end].iter().filter(|c| **c != '_').collect();
+            let value = i64::from_str_radix(&digits, radix).ok()?;
+
+            has_hex |= radix == 16;
+            tokens.push(Token::Number(value as f64));
+            i = end;
+            continue;
+        }
+
+        if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.' || chars[i] == '_' || chars[i] == ',') {
+                i += 1;
+            }
+            // 逗号只能作为千位分隔符，避免 1,5 被当作 15
+            let mantissa: String = chars[start..i].iter().collect();
+            if !valid_thousands(&mantissa) {
+                return None;
+            }
+            // 科学计数法，例如 1e3、2.5E-4
+            if i + 1 < chars.len() && matches!(chars[i], 'e' | 'E')
+                && (chars[i + 1].is_ascii_digit()
+                    || (matches!(chars[i + 1], '+' | '-') && chars.get(i + 2).is_some_and(|c| c.is_ascii_digit())))
+            {
+                i += 2;
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+            }
+            // 千位分隔符与下划线都会被忽略
+            let literal: String = chars[start..i].iter().filter(|c| !matches!(c, '_' | ',')).collect();
+            tokens.push(Token::Number(literal.parse().ok()?));
+            continue;
+        }
+
+        if c.is_alphabetic() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric()) {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect::<String>().to_lowercase()));
+            continue;
+        }
+
+        let token = match c {
+            '+' | '-' | '%' | '^' => Token::Op(c),
+            '*' | '×' => {
+                // ** 也表示乘方
+                if chars.get(i + 1) == Some(&'*') {
+                    i += 1;
+                    Token::Op('^')
+                } else {
+                    Token::Op('*')
+                }
+            }
+            '/' | '÷' => Token::Op('/'),
+            '(' | '（' => Token::LeftParen,
+            ')' | '）' => Token::RightParen,
+            _ => return None,
+        };
+        tokens.push(token);
+        i += 1;
+    }
+
+    Some((tokens, has_hex))
+}
+
+/// 检查数字中的千位分隔符，只能出现在整数部分，并且除第一组外每组都是三位数字，例如 1,234,567
+fn valid_thousands(mantissa: &str) -> bool {
+    if !mantissa.contains(',') {
+        return true;
+    }
+
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if fraction.contains(',') {
+        return false;
+    }
+
+    integer.split(',').enumerate().all(|(index, group)| {
+        group.chars().all(|c| c.is_ascii_digit())
+            && if index == 0 { (1..=3).contains(&group.len()) } else { group.len() == 3 }
+    })
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dimension {
+    Length,
+    Mass,
+    Time,
+    Data,
+    Volume,
+    Temperature,
+}
+
+/// (可用的名称, 显示名称, 维度, 换算到基本单位的倍数)
+///
+/// 温度的倍数没有意义，单独处理
+const UNITS: &[(&[&str], &str, Dimension, f64)] = &[
+    (&["mm", "毫米"], "mm", Dimension::Length, 0.001),
+    (&["cm", "厘米"], "cm", Dimension::Length, 0.01),
+    (&["m", "米"], "m", Dimension::Length, 1.),
+    (&["km", "公里", "千米"], "km", Dimension::Length, 1000.),
+    (&["in", "inch", "inches", "英寸"], "in", Dimension::Length, 0.0254),
+    (&["ft", "foot", "feet", "英尺"], "ft", Dimension::Length, 0.3048),
+    (&["yd", "yard", "yards", "码"], "yd", Dimension::Length, 0.9144),
+    (&["mi", "mile", "miles", "英里"], "mi", Dimension::Length, 1609.344),
+    (&["nmi", "海里"], "nmi", Dimension::Length, 1852.),
+
+    (&["mg", "毫克"], "mg", Dimension::Mass, 0.001),
+    (&["g", "克"], "g", Dimension::Mass, 1.),
+    (&["kg", "公斤", "千克"], "kg", Dimension::Mass, 1000.),
+    (&["斤"], "斤", Dimension::Mass, 500.),
+    (&["t", "ton", "吨"], "t", Dimension::Mass, 1_000_000.),
+    (&["oz", "ounce", "盎司"], "oz", Dimension::Mass, 28.349523125),
+    (&["lb", "lbs", "pound", "磅"], "lb", Dimension::Mass, 453.59237),
+
+    (&["ms", "毫秒"], "ms", Dimension::Time, 0.001),
+    (&["s", "sec", "秒"], "s", Dimension::Time, 1.),
+    (&["min", "分钟"], "min", Dimension::Time, 60.),
+    (&["h", "hr", "hour", "hours", "小时"], "h", Dimension::Time, 3600.),
+    (&["d", "day", "days", "天"], "d", Dimension::Time, 86400.),
+    (&["week", "weeks", "wk", "周"], "week", Dimension::Time, 604800.),
+
+    (&["bit", "bits"], "bit", Dimension::Data, 0.125),
+    (&["b", "byte", "bytes", "字节"], "B", Dimension::Data, 1.),
+    (&["kb"], "KB", Dimension::Data, 1e3),
+    (&["mb"], "MB", Dimension::Data, 1e6),
+    (&["gb"], "GB", Dimension::Data, 1e9),
+    (&["tb"], "TB", Dimension::Data, 1e12),
+    (&["kib"], "KiB", Dimension::Data, 1024.),
+    (&["mib"], "MiB", Dimension::Data, 1048576.),
+    (&["gib"], "GiB", Dimension::Data, 1073741824.),
+    (&["tib"], "TiB", Dimension::Data, 1099511627776.),
+
+    (&["ml", "毫升"], "ml", Dimension::Volume, 0.001),
+    (&["l", "liter", "litre", "升"], "L", Dimension::Volume, 1.),
+    (&["gal", "gallon", "加仑"], "gal", Dimension::Volume, 3.785411784),
+
+    (&["c", "°c", "℃", "摄氏度"], "°C", Dimension::Temperature, 1.),
+    (&["f", "°f", "℉", "华氏度"], "°F", Dimension::Temperature, 1.),
+    (&["k", "kelvin", "开尔文"], "K", Dimension::Temperature, 1.),
+];
+
+type Unit = (&'static str, Dimension, f64);
+
+fn find_unit(name: &str) -> Option<Unit> {
+    let name = name.trim().to_lowercase();
+    UNITS
+        .iter()
+        .find(|(names, _, _, _)| names.contains(&name.as_str()))
+        .map(|(_, display, dimension, factor)| (*display, *dimension, *factor))
+}
+
+/// 在文本末尾寻找最长的单位名称，返回剩下的部分与单位
+fn split_unit(text: &str) -> Option<(&str, Unit)> {
+    let text = text.trim_end();
+
+    text.char_indices()
+        .map(|(index, _)| index)
+        .filter(|index| *index > 0)
+        .find_map(|index| {
+            let (rest, unit) = text.split_at(index);
+            find_unit(unit).map(|unit| (rest, unit))
+        })
+}
+
+fn to_celsius(value: f64, unit: &str) -> f64 {
+    match unit {
+        "°F" => (value - 32.) * 5. / 9.,
+        "K" => value - 273.15,
+        _ => value,
+    }
+}
+
+fn from_celsius(value: f64, unit: &str) -> f64 {
+    match unit {
+        "°F" => value * 9. / 5. + 32.,
+        "K" => value + 273.15,
+        _ => value,
+    }
+}
+
+/// 单位换算，格式为 `<算式> <单位> in|to <单位>`
+fn convert(input: &str) -> Option<Calculation> {
+    let lower = input.to_lowercase();
+
+    // 取最后一个关键字，这样 `5 in in cm` 中的第一个 in 会被当作英寸
+    let (left, target) = [" in ", " to ", " => ", " -> ", " 转 "]
+        .iter()
+        .filter_map(|keyword| lower.rfind(keyword).map(|index| (index, keyword.len())))
+        .max_by_key(|(index, _)| *index)
+        .map(|(index, length)| (&lower[..index], &lower[index + length..]))?;
+
+    let (_, target_dimension, target_factor) = find_unit(target)?;
+    let target_display = find_unit(target)?.0;
+    let (expression, (source_display, source_dimension, source_factor)) = split_unit(left)?;
+
+    if source_dimension != target_dimension {
+        return None;
+    }
+
+    // 单位前面可以是任意算式
+    let amount = if expression.trim().is_empty() {
+        1.
+    } else {
+        Parser::new(expression)?.parse()?
+    };
+
+    let value = if source_dimension == Dimension::Temperature {
+        from_celsius(to_celsius(amount, source_display), target_display)
+    } else {
+        amount * source_factor / target_factor
+    };
+
+    if !value.is_finite() {
+        return None;
+    }
+
+    Some(Calculation {
+        value: value,
+        unit: Some(target_display),
+        description: format!("{} {} =", format_number(amount), source_display),
+        show_hex: false,
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(input: &str) -> f64 {
+        evaluate(input).unwrap_or_else(|| panic!("{} 应当可以求值", input)).value
+    }
+
+    fn assert_close(input: &str, expected: f64) {
+        let actual = value(input);
+        assert!((actual - expected).abs() < 1e-9, "{} = {}，应为 {}", input, actual, expected);
+    }
+
+    #[test]
+    fn respects_precedence() {
+        let cases = [
+            ("1 + 2 * 3", 7.),
+            ("(1 + 2) * 3", 9.),
+            ("10 - 4 - 3", 3.),
+            ("2 * 3 % 4", 2.),
+            // 乘方是右结合的，并且优先于负号
+            ("2 ^ 3 ^ 2", 512.),
+            ("2 ** 3", 8.),
+            ("-2 ^ 2", -4.),
+            ("2 × 3 ÷ 4", 1.5),
+            ("sqrt(16) + 1", 5.),
+            ("（1 + 1）* pi", 2. * std::f64::consts::PI),
+            ("1e3 + 2.5E-1", 1000.25),
+        ];
+        for (input, expected) in cases {
+            assert_close(input, expected);
+        }
+    }
+
+    #[test]
+    fn parses_radix_literals() {
+        assert_close("0x10", 16.);
+        assert_close("0o17 + 0b101", 20.);
+        assert_close("0xff_ff", 65535.);
+
+        // 使用十六进制时结果也附带十六进制
+        let calculation = evaluate("0xff + 1").unwrap();
+        assert_eq!(calculation.result_text(), "256 (0x100)");
+        assert_eq!(evaluate("8 + 8").unwrap().result_text(), "16");
+        // 负数不附带十六进制
+        assert_eq!(evaluate("0x10 - 0x20").unwrap().result_text(), "-16");
+        assert_eq!(evaluate("0x1 / 2").unwrap().result_text(), "0.5");
+    }
+
+    #[test]
+    fn accepts_only_grouped_thousands() {
+        assert_close("1,234 + 1", 1235.);
+        assert_close("1,234,567.5 * 2", 2469135.);
+        assert_close("1_000 * 2", 2000.);
+
+        for input in ["1,5 * 2", "1,2345 + 1", "1234,567 + 1", "1.5,000 + 1", "1,,000 + 1", ",5 + 1"] {
+            assert_eq!(evaluate(input), None, "{}", input);
+        }
+    }
+
+    #[test]
+    fn converts_units() {
+        assert_close("5 km in mi", 5000. / 1609.344);
+        assert_close("1 GiB to MiB", 1024.);
+        assert_close("2 * 3 ft in in", 72.);
+        // 第一个 in 是英寸
+        assert_close("5 in in cm", 12.7);
+        assert_close("100 c to f", 212.);
+        assert_close("0 k in c", -273.15);
+
+        let calculation = evaluate("5 km in mi").unwrap();
+        assert_eq!(calculation.unit, Some("mi"));
+        assert_eq!(calculation.description, "5 km =");
+
+        // 维度不同的单位不能换算
+        assert_eq!(evaluate("1 kg in m"), None);
+        assert_eq!(evaluate("1 km in parsecs"), None);
+    }
+
+    #[test]
+    fn rejects_division_by_zero() {
+        for input in ["1 / 0", "0 / 0", "-1 / 0", "5 % 0", "1 / 0 km in mi"] {
+            assert_eq!(evaluate(input), None, "{}", input);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        let cases = [
+            "",
+            "1 +",
+            "* 2",
+            "(1 + 2",
+            "1 + 2)",
+            "1 2",
+            "sqrt 4",
+            "foo(1)",
+            "1 $ 2",
+            "0xg + 1",
+            "1..2 + 1",
+        ];
+        for input in cases {
+            assert_eq!(evaluate(input), None, "{}", input);
+        }
+    }
+
+    #[test]
+    fn limits_nesting_depth() {
+        let nested = |depth: usize| format!("{}1 + 1{}", "(".repeat(depth), ")".repeat(depth));
+        assert_close(&nested(20), 2.);
+        assert_close(&format!("{}1", "-".repeat(20)), 1.);
+
+        // 过深的嵌套直接放弃，而不是耗尽栈空间
+        assert_eq!(evaluate(&nested(10_000)), None);
+        assert_eq!(evaluate(&format!("{}1", "-".repeat(100_000))), None);
+        assert_eq!(evaluate(&format!("2{}", "^2".repeat(10_000))), None);
+        assert_eq!(evaluate(&format!("{}1)", "sqrt(".repeat(10_000))), None);
+    }
+
+    #[test]
+    fn ignores_plain_numbers_and_names() {
+        // 单独的数字、常量或名称更可能是在搜索
+        for input in ["42", "3.14", "pi", "firefox"] {
+            assert_eq!(evaluate(input), None, "{}", input);
+        }
+    }
+}
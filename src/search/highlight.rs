@@ -0,0 +1,174 @@
+use pinyin::ToPinyin;
+
+use super::normalize::{fold, fold_char, to_simplified};
+use super::query::{QueryField, SearchQuery};
+
+
+/// 名称中的一个字符，以及它在原名称中的下标
+///
+/// 拼音展开后一个汉字会对应多个字符，它们共享同一个下标
+type IndexedChar = (char, usize);
+
+/// 逐字折叠，保留每个字符与原名称的对应关系
+fn original_chars(name: &str, fold_chinese: bool) -> Vec<IndexedChar> {
+    name.chars()
+        .enumerate()
+        .flat_map(|(index, c)| fold_char(c, fold_chinese).map(move |folded| (folded, index)))
+        .collect()
+}
+
+fn pinyin_chars(name: &str, fold_chinese: bool) -> Vec<IndexedChar> {
+    name.chars()
+        .enumerate()
+        .flat_map(|(index, c)| {
+            let c = if fold_chinese { to_simplified(c) } else { c };
+            let expanded = c.to_pinyin()
+                .map(|p| p.plain().to_string())
+                .unwrap_or_else(|| fold_char(c, fold_chinese).collect());
+
+            expanded.chars().map(move |p| (p, index)).collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// 连续匹配，返回命中的原名称下标
+fn contiguous(needle: &[char], haystack: &[IndexedChar]) -> Option<Vec<usize>> {
+    if needle.is_empty() || needle.len() > haystack.len() {
+        return None;
+    }
+
+    haystack
+        .windows(needle.len())
+        .find(|window| window.iter().zip(needle).all(|((c, _), n)| c == n))
+        .map(|window| window.iter().map(|(_, index)| *index).collect())
+}
+
+/// 按顺序逐个字符匹配，找不到的字符会被跳过
+///
+/// 返回命中的字符数与原名称下标
+fn subsequence(needle: &[char], haystack: &[IndexedChar]) -> (usize, Vec<usize>) {
+    let mut cursor = 0;
+    let mut matched = 0;
+    let mut indexes = Vec::new();
+
+    for n in needle {
+        if let Some(offset) = haystack[cursor..].iter().position(|(c, _)| c == n) {
+            let (_, index) = haystack[cursor + offset];
+            indexes.push(index);
+            matched += 1;
+            cursor += offset + 1;
+        }
+    }
+
+    (matched, indexes)
+}
+
+/// 计算 `text` 在名称中命中的字符
+///
+/// 优先寻找连续的匹配，其次按顺序逐字匹配。原文与拼音各算一次，取命中更多的一方，
+/// 拼音的命中会映射回对应的汉字
+pub fn match_spans(text: &str, name: &str, fold_chinese: bool) -> Vec<usize> {
+    let needle: Vec<char> = fold(text, fold_chinese)
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+
+    if needle.is_empty() {
+        return Vec::new();
+    }
+
+    let original = original_chars(name, fold_chinese);
+    let pinyin = pinyin_chars(name, fold_chinese);
+
+    let mut indexes = if let Some(indexes) = contiguous(&needle, &original) {
+        indexes
+    } else if let Some(indexes) = contiguous(&needle, &pinyin) {
+        indexes
+    } else {
+        let (original_matched, original_indexes) = subsequence(&needle, &original);
+        let (pinyin_matched, pinyin_indexes) = subsequence(&needle, &pinyin);
+
+        if pinyin_matched > original_matched {
+            pinyin_indexes
+        } else {
+            original_indexes
+        }
+    };
+
+    // 一个汉字的拼音可能命中多次
+    indexes.dedup();
+    indexes
+}
+
+/// 显示名称中需要高亮的字符下标，已排序且不重复
+pub fn highlights(query: &SearchQuery, name: &str, fold_chinese: bool) -> Vec<usize> {
+    let mut indexes = match_spans(&query.text, name, fold_chinese);
+
+    // 带引号的短语也一并高亮
+    for filter in query.filters.iter().filter(|f| f.field == QueryField::Name && !f.negated) {
+        let needle: Vec<char> = fold(&filter.value, fold_chinese).chars().collect();
+        if let Some(phrase) = contiguous(&needle, &original_chars(name, fold_chinese)) {
+            indexes.extend(phrase);
+        }
+    }
+
+    indexes.sort_unstable();
+    indexes.dedup();
+    indexes
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_spans() {
+        // (搜索文本, 名称, 繁简通搜, 高亮的下标)
+        let cases: &[(&str, &str, bool, &[usize])] = &[
+            ("", "Firefox", false, &[]),
+            ("xyz", "Firefox", false, &[]),
+            ("fire", "Firefox", false, &[0, 1, 2, 3]),
+            ("fox", "Firefox", false, &[4, 5, 6]),
+            // 空白会被忽略
+            ("fire fox", "Firefox", false, &[0, 1, 2, 3, 4, 5, 6]),
+            // 没有连续的匹配时按顺序逐字匹配
+            ("ff", "Firefox", false, &[0, 4]),
+            ("vscode", "VS Code", false, &[0, 1, 3, 4, 5, 6]),
+            ("fxz", "Firefox", false, &[0, 6]),
+            // 全角与大小写
+            ("ＦＩＲＥ", "Firefox", false, &[0, 1, 2, 3]),
+            ("cafe", "Café", false, &[0, 1, 2, 3]),
+            // 拼音的命中映射回对应的汉字
+            ("liulan", "浏览器", false, &[0, 1]),
+            ("lanqi", "浏览器", false, &[1, 2]),
+            ("llq", "浏览器", false, &[0, 1, 2]),
+            ("chromeliulanqi", "Chrome浏览器", false, &[0, 1, 2, 3, 4, 5, 6, 7, 8]),
+            ("浏览", "浏览器", false, &[0, 1]),
+            // 繁简通搜
+            ("浏览", "瀏覽器", true, &[0, 1]),
+            ("浏览", "瀏覽器", false, &[]),
+        ];
+
+        for (text, name, fold_chinese, expected) in cases {
+            assert_eq!(match_spans(text, name, *fold_chinese), *expected, "{:?} in {:?}", text, name);
+        }
+    }
+
+    #[test]
+    fn highlights_quoted_phrases() {
+        let cases: &[(&str, &str, &[usize])] = &[
+            ("\"fox\" fire", "Firefox", &[0, 1, 2, 3, 4, 5, 6]),
+            ("\"studio\"", "Visual Studio", &[7, 8, 9, 10, 11, 12]),
+            // 排除的词不高亮
+            ("fire -fox", "Firefox", &[0, 1, 2, 3]),
+            // 字段过滤不高亮
+            ("tag:fire", "Firefox", &[]),
+        ];
+
+        for (input, name, expected) in cases {
+            let query = SearchQuery::parse(input);
+            assert_eq!(highlights(&query, name, false), *expected, "{:?} in {:?}", input, name);
+        }
+    }
+}
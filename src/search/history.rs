@@ -0,0 +1,306 @@
+//! 搜索历史
+//!
+//! 回车执行了结果的查询会被记录下来，在空的搜索框中按上下键可以依次回填
+
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+use log::{debug, error, info};
+
+use crate::utils::file_utils::write_atomic;
+
+
+/// 搜索历史的设置
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct HistorySettings {
+    // 关闭时不会记录，也不会保存到磁盘
+    pub enabled: bool,
+    // 最多保留的条数
+    pub max_entries: usize,
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_entries: 50,
+        }
+    }
+}
+
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+struct HistoryFile {
+    // 最近的查询在前
+    entries: Vec<String>,
+}
+
+
+/// 已执行过的查询，最近的在前且不重复
+#[derive(Debug, Default)]
+pub struct SearchHistory {
+    entries: Vec<String>,
+    // 正在回填的条目，None 表示没有在浏览历史
+    cursor: Option<usize>,
+    // 保存历史的文件，None 时只保留在内存中
+    path: Option<PathBuf>,
+}
+
+fn history_path() -> PathBuf {
+    crate::paths::config_dir().join(crate::HISTORY_FILE_NAME)
+}
+
+impl SearchHistory {
+    /// 从磁盘读取历史，读取失败时为空
+    pub fn load(settings: &HistorySettings) -> Self {
+        Self::load_from(history_path(), settings)
+    }
+
+    fn load_from(path: PathBuf, settings: &HistorySettings) -> Self {
+        // 关闭时也记住路径，之后重新开启仍会保存
+        let file = settings.enabled
+            .then(|| std::fs::read_to_string(&path).ok())
+            .flatten()
+            .and_then(|content| serde_json::from_str::<HistoryFile>(&content).ok())
+            .unwrap_or_default();
+
+        let mut history = Self {
+            entries: file.entries,
+            cursor: None,
+            path: Some(path),
+        };
+        history.entries.truncate(settings.max_entries);
+        debug!("读取了 {} 条搜索历史", history.entries.len());
+        history
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let file = HistoryFile {
+            entries: self.entries.clone(),
+        };
+
+        let result = serde_json::to_string_pretty(&file)
+            .map_err(std::io::Error::from)
+            .and_then(|content| write_atomic(path, content.as_bytes()));
+        if let Err(e) = result {
+            error!("保存搜索历史失败: {}", e);
+        }
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// 记录一次执行过的查询，重复的查询会被移到最前
+    pub fn record(&mut self, query: &str, settings: &HistorySettings) {
+        self.cursor = None;
+
+        let query = query.trim();
+        if !settings.enabled || query.is_empty() {
+            return;
+        }
+
+        self.entries.retain(|entry| entry != query);
+        self.entries.insert(0, query.to_string());
+        self.entries.truncate(settings.max_entries);
+        self.save();
+    }
+
+    /// 清空历史并删除磁盘上的记录
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.cursor = None;
+
+        let Some(path) = &self.path else {
+            return;
+        };
+        if path.exists() {
+            match std::fs::remove_file(path) {
+                Ok(_) => info!("已清空搜索历史"),
+                Err(e) => error!("删除搜索历史失败: {}", e),
+            }
+        }
+    }
+
+    /// 按新的上限截断历史
+    pub fn apply_settings(&mut self, settings: &HistorySettings) {
+        if !settings.enabled {
+            self.clear();
+        } else if self.entries.len() > settings.max_entries {
+            self.entries.truncate(settings.max_entries);
+            self.save();
+        }
+    }
+
+    /// 是否正在回填历史
+    pub fn is_browsing(&self) -> bool {
+        self.cursor.is_some()
+    }
+
+    /// 结束浏览，例如用户修改了搜索框中的内容
+    pub fn reset_cursor(&mut self) {
+        self.cursor = None;
+    }
+
+    /// 向更早的历史移动，返回需要回填的内容
+    pub fn previous(&mut self) -> Option<&str> {
+        let next = match self.cursor {
+            None => 0,
+            Some(cursor) => (cursor + 1).min(self.entries.len().saturating_sub(1)),
+        };
+        let entry = self.entries.get(next)?;
+        self.cursor = Some(next);
+        Some(entry)
+    }
+
+    /// 向更新的历史移动，越过最新的一条时返回空字符串
+    pub fn next(&mut self) -> Option<&str> {
+        match self.cursor? {
+            0 => {
+                self.cursor = None;
+                Some("")
+            }
+            cursor => {
+                self.cursor = Some(cursor - 1);
+                self.entries.get(cursor - 1).map(String::as_str)
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(max_entries: usize) -> HistorySettings {
+        HistorySettings {
+            enabled: true,
+            max_entries: max_entries,
+        }
+    }
+
+    /// 依次记录，最后记录的在最前
+    fn history(queries: &[&str]) -> SearchHistory {
+        let mut history = SearchHistory::default();
+        for query in queries {
+            history.record(query, &settings(50));
+        }
+        history
+    }
+
+    fn temp_file() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("baroboard-history-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join(crate::HISTORY_FILE_NAME)
+    }
+
+    #[test]
+    fn browses_with_cursor() {
+        let mut history = history(&["a", "b", "c"]);
+        assert!(!history.is_browsing());
+
+        // 向更早移动，停在最早的一条
+        assert_eq!(history.previous(), Some("c"));
+        assert_eq!(history.previous(), Some("b"));
+        assert_eq!(history.previous(), Some("a"));
+        assert_eq!(history.previous(), Some("a"));
+        assert!(history.is_browsing());
+
+        // 向更新移动，越过最新的一条时回到空的搜索框
+        assert_eq!(history.next(), Some("b"));
+        assert_eq!(history.next(), Some("c"));
+        assert_eq!(history.next(), Some(""));
+        assert!(!history.is_browsing());
+        assert_eq!(history.next(), None);
+
+        assert_eq!(history.previous(), Some("c"));
+        history.reset_cursor();
+        assert_eq!(history.previous(), Some("c"));
+    }
+
+    #[test]
+    fn browsing_empty_history() {
+        let mut history = SearchHistory::default();
+        assert_eq!(history.previous(), None);
+        assert!(!history.is_browsing());
+        assert_eq!(history.next(), None);
+    }
+
+    #[test]
+    fn records_without_duplicates() {
+        let mut history = history(&["a", "b", "c", " b ", "", "   "]);
+        assert_eq!(history.entries(), ["b", "c", "a"]);
+
+        // 记录会结束浏览
+        history.previous();
+        history.previous();
+        history.record("d", &settings(50));
+        assert!(!history.is_browsing());
+        assert_eq!(history.previous(), Some("d"));
+
+        // 关闭时不记录
+        let disabled = HistorySettings {
+            enabled: false,
+            ..settings(50)
+        };
+        history.record("e", &disabled);
+        assert_eq!(history.entries(), ["d", "b", "c", "a"]);
+    }
+
+    #[test]
+    fn truncates_to_max_entries() {
+        let mut history = SearchHistory::default();
+        for query in ["a", "b", "c", "d"] {
+            history.record(query, &settings(3));
+        }
+        assert_eq!(history.entries(), ["d", "c", "b"]);
+
+        history.apply_settings(&settings(2));
+        assert_eq!(history.entries(), ["d", "c"]);
+
+        history.apply_settings(&HistorySettings {
+            enabled: false,
+            ..settings(2)
+        });
+        assert!(history.entries().is_empty());
+    }
+
+    #[test]
+    fn saves_and_loads() {
+        let path = temp_file();
+
+        let mut history = SearchHistory::load_from(path.clone(), &settings(50));
+        assert!(history.entries().is_empty());
+        history.record("firefox", &settings(50));
+        history.record("code", &settings(50));
+
+        let loaded = SearchHistory::load_from(path.clone(), &settings(50));
+        assert_eq!(loaded.entries(), ["code", "firefox"]);
+        // 读取时按上限截断
+        assert_eq!(SearchHistory::load_from(path.clone(), &settings(1)).entries(), ["code"]);
+
+        // 关闭时不读取，重新开启后仍会保存到同一个文件
+        let disabled = HistorySettings {
+            enabled: false,
+            ..settings(50)
+        };
+        let mut history = SearchHistory::load_from(path.clone(), &disabled);
+        assert!(history.entries().is_empty());
+        history.record("terminal", &settings(50));
+        assert_eq!(SearchHistory::load_from(path.clone(), &settings(50)).entries(), ["terminal"]);
+
+        history.clear();
+        assert!(!path.exists());
+
+        // 损坏的文件当作空的历史
+        std::fs::write(&path, "not json").unwrap();
+        assert!(SearchHistory::load_from(path.clone(), &settings(50)).entries().is_empty());
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
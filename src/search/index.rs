@@ -0,0 +1,109 @@
+use std::collections::{HashMap, HashSet};
+use pinyin::ToPinyin;
+
+use crate::my_structs::ProgramLink;
+use super::normalize::{fold, to_simplified};
+
+
+/// 快捷方式折叠后的各个字段
+#[derive(Debug, Clone)]
+pub struct IndexedLink {
+    // 折叠前的原始内容，用于判断快捷方式是否被修改
+    source: (String, Vec<String>, String, Vec<String>),
+
+    // 显示名称在前，其后为别名
+    pub names: Vec<String>,
+    pub pinyins: Vec<String>,
+    pub command: String,
+    pub arguments: Vec<String>,
+}
+
+impl IndexedLink {
+    fn new(program_link: &ProgramLink, fold_chinese: bool) -> Self {
+        Self {
+            source: (
+                program_link.name.clone(),
+                program_link.keywords.clone(),
+                program_link.run_command.clone(),
+                program_link.arguments.clone(),
+            ),
+
+            names: all_names(program_link)
+                .map(|name| fold(name, fold_chinese))
+                .collect(),
+            pinyins: all_names(program_link)
+                .map(|name| fold(&to_pinyin(name, fold_chinese), fold_chinese))
+                .collect(),
+            command: fold(&program_link.run_command, fold_chinese),
+            arguments: program_link.arguments
+                .iter()
+                .map(|argument| fold(argument, fold_chinese))
+                .collect(),
+        }
+    }
+
+    fn is_built_from(&self, program_link: &ProgramLink) -> bool {
+        self.source.0 == program_link.name
+            && self.source.1 == program_link.keywords
+            && self.source.2 == program_link.run_command
+            && self.source.3 == program_link.arguments
+    }
+}
+
+
+/// 显示名称与所有别名
+fn all_names(program_link: &ProgramLink) -> impl Iterator<Item = &String> {
+    std::iter::once(&program_link.name).chain(program_link.keywords.iter())
+}
+
+/// 名称的拼音，非汉字原样保留
+pub fn to_pinyin(name: &str, fold_chinese: bool) -> String {
+    name.chars().map(|c| {
+        let c = if fold_chinese { to_simplified(c) } else { c };
+        c.to_pinyin()
+        .map(|p| p.plain().to_string())
+        .unwrap_or_else(|| c.to_string())
+    }).collect::<String>()
+}
+
+
+/// 搜索索引，缓存每个快捷方式折叠后的字段
+///
+/// 以uuid为键，只有在快捷方式的内容或折叠方式改变时才会重新计算
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    fold_chinese: bool,
+    links: HashMap<String, IndexedLink>,
+}
+
+impl SearchIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 使索引与快捷方式列表保持一致
+    pub fn sync(&mut self, program_links: &[ProgramLink], fold_chinese: bool) {
+        if self.fold_chinese != fold_chinese {
+            self.links.clear();
+            self.fold_chinese = fold_chinese;
+        }
+
+        // 移除已经不存在的快捷方式
+        let uuids: HashSet<&str> = program_links.iter().map(|link| link.uuid.as_str()).collect();
+        self.links.retain(|uuid, _| uuids.contains(uuid.as_str()));
+
+        for program_link in program_links {
+            let up_to_date = self.links
+                .get(&program_link.uuid)
+                .is_some_and(|indexed| indexed.is_built_from(program_link));
+
+            if !up_to_date {
+                self.links.insert(program_link.uuid.clone(), IndexedLink::new(program_link, fold_chinese));
+            }
+        }
+    }
+
+    pub fn get(&self, uuid: &str) -> Option<&IndexedLink> {
+        self.links.get(uuid)
+    }
+}
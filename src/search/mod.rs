@@ -0,0 +1,160 @@
+pub mod query;
+pub mod highlight;
+pub mod scorer;
+pub mod normalize;
+pub mod index;
+pub mod calculator;
+pub mod provider;
+pub mod history;
+
+use serde::{Serialize, Deserialize};
+
+use crate::my_structs::ProgramLink;
+pub use index::{IndexedLink, SearchIndex};
+pub use query::{QueryFilter, SearchQuery};
+pub use scorer::Scorer;
+pub use history::{HistorySettings, SearchHistory};
+pub use provider::{FileSearchSettings, ItemIcon, SearchAction, SearchEngine, SearchItem, ShellCommand, ShellOutput, ShellSettings, WebSearchSettings, WebTemplate};
+
+
+/// 各字段得分的权重，为0时不参与匹配
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct FieldWeights {
+    // 显示名称
+    pub name: f64,
+    // 只用于匹配的别名
+    pub alias: f64,
+    // 名称的拼音
+    pub pinyin: f64,
+    pub command: f64,
+    pub arguments: f64,
+}
+
+impl Default for FieldWeights {
+    fn default() -> Self {
+        Self {
+            name: 1.,
+            alias: 1.,
+            pinyin: 1.,
+            command: 0.,
+            arguments: 0.,
+        }
+    }
+}
+
+
+/// 搜索相关的设置
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct SearchSettings {
+    pub scorer: Scorer,
+    // 相似度阈值，低于此值的结果不会被显示
+    pub threshold: f64,
+    pub weights: FieldWeights,
+    // 在结果上显示得分，用于调试
+    pub show_score: bool,
+    // 繁体与简体视为相同的字
+    pub fold_chinese: bool,
+    // 文件与文件夹搜索
+    pub files: FileSearchSettings,
+    // 关键字触发的网页搜索
+    pub web: WebSearchSettings,
+    pub history: HistorySettings,
+    // 以 $ 开头的命令模式
+    pub shell: ShellSettings,
+}
+
+impl Default for SearchSettings {
+    fn default() -> Self {
+        Self {
+            scorer: Scorer::default(),
+            threshold: 0.5,
+            weights: FieldWeights::default(),
+            show_score: false,
+            fold_chinese: false,
+            files: FileSearchSettings::default(),
+            web: WebSearchSettings::default(),
+            history: HistorySettings::default(),
+            shell: ShellSettings::default(),
+        }
+    }
+}
+
+
+/// 快捷方式各字段加权后的最高分
+///
+/// `text` 需要事先经过 [`normalize::fold`] 折叠
+pub fn link_score(text: &str, indexed: &IndexedLink, settings: &SearchSettings) -> f64 {
+    let scorer = settings.scorer;
+    let weights = &settings.weights;
+
+    // (权重, 字段内容)
+    let mut fields: Vec<(f64, &str)> = Vec::new();
+
+    for (index, name) in indexed.names.iter().enumerate() {
+        fields.push((if index == 0 { weights.name } else { weights.alias }, name));
+    }
+    for pinyin in indexed.pinyins.iter() {
+        fields.push((weights.pinyin, pinyin));
+    }
+    fields.push((weights.command, &indexed.command));
+    for argument in indexed.arguments.iter() {
+        fields.push((weights.arguments, argument));
+    }
+
+    fields
+        .iter()
+        .filter(|(weight, field)| *weight > 0. && !field.is_empty())
+        .map(|(weight, field)| weight * scorer.score(text, field))
+        .fold(0., f64::max)
+}
+
+/// 一条搜索结果
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub program_link: ProgramLink,
+    pub score: f64,
+    // 显示名称中被匹配的字符下标
+    pub highlights: Vec<usize>,
+}
+
+/// 按查询语句筛选并排序快捷方式
+///
+/// 没有搜索文本时，所有满足过滤条件的快捷方式都会以原有顺序返回
+pub fn search(
+    index: &mut SearchIndex,
+    program_links: &[ProgramLink],
+    query: &SearchQuery,
+    settings: &SearchSettings,
+) -> Vec<SearchResult> {
+    index.sync(program_links, settings.fold_chinese);
+
+    let text = normalize::fold(&query.text, settings.fold_chinese);
+
+    let mut results: Vec<SearchResult> = program_links
+        .iter()
+        .filter(|program_link| query.matches_filters(program_link, settings.fold_chinese))
+        .filter_map(|program_link| {
+            if text.is_empty() {
+                return Some((program_link, 1.));
+            }
+
+            let score = index
+                .get(&program_link.uuid)
+                .map_or(0., |indexed| link_score(&text, indexed, settings));
+            // 设置相似度阈值
+            (score > settings.threshold).then_some((program_link, score))
+        })
+        .map(|(program_link, score)| SearchResult {
+            program_link: program_link.clone(),
+            score: score,
+            highlights: highlight::highlights(query, &program_link.name, settings.fold_chinese),
+        })
+        .collect();
+
+    // 按相似度降序排列，sort_by 是稳定排序，同分时保留原有顺序
+    results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+
+    results
+}
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+
+
+/// 将单个字符折叠为用于比较的形式
+///
+/// 先做兼容分解（NFKD），去掉组合用的附加符号后再转为小写。
+/// 效果上相当于 NFKC 加上去除变音符号：全角的 `Ｖ` 会变成 `v`，`é` 会变成 `e`
+pub fn fold_char(c: char, fold_chinese: bool) -> impl Iterator<Item = char> {
+    let c = if fold_chinese { to_simplified(c) } else { c };
+
+    std::iter::once(c)
+        .nfkd()
+        .filter(|d| !is_combining_mark(*d))
+        .flat_map(|d| d.to_lowercase())
+}
+
+/// 将字符串折叠为用于比较的形式，搜索文本与被搜索的字段都需要经过这一步
+pub fn fold(text: &str, fold_chinese: bool) -> String {
+    text.chars()
+        .flat_map(|c| fold_char(c, fold_chinese))
+        .collect()
+}
+
+
+/// 繁体字转换为简体字，没有收录的字符原样返回
+///
+/// 这里只收录了常用字，足以应付快捷方式的名称
+pub fn to_simplified(c: char) -> char {
+    static TABLE: OnceLock<HashMap<char, char>> = OnceLock::new();
+
+    let table = TABLE.get_or_init(|| {
+        TRADITIONAL.concat().chars()
+            .zip(SIMPLIFIED.concat().chars())
+            .collect()
+    });
+
+    table.get(&c).copied().unwrap_or(c)
+}
+
+
+// 两张表按位置一一对应
+const TRADITIONAL: &[&str] = &[
+    "來係俠倆倉個們偉側偵傑傘備傳債傷傾僅價儀億儘償優儲兇兒內兩冊則剎剛創劃劇劉劍勁動",
+    "務勝勞勢勵匯匱區協卻厭厲參叢吳員問啟喚單嗎噴嚇嚴囑國圍圓圖團報場塊塵墜墳壓壞壯壺",
+    "壽夠夢夥夾奧奪奬奮姍娛婁婦媽嫻嬰孫學實寧審寫寬寵寶將專尋對導尷屆屍層屬岡島峽巖幟",
+    "幣幫幹幾庫廈廟廠廢廣廳弔張強彈彎後徑從復徵恆惡惱愛態慘慣慮憂憑憲憶應懶懷懸戀戰戲",
+    "戶拋挾捨掃掙掛採揀揚換揮損搖搶摟撥撲擁擇擊擔據擠擬擴擺攔攜攝敗敘敵數斂斃斷於昇時",
+    "晉晝暈暢暫曆曉曬書會朧朮東桿條棄棟楊業極榪榮構槍槓樁樂樓標樞樣樹橋機橫檔檢櫃欄權",
+    "歐歡歲歷歸殘殺殼毀氣氫決沒沖況淚淨淺測渾湧湯準溝溫滄滅滬滿漁漢漲潔潛澀澤濃濟瀉瀏",
+    "灑灣災為無煙熱燈爐爛爭爲爺爾牆牽犧狀猶獄獅獎獨獲現瑣瑪環甕產畝畢畫異當疊痺瘋瘡療",
+    "癡癢發皚皺盜盞盡監盤眾睜矯硯碩確碼磚礎祿禍禦禪禮禱稅種稱穀穌穩窩窮竊競筆筍節範篩",
+    "簡簽簾籃粵糧紀約紅紋紙級紛紡細終組結絕絡給統絲綁經綜綠維網緊緒線緣編緩練縣縫縮總",
+    "績織繩繪繳繼續纖罈罰罷羅義習翹聖聞聯聰聲聳職聽肅脅腦腳膚膽臉臨臺與興舉舊艙艦艷莊",
+    "華萬葉蔣薦藍藝藥蘇蘋蘭蘿處虛號虧蝦蟲蠟蠶術衛衝袞裏補裝裡製複褲襪襯見規視親覺覽觀",
+    "觸訂計訊記訝訪設許訴診詐評詞詢試詩話該詳誇誌認語誠誤說誰課調談請論諸諾謊謎謙講謝",
+    "謹證譏識譜譯議護譽讀變讓讚豎豐豬貓貝貞負財貢貧貨販貪貫貴買費貼貿賀資賊賓賜賞賠賣",
+    "質賬賴賺購賽贈贏趕趙趨跡踐蹤躍軀車軌軍軟軸較載輔輕輛輝輪輯輸轉轎轟辦辭辮辯農這連",
+    "週進遊運過達遙遞遠適遲選遺邁還邊郵鄉鄭鄰醜醫醬釀釋針鈔鈕鈴鉛銀銅銘銳鋒鋪鋼錄錢錦",
+    "錯鍊鍋鍛鍵鎖鎮鏈鏡鐘鐵鑄鑰長門閃閉開閒間閘閣閱闆闊闖關陝陣陰陳陸陽隊際隨險隱隴隸",
+    "隻雋雖雙雛雜雞離難雲電霧靂靈靜韋韓響頁頂頃項順須頌預頒頓領頭頰頸頹頻顆題額顏願類",
+    "顧顫顯風颱飄飛飢飯飲飾餅養餓餘館餵饑饒馬馮駐駕駛騎騙騷驅驕驗驚驟骯髒體髮鬆鬍鬢鬥",
+    "鬧鬱魚魯鮑鮮鯨鳥鳳鴨鴻鵝鶴鷹鹹鹽麗麥麵麼黃點黨黴黷鼕齊齋齒齡齣龍龐龜",
+];
+
+const SIMPLIFIED: &[&str] = &[
+    "来系侠俩仓个们伟侧侦杰伞备传债伤倾仅价仪亿尽偿优储凶儿内两册则刹刚创划剧刘剑劲动",
+    "务胜劳势励汇匮区协却厌厉参丛吴员问启唤单吗喷吓严嘱国围圆图团报场块尘坠坟压坏壮壶",
+    "寿够梦伙夹奥夺奖奋姗娱娄妇妈娴婴孙学实宁审写宽宠宝将专寻对导尴届尸层属冈岛峡岩帜",
+    "币帮干几库厦庙厂废广厅吊张强弹弯后径从复征恒恶恼爱态惨惯虑忧凭宪忆应懒怀悬恋战戏",
+    "户抛挟舍扫挣挂采拣扬换挥损摇抢搂拨扑拥择击担据挤拟扩摆拦携摄败叙敌数敛毙断于升时",
+    "晋昼晕畅暂历晓晒书会胧术东杆条弃栋杨业极杩荣构枪杠桩乐楼标枢样树桥机横档检柜栏权",
+    "欧欢岁历归残杀壳毁气氢决没冲况泪净浅测浑涌汤准沟温沧灭沪满渔汉涨洁潜涩泽浓济泻浏",
+    "洒湾灾为无烟热灯炉烂争为爷尔墙牵牺状犹狱狮奖独获现琐玛环瓮产亩毕画异当叠痹疯疮疗",
+    "痴痒发皑皱盗盏尽监盘众睁矫砚硕确码砖础禄祸御禅礼祷税种称谷稣稳窝穷窃竞笔笋节范筛",
+    "简签帘篮粤粮纪约红纹纸级纷纺细终组结绝络给统丝绑经综绿维网紧绪线缘编缓练县缝缩总",
+    "绩织绳绘缴继续纤坛罚罢罗义习翘圣闻联聪声耸职听肃胁脑脚肤胆脸临台与兴举旧舱舰艳庄",
+    "华万叶蒋荐蓝艺药苏苹兰萝处虚号亏虾虫蜡蚕术卫冲衮里补装里制复裤袜衬见规视亲觉览观",
+    "触订计讯记讶访设许诉诊诈评词询试诗话该详夸志认语诚误说谁课调谈请论诸诺谎谜谦讲谢",
+    "谨证讥识谱译议护誉读变让赞竖丰猪猫贝贞负财贡贫货贩贪贯贵买费贴贸贺资贼宾赐赏赔卖",
+    "质账赖赚购赛赠赢赶赵趋迹践踪跃躯车轨军软轴较载辅轻辆辉轮辑输转轿轰办辞辫辩农这连",
+    "周进游运过达遥递远适迟选遗迈还边邮乡郑邻丑医酱酿释针钞钮铃铅银铜铭锐锋铺钢录钱锦",
+    "错炼锅锻键锁镇链镜钟铁铸钥长门闪闭开闲间闸阁阅板阔闯关陕阵阴陈陆阳队际随险隐陇隶",
+    "只隽虽双雏杂鸡离难云电雾雳灵静韦韩响页顶顷项顺须颂预颁顿领头颊颈颓频颗题额颜愿类",
+    "顾颤显风台飘飞饥饭饮饰饼养饿余馆喂饥饶马冯驻驾驶骑骗骚驱骄验惊骤肮脏体发松胡鬓斗",
+    "闹郁鱼鲁鲍鲜鲸鸟凤鸭鸿鹅鹤鹰咸盐丽麦面么黄点党霉黩冬齐斋齿龄出龙庞龟",
+];
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_width_case_and_diacritics() {
+        let cases = [
+            ("Firefox", "firefox"),
+            ("ＶＳ Ｃｏｄｅ", "vs code"),
+            ("Café", "cafe"),
+            ("naïve", "naive"),
+            ("Ångström", "angstrom"),
+            ("Ｎｏ．１", "no.1"),
+            // 兼容分解会拆开连字与带圈数字
+            ("ﬁle", "file"),
+            ("①②", "12"),
+            ("浏览器", "浏览器"),
+            ("", ""),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(fold(text, false), expected, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn folds_traditional_chinese() {
+        assert_eq!(fold("瀏覽器", true), "浏览器");
+        assert_eq!(fold("開關 Ｖ", true), "开关 v");
+        // 不开启繁简通搜时保留繁体
+        assert_eq!(fold("瀏覽器", false), "瀏覽器");
+    }
+
+    #[test]
+    fn simplifies_known_characters_only() {
+        assert_eq!(to_simplified('體'), '体');
+        assert_eq!(to_simplified('体'), '体');
+        assert_eq!(to_simplified('a'), 'a');
+        assert_eq!(to_simplified('器'), '器');
+    }
+
+    #[test]
+    fn conversion_tables_line_up() {
+        // 两张表按位置一一对应，长度不同会让后面的字全部错位
+        let traditional: Vec<char> = TRADITIONAL.concat().chars().collect();
+        let simplified: Vec<char> = SIMPLIFIED.concat().chars().collect();
+        assert_eq!(traditional.len(), simplified.len());
+
+        let mut seen = std::collections::HashSet::new();
+        for c in traditional {
+            assert!(seen.insert(c), "{} 在繁体表中重复", c);
+        }
+    }
+}
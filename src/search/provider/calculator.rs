@@ -0,0 +1,32 @@
+use crate::search::calculator;
+use super::{ItemIcon, ProviderQuery, SearchAction, SearchItem, SearchProvider};
+
+
+/// 计算结果总是排在快捷方式之前
+const CALCULATOR_SCORE: f64 = 10.;
+
+
+/// 搜索栏中的计算器与单位换算
+#[derive(Debug)]
+pub struct CalculatorProvider;
+
+impl SearchProvider for CalculatorProvider {
+    fn name(&self) -> &'static str {
+        "计算器"
+    }
+
+    fn search(&self, query: &ProviderQuery) -> Vec<SearchItem> {
+        let Some(calculation) = calculator::evaluate(&query.raw) else {
+            return Vec::new();
+        };
+
+        vec![SearchItem {
+            label: calculation.result_text(),
+            detail: format!("{} {}\n回车复制", calculation.description, calculation.result_text()),
+            icon: ItemIcon::Glyph("🖩"),
+            score: CALCULATOR_SCORE,
+            highlights: Vec::new(),
+            action: SearchAction::CopyText(calculation.value_text()),
+        }]
+    }
+}
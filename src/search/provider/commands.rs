@@ -0,0 +1,81 @@
+use crate::commands::AppCommand;
+use crate::pages::popups::link::profile;
+use crate::search::highlight;
+use crate::search::index::to_pinyin;
+use crate::search::normalize::fold;
+use super::{ItemIcon, ProviderQuery, SearchAction, SearchItem, SearchProvider};
+
+
+/// 命令面板的前缀
+pub const COMMAND_PREFIX: &str = ">";
+
+
+/// 以 `>` 开头的命令面板，匹配 BaroBoard 自身的命令与切换到各个工作区
+#[derive(Debug)]
+pub struct CommandProvider;
+
+impl SearchProvider for CommandProvider {
+    fn name(&self) -> &'static str {
+        "命令"
+    }
+
+    fn prefix(&self) -> Option<&'static str> {
+        Some(COMMAND_PREFIX)
+    }
+
+    fn search(&self, query: &ProviderQuery) -> Vec<SearchItem> {
+        let Some(text) = query.raw.trim_start().strip_prefix(COMMAND_PREFIX) else {
+            return Vec::new();
+        };
+        let settings = &query.settings;
+        let text = fold(text.trim(), settings.fold_chinese);
+
+        // 没有输入时按注册顺序列出所有命令
+        let score = |label: &str, keywords: &[&str]| {
+            if text.is_empty() {
+                return Some(1.);
+            }
+
+            let score = [fold(label, settings.fold_chinese), fold(&to_pinyin(label, settings.fold_chinese), settings.fold_chinese)]
+                .iter()
+                .map(String::as_str)
+                .chain(keywords.iter().copied())
+                .map(|field| settings.scorer.score(&text, field))
+                .fold(0., f64::max);
+
+            (score > settings.threshold).then_some(score)
+        };
+
+        let commands = AppCommand::ALL
+            .into_iter()
+            .filter_map(|command| Some((command, score(command.label(), command.keywords())?)))
+            .map(|(command, score)| SearchItem {
+                label: command.label().to_string(),
+                detail: command.description().to_string(),
+                icon: ItemIcon::Glyph(command.glyph()),
+                score: score,
+                highlights: highlight::match_spans(&text, command.label(), settings.fold_chinese),
+                action: SearchAction::RunCommand(command),
+            });
+
+        // 当前工作区以外的工作区
+        let current = profile::current();
+        let profiles = profile::list_profiles()
+            .into_iter()
+            .filter(|name| *name != current)
+            .filter_map(|name| {
+                let label = format!("切换到工作区：{}", profile::display_name(name.as_deref()));
+                let score = score(&label, &["switch profile", "workspace"])?;
+                Some(SearchItem {
+                    highlights: highlight::match_spans(&text, &label, settings.fold_chinese),
+                    label: label,
+                    detail: profile::profile_path(name.as_deref()).display().to_string(),
+                    icon: ItemIcon::Glyph("⇄"),
+                    score: score,
+                    action: SearchAction::SwitchProfile(name),
+                })
+            });
+
+        commands.chain(profiles).collect()
+    }
+}
@@ -0,0 +1,553 @@
+//! Linux 下已安装的应用程序
+//!
+//! 按照 XDG 规范扫描各个数据目录中的 `.desktop` 文件，
+//! 用户目录中的同名文件会覆盖系统目录中的
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use log::{debug, info, warn};
+
+use crate::my_structs::ProgramLink;
+use crate::search::{self, SearchIndex};
+use super::{ItemIcon, ProviderQuery, SearchAction, SearchItem, SearchProvider};
+
+
+/// 应用列表的有效期，过期后会在后台重新扫描
+const REFRESH_INTERVAL: Duration = Duration::from_secs(300);
+/// 应用的得分略低于同名的快捷方式
+const APP_SCORE_FACTOR: f64 = 0.95;
+
+
+/// 一个 `.desktop` 文件描述的应用
+#[derive(Debug, Clone, PartialEq)]
+pub struct DesktopApp {
+    // 去掉 .desktop 后缀的文件ID，例如 org.gnome.Nautilus
+    pub id: String,
+    pub name: String,
+    pub generic_name: Option<String>,
+    pub keywords: Vec<String>,
+    // 已去掉字段代码的命令与参数
+    pub command: String,
+    pub arguments: Vec<String>,
+    // 图标文件的路径
+    pub icon_path: Option<String>,
+}
+
+impl DesktopApp {
+    /// 通用名称与关键词，作为快捷方式的别名
+    fn link_keywords(&self) -> Vec<String> {
+        let mut keywords: Vec<String> = self.generic_name.iter().cloned().collect();
+        keywords.extend(self.keywords.iter().cloned());
+        keywords
+    }
+
+    /// 用于搜索的快捷方式
+    fn to_search_link(&self) -> ProgramLink {
+        ProgramLink {
+            name: self.name.clone(),
+            keywords: self.link_keywords(),
+            icon_path: self.icon_path.clone().unwrap_or_default(),
+            run_command: self.command.clone(),
+            arguments: self.arguments.clone(),
+            uuid: format!("desktop:{}", self.id),
+            ..Default::default()
+        }
+    }
+
+    /// 固定为一个永久的快捷方式
+    pub fn to_program_link(&self) -> ProgramLink {
+        ProgramLink {
+            name: self.name.clone(),
+            keywords: self.link_keywords(),
+            icon_path: self.icon_path.clone().unwrap_or_default(),
+            run_command: self.command.clone(),
+            arguments: self.arguments.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// 直接启动应用
+    pub fn launch(&self) {
+        match std::process::Command::new(&self.command).args(&self.arguments).spawn() {
+            Ok(_) => debug!("{} 启动成功", self.name),
+            Err(e) => warn!("{} 启动失败: {}", self.name, e),
+        }
+    }
+}
+
+
+/// XDG 数据目录，优先级从高到低
+fn data_dirs() -> Vec<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home.as_ref().map(|home| home.join(".local/share")));
+
+    let system_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+
+    let mut dirs: Vec<PathBuf> = data_home.into_iter().collect();
+    // flatpak 导出的应用
+    if let Some(home) = &home {
+        dirs.push(home.join(".local/share/flatpak/exports/share"));
+    }
+    dirs.push(PathBuf::from("/var/lib/flatpak/exports/share"));
+    dirs.extend(system_dirs.split(':').filter(|dir| !dir.is_empty()).map(PathBuf::from));
+
+    let mut seen = Vec::new();
+    dirs.retain(|dir| {
+        let new = !seen.contains(dir);
+        seen.push(dir.clone());
+        new
+    });
+    dirs
+}
+
+/// 当前语言，例如 zh_CN.UTF-8 对应 ("zh_CN", "zh")
+fn locale() -> Option<(String, String)> {
+    let lang = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|key| std::env::var(key).ok())
+        .find(|value| !value.is_empty())?;
+
+    let full = lang.split(['.', '@']).next()?.to_string();
+    let short = full.split('_').next()?.to_string();
+    Some((full, short))
+}
+
+
+/// 递归收集目录中的 `.desktop` 文件，子目录中的文件ID用 `-` 连接
+fn collect_desktop_files(dir: &Path, prefix: &str, files: &mut Vec<(String, PathBuf)>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let file_name = entry.file_name().to_string_lossy().to_string();
+
+        if path.is_dir() {
+            collect_desktop_files(&path, &format!("{}{}-", prefix, file_name), files);
+        } else if let Some(id) = file_name.strip_suffix(".desktop") {
+            files.push((format!("{}{}", prefix, id), path));
+        }
+    }
+}
+
+/// 解析 `.desktop` 文件，只返回可以显示的应用
+fn parse_desktop_file(id: &str, content: &str, locale: &Option<(String, String)>) -> Option<DesktopApp> {
+    let mut in_entry = false;
+    let mut values: HashMap<&str, &str> = HashMap::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_entry {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            values.entry(key.trim()).or_insert(value.trim());
+        }
+    }
+
+    // 优先使用本地化的值
+    let localized = |key: &str| -> Option<String> {
+        if let Some((full, short)) = locale {
+            for lang in [full, short] {
+                if let Some(value) = values.get(format!("{}[{}]", key, lang).as_str()) {
+                    return Some(unescape(value));
+                }
+            }
+        }
+        values.get(key).map(|value| unescape(value))
+    };
+    let is_true = |key: &str| values.get(key).is_some_and(|value| *value == "true");
+
+    if values.get("Type").is_some_and(|kind| *kind != "Application")
+        || is_true("NoDisplay")
+        || is_true("Hidden")
+    {
+        return None;
+    }
+
+    let name = localized("Name")?;
+    let exec = values.get("Exec")?;
+    let (command, arguments) = parse_exec(exec, &name)?;
+
+    Some(DesktopApp {
+        id: id.to_string(),
+        name: name,
+        generic_name: localized("GenericName"),
+        keywords: localized("Keywords")
+            .map(|keywords| keywords
+                .split(';')
+                .map(|keyword| keyword.trim().to_string())
+                .filter(|keyword| !keyword.is_empty())
+                .collect())
+            .unwrap_or_default(),
+        command: command,
+        arguments: arguments,
+        icon_path: values.get("Icon").and_then(|icon| find_icon(icon)),
+    })
+}
+
+/// 字符串值中的转义字符
+fn unescape(value: &str) -> String {
+    let mut result = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => result.push(' '),
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some(other) => result.push(other),
+            None => {}
+        }
+    }
+    result
+}
+
+/// 拆分 Exec 并去掉 `%f` `%U` 等字段代码
+fn parse_exec(exec: &str, name: &str) -> Option<(String, Vec<String>)> {
+    let mut words = shlex::split(&unescape(exec))?
+        .into_iter()
+        .filter_map(|word| {
+            // 只由字段代码组成的参数整个去掉，原本就是空字符串的参数保留
+            let expanded = expand_field_codes(&word, name);
+            (!expanded.is_empty() || word.is_empty()).then_some(expanded)
+        });
+
+    let command = words.next()?;
+    Some((command, words.collect()))
+}
+
+/// 展开单个参数中的字段代码，字段代码也可能嵌在参数中间，例如 `--file=%f`
+///
+/// 没有文件可以传入，文件与网址相关的代码直接去掉，`%c` 替换为应用名称
+fn expand_field_codes(word: &str, name: &str) -> String {
+    let mut result = String::new();
+    let mut chars = word.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('%') => result.push('%'),
+            Some('c') => result.push_str(name),
+            Some('f' | 'F' | 'u' | 'U' | 'd' | 'D' | 'n' | 'N' | 'v' | 'm' | 'i' | 'k') => {}
+            // 未知的字段代码原样保留
+            Some(other) => {
+                result.push('%');
+                result.push(other);
+            }
+            None => result.push('%'),
+        }
+    }
+    result
+}
+
+/// 在图标主题中查找图标文件，只接受 png 与 svg
+fn find_icon(icon: &str) -> Option<String> {
+    let path = Path::new(icon);
+    if path.is_absolute() {
+        return path.exists().then(|| icon.to_string());
+    }
+
+    let sizes = ["256x256", "128x128", "96x96", "64x64", "48x48", "scalable"];
+    let mut candidates = Vec::new();
+
+    for data_dir in data_dirs() {
+        for size in sizes {
+            for extension in ["png", "svg"] {
+                candidates.push(data_dir.join(format!("icons/hicolor/{}/apps/{}.{}", size, icon, extension)));
+            }
+        }
+    }
+    for extension in ["png", "svg"] {
+        candidates.push(PathBuf::from(format!("/usr/share/pixmaps/{}.{}", icon, extension)));
+    }
+
+    candidates
+        .into_iter()
+        .find(|candidate| candidate.exists())
+        .map(|candidate| candidate.display().to_string())
+}
+
+/// 扫描所有的应用，同一个文件ID只保留优先级最高的
+pub fn scan_desktop_apps() -> Vec<DesktopApp> {
+    let locale = locale();
+    let mut apps: Vec<DesktopApp> = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+
+    for data_dir in data_dirs() {
+        let mut files = Vec::new();
+        collect_desktop_files(&data_dir.join("applications"), "", &mut files);
+        files.sort();
+
+        for (id, path) in files {
+            // 高优先级目录中的文件，即使被隐藏也会覆盖低优先级的
+            if !seen.insert(id.clone()) {
+                continue;
+            }
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            if let Some(app) = parse_desktop_file(&id, &content, &locale) {
+                apps.push(app);
+            }
+        }
+    }
+
+    apps
+}
+
+
+/// 已安装应用的索引
+struct AppIndex {
+    apps: Vec<DesktopApp>,
+    links: Vec<ProgramLink>,
+    // 搜索用快捷方式的 uuid 到应用下标的映射
+    positions: HashMap<String, usize>,
+    scanned_at: Option<Instant>,
+}
+
+/// 已安装的应用程序，不需要创建快捷方式也可以搜索
+pub struct DesktopAppProvider {
+    index: Arc<RwLock<AppIndex>>,
+    search_index: Mutex<SearchIndex>,
+    scanning: Arc<AtomicBool>,
+}
+
+impl DesktopAppProvider {
+    pub fn new() -> Self {
+        let provider = Self {
+            index: Arc::new(RwLock::new(AppIndex {
+                apps: Vec::new(),
+                links: Vec::new(),
+                positions: HashMap::new(),
+                scanned_at: None,
+            })),
+            search_index: Mutex::new(SearchIndex::new()),
+            scanning: Arc::new(AtomicBool::new(false)),
+        };
+        provider.refresh();
+        provider
+    }
+
+    /// 在后台重新扫描应用
+    fn refresh(&self) {
+        if self.scanning.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let index = self.index.clone();
+        let scanning = self.scanning.clone();
+        std::thread::spawn(move || {
+            let apps = scan_desktop_apps();
+            info!("扫描到 {} 个已安装的应用", apps.len());
+
+            let links: Vec<ProgramLink> = apps.iter().map(DesktopApp::to_search_link).collect();
+            let positions = links
+                .iter()
+                .enumerate()
+                .map(|(position, link)| (link.uuid.clone(), position))
+                .collect();
+
+            let mut index = index.write().unwrap();
+            index.links = links;
+            index.positions = positions;
+            index.apps = apps;
+            index.scanned_at = Some(Instant::now());
+            scanning.store(false, Ordering::SeqCst);
+        });
+    }
+}
+
+impl SearchProvider for DesktopAppProvider {
+    fn name(&self) -> &'static str {
+        "已安装的应用"
+    }
+
+    fn search(&self, query: &ProviderQuery) -> Vec<SearchItem> {
+        // 只有过滤条件时不列出所有的应用
+        if query.query.text.trim().is_empty() {
+            return Vec::new();
+        }
+
+        let index = self.index.read().unwrap();
+
+        if index.scanned_at.is_none_or(|scanned_at| scanned_at.elapsed() > REFRESH_INTERVAL) {
+            self.refresh();
+        }
+
+        // 已经固定为快捷方式的应用不再重复显示
+        let pinned: Vec<(&String, &Vec<String>)> = query.program_links
+            .iter()
+            .map(|link| (&link.run_command, &link.arguments))
+            .collect();
+
+        let mut search_index = self.search_index.lock().unwrap();
+        search::search(&mut search_index, &index.links, &query.query, &query.settings)
+            .into_iter()
+            .filter_map(|result| {
+                let app = &index.apps[*index.positions.get(&result.program_link.uuid)?];
+                if pinned.contains(&(&app.command, &app.arguments)) {
+                    return None;
+                }
+
+                Some(SearchItem {
+                    label: app.name.clone(),
+                    detail: format!(
+                        "{}\n{} {}",
+                        app.generic_name.clone().unwrap_or_else(|| "应用程序".to_string()),
+                        app.command,
+                        app.arguments.join(" "),
+                    ),
+                    icon: match &app.icon_path {
+                        Some(path) => ItemIcon::Image(path.clone()),
+                        None => ItemIcon::Glyph("📦"),
+                    },
+                    score: result.score * APP_SCORE_FACTOR,
+                    highlights: result.highlights,
+                    action: SearchAction::LaunchApp(app.clone()),
+                })
+            })
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_exec() {
+        // (Exec, 命令, 参数)
+        let cases: &[(&str, &str, &[&str])] = &[
+            ("firefox", "firefox", &[]),
+            ("firefox %u", "firefox", &[]),
+            ("code --new-window %F", "code", &["--new-window"]),
+            (r#"sh -c "echo hi; read""#, "sh", &["-c", "echo hi; read"]),
+            // 嵌在参数中的字段代码
+            ("app --file=%f --url=%u", "app", &["--file=", "--url="]),
+            ("app --title=%c %c", "app", &["--title=Editor", "Editor"]),
+            ("app 100%% %%f", "app", &["100%", "%f"]),
+            // 未知的字段代码原样保留
+            ("app %z", "app", &["%z"]),
+            // 空字符串参数保留
+            (r#"app """#, "app", &[""]),
+            (r#""/opt/my app/run" --verbose"#, "/opt/my app/run", &["--verbose"]),
+            // 先处理字符串的转义，再按引号规则拆分
+            (r#"app "a\\\\b""#, "app", &[r"a\b"]),
+        ];
+
+        for (exec, command, arguments) in cases {
+            assert_eq!(
+                parse_exec(exec, "Editor"),
+                Some((command.to_string(), strings(arguments))),
+                "{:?}", exec,
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_exec() {
+        for exec in ["", "%U", "%f %u", r#"app "unclosed"#] {
+            assert_eq!(parse_exec(exec, "Editor"), None, "{:?}", exec);
+        }
+    }
+
+    #[test]
+    fn parses_desktop_file() {
+        let content = "\
+# 注释
+[Desktop Entry]
+Type=Application
+Name=Text Editor
+Name[zh_CN]=文本编辑器
+Name[zh]=编辑器
+GenericName=Editor
+Keywords=text;notes; ;
+Exec=gedit --new-window %U
+Terminal=false
+Name=Ignored Duplicate
+
+[Desktop Action new-window]
+Name=New Window
+Exec=gedit --new-document
+";
+
+        let english = parse_desktop_file("org.gnome.gedit", content, &None).unwrap();
+        assert_eq!(english, DesktopApp {
+            id: "org.gnome.gedit".to_string(),
+            name: "Text Editor".to_string(),
+            generic_name: Some("Editor".to_string()),
+            keywords: strings(&["text", "notes"]),
+            command: "gedit".to_string(),
+            arguments: strings(&["--new-window"]),
+            icon_path: None,
+        });
+
+        // 优先使用完整的语言代码，其次是语言
+        let locale = Some(("zh_CN".to_string(), "zh".to_string()));
+        assert_eq!(parse_desktop_file("gedit", content, &locale).unwrap().name, "文本编辑器");
+        let locale = Some(("zh_TW".to_string(), "zh".to_string()));
+        assert_eq!(parse_desktop_file("gedit", content, &locale).unwrap().name, "编辑器");
+        let locale = Some(("fr_FR".to_string(), "fr".to_string()));
+        assert_eq!(parse_desktop_file("gedit", content, &locale).unwrap().name, "Text Editor");
+    }
+
+    #[test]
+    fn skips_hidden_and_incomplete_entries() {
+        let cases = [
+            "[Desktop Entry]\nType=Link\nName=Site\nURL=https://example.com\n",
+            "[Desktop Entry]\nName=Hidden\nExec=app\nNoDisplay=true\n",
+            "[Desktop Entry]\nName=Removed\nExec=app\nHidden=true\n",
+            "[Desktop Entry]\nName=No Exec\n",
+            "[Desktop Entry]\nExec=app\n",
+            // 只有动作中的 Exec 不算
+            "[Desktop Entry]\nName=App\n[Desktop Action new]\nExec=app\n",
+            "[Desktop Entry]\nName=App\nExec=%U\n",
+        ];
+        for content in cases {
+            assert_eq!(parse_desktop_file("app", content, &None), None, "{:?}", content);
+        }
+
+        // 没有 Type 时当作应用
+        let app = parse_desktop_file("app", "[Desktop Entry]\nName=App\nExec=app\nNoDisplay=false\n", &None);
+        assert_eq!(app.map(|app| app.command), Some("app".to_string()));
+    }
+
+    #[test]
+    fn pinned_link_keeps_command() {
+        let app = parse_desktop_file("htop", "[Desktop Entry]\nName=htop\nExec=htop\nTerminal=true\n", &None).unwrap();
+        let program_link = app.to_program_link();
+        assert_eq!(program_link.run_command, "htop");
+        // 新窗口是 Windows 的命令行选项，不用于终端应用
+        assert!(!program_link.is_new_window);
+    }
+}
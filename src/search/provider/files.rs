@@ -0,0 +1,406 @@
+//! 指定目录中的文件与文件夹
+//!
+//! 索引在后台建立并保存到缓存文件夹中。刷新时只会重新读取修改时间发生变化的目录，
+//! 其余目录直接使用缓存中的内容
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use serde::{Serialize, Deserialize};
+use log::{debug, info, warn};
+
+use crate::my_structs::ProgramLink;
+use crate::search::{self, SearchIndex};
+use super::{ItemIcon, ProviderQuery, SearchAction, SearchItem, SearchProvider};
+
+
+/// 索引缓存的文件名
+const CACHE_FILE_NAME: &str = "files.json";
+/// 缓存格式的版本，不一致时丢弃缓存
+const CACHE_VERSION: u32 = 1;
+/// 索引的有效期，过期后会在后台增量刷新
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+/// 文件的得分略低于快捷方式与应用
+const FILE_SCORE_FACTOR: f64 = 0.9;
+/// 最多显示的文件数量
+const MAX_RESULTS: usize = 50;
+
+
+/// 文件搜索的设置
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct FileSearchSettings {
+    // 需要索引的目录，支持以 ~ 开头
+    pub directories: Vec<String>,
+    // 忽略的文件名，支持 * 与 ? 通配符，包含 / 时匹配相对路径
+    pub ignore_patterns: Vec<String>,
+    // 最大递归深度，目录本身的子项为第1层
+    pub max_depth: usize,
+}
+
+impl Default for FileSearchSettings {
+    fn default() -> Self {
+        Self {
+            directories: Vec::new(),
+            ignore_patterns: vec![
+                ".*".to_string(),
+                "node_modules".to_string(),
+                "target".to_string(),
+                "__pycache__".to_string(),
+            ],
+            max_depth: 4,
+        }
+    }
+}
+
+
+/// 索引中的一个文件或文件夹
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FileEntry {
+    pub path: PathBuf,
+    pub name: String,
+    pub is_dir: bool,
+}
+
+/// 某个目录上一次读取时的状态
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct CachedDir {
+    // 修改时间，单位为纳秒
+    modified: u128,
+    // (名称, 是否为目录)
+    children: Vec<(String, bool)>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct FileIndexCache {
+    version: u32,
+    dirs: HashMap<PathBuf, CachedDir>,
+}
+
+
+/// 展开开头的 ~
+fn expand_home(dir: &str) -> PathBuf {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"));
+
+    match (dir.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest.trim_start_matches(['/', '\\'])),
+        _ => PathBuf::from(dir),
+    }
+}
+
+/// 简单的通配符匹配，`*` 匹配任意个字符，`?` 匹配一个字符
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    // 上一个 * 的位置，以及它当时对应的文本位置
+    let mut star: Option<(usize, usize)> = None;
+    let (mut p, mut t) = (0, 0);
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+fn is_ignored(patterns: &[String], name: &str, relative: &Path) -> bool {
+    let relative = relative.to_string_lossy().replace('\\', "/");
+
+    patterns.iter().any(|pattern| {
+        if pattern.contains('/') {
+            glob_match(pattern.trim_matches('/'), &relative)
+        } else {
+            glob_match(pattern, name)
+        }
+    })
+}
+
+fn modified_time(path: &Path) -> Option<u128> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_nanos())
+}
+
+fn cache_path() -> PathBuf {
+    crate::paths::cache_dir().join(CACHE_FILE_NAME)
+}
+
+fn load_cache() -> FileIndexCache {
+    let cache = std::fs::read_to_string(cache_path())
+        .ok()
+        .and_then(|content| serde_json::from_str::<FileIndexCache>(&content).ok());
+
+    match cache {
+        Some(cache) if cache.version == CACHE_VERSION => cache,
+        _ => FileIndexCache {
+            version: CACHE_VERSION,
+            dirs: HashMap::new(),
+        },
+    }
+}
+
+fn save_cache(cache: &FileIndexCache) -> Result<(), std::io::Error> {
+    let path = cache_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, serde_json::to_string(cache)?)
+}
+
+
+/// 遍历所有目录，修改时间没有变化的目录直接使用缓存
+///
+/// 返回所有的文件以及新的缓存
+fn walk(settings: &FileSearchSettings, old: &FileIndexCache) -> (Vec<FileEntry>, FileIndexCache) {
+    let mut entries = Vec::new();
+    let mut cache = FileIndexCache {
+        version: CACHE_VERSION,
+        dirs: HashMap::new(),
+    };
+    let mut reused = 0;
+
+    for root in settings.directories.iter().map(|dir| expand_home(dir)) {
+        if !root.is_dir() {
+            warn!("文件搜索目录 {} 不存在", root.display());
+            continue;
+        }
+
+        // (目录, 深度)
+        let mut stack = vec![(root.clone(), 1)];
+        while let Some((dir, depth)) = stack.pop() {
+            let Some(modified) = modified_time(&dir) else {
+                continue;
+            };
+
+            let children = match old.dirs.get(&dir) {
+                Some(cached) if cached.modified == modified => {
+                    reused += 1;
+                    cached.children.clone()
+                }
+                _ => match std::fs::read_dir(&dir) {
+                    Ok(read_dir) => read_dir
+                        .flatten()
+                        .map(|entry| (
+                            entry.file_name().to_string_lossy().to_string(),
+                            entry.file_type().is_ok_and(|kind| kind.is_dir()),
+                        ))
+                        .collect(),
+                    Err(e) => {
+                        debug!("无法读取目录 {}: {}", dir.display(), e);
+                        continue;
+                    }
+                },
+            };
+
+            for (name, is_dir) in children.iter() {
+                let path = dir.join(name);
+                let relative = path.strip_prefix(&root).unwrap_or(&path);
+                if is_ignored(&settings.ignore_patterns, name, relative) {
+                    continue;
+                }
+
+                if *is_dir && depth < settings.max_depth {
+                    stack.push((path.clone(), depth + 1));
+                }
+                entries.push(FileEntry {
+                    path: path,
+                    name: name.clone(),
+                    is_dir: *is_dir,
+                });
+            }
+
+            cache.dirs.insert(dir, CachedDir {
+                modified: modified,
+                children: children,
+            });
+        }
+    }
+
+    debug!("文件索引: 共 {} 个目录，其中 {} 个未变化", cache.dirs.len(), reused);
+    (entries, cache)
+}
+
+
+/// 在系统默认程序中打开
+pub fn open_path(path: &Path) {
+    #[cfg(target_os = "windows")]
+    let result = std::process::Command::new("explorer").arg(path).spawn();
+    #[cfg(target_os = "macos")]
+    let result = std::process::Command::new("open").arg(path).spawn();
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let result = std::process::Command::new("xdg-open").arg(path).spawn();
+
+    match result {
+        Ok(_) => debug!("打开 {}", path.display()),
+        Err(e) => warn!("无法打开 {}: {}", path.display(), e),
+    }
+}
+
+/// 在文件管理器中显示并选中
+pub fn reveal_path(path: &Path) {
+    #[cfg(target_os = "windows")]
+    let result = std::process::Command::new("explorer")
+        .arg(format!("/select,{}", path.display()))
+        .spawn();
+    #[cfg(target_os = "macos")]
+    let result = std::process::Command::new("open").arg("-R").arg(path).spawn();
+    // xdg-open 无法选中文件，只能打开所在的文件夹
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let result = std::process::Command::new("xdg-open")
+        .arg(path.parent().unwrap_or(path))
+        .spawn();
+
+    match result {
+        Ok(_) => debug!("在文件管理器中显示 {}", path.display()),
+        Err(e) => warn!("无法在文件管理器中显示 {}: {}", path.display(), e),
+    }
+}
+
+
+/// 已经建立的索引
+struct FileIndex {
+    settings: Option<FileSearchSettings>,
+    entries: Vec<FileEntry>,
+    // 用于搜索的快捷方式，与 entries 一一对应
+    links: Vec<ProgramLink>,
+    // 快捷方式的uuid在 entries 中的位置
+    positions: HashMap<String, usize>,
+    indexed_at: Option<Instant>,
+}
+
+/// 指定目录中的文件与文件夹
+pub struct FileProvider {
+    index: Arc<RwLock<FileIndex>>,
+    search_index: Mutex<SearchIndex>,
+    indexing: Arc<AtomicBool>,
+}
+
+impl FileProvider {
+    pub fn new(settings: &FileSearchSettings) -> Self {
+        let provider = Self {
+            index: Arc::new(RwLock::new(FileIndex {
+                settings: None,
+                entries: Vec::new(),
+                links: Vec::new(),
+                positions: HashMap::new(),
+                indexed_at: None,
+            })),
+            search_index: Mutex::new(SearchIndex::new()),
+            indexing: Arc::new(AtomicBool::new(false)),
+        };
+        provider.refresh(settings.clone());
+        provider
+    }
+
+    /// 在后台增量刷新索引
+    fn refresh(&self, settings: FileSearchSettings) {
+        if settings.directories.is_empty() {
+            let mut index = self.index.write().unwrap();
+            index.entries.clear();
+            index.links.clear();
+            index.positions.clear();
+            index.settings = Some(settings);
+            index.indexed_at = Some(Instant::now());
+            return;
+        }
+
+        if self.indexing.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let index = self.index.clone();
+        let indexing = self.indexing.clone();
+        std::thread::spawn(move || {
+            let started = Instant::now();
+            let old = load_cache();
+            let (entries, cache) = walk(&settings, &old);
+            info!("文件索引完成: {} 项，用时 {:?}", entries.len(), started.elapsed());
+
+            if let Err(e) = save_cache(&cache) {
+                warn!("保存文件索引缓存失败: {}", e);
+            }
+
+            let mut index = index.write().unwrap();
+            index.links = entries
+                .iter()
+                .map(|entry| ProgramLink {
+                    name: entry.name.clone(),
+                    run_command: entry.path.display().to_string(),
+                    uuid: format!("file:{}", entry.path.display()),
+                    ..Default::default()
+                })
+                .collect();
+            index.positions = index.links
+                .iter()
+                .enumerate()
+                .map(|(position, link)| (link.uuid.clone(), position))
+                .collect();
+            index.entries = entries;
+            index.settings = Some(settings);
+            index.indexed_at = Some(Instant::now());
+            indexing.store(false, Ordering::SeqCst);
+        });
+    }
+}
+
+impl SearchProvider for FileProvider {
+    fn name(&self) -> &'static str {
+        "文件"
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_millis(500)
+    }
+
+    fn search(&self, query: &ProviderQuery) -> Vec<SearchItem> {
+        // 只有过滤条件时不列出所有的文件
+        if query.query.text.trim().is_empty() {
+            return Vec::new();
+        }
+
+        let settings = &query.settings.files;
+        let outdated = {
+            let index = self.index.read().unwrap();
+            index.settings.as_ref() != Some(settings)
+                || index.indexed_at.is_none_or(|indexed_at| indexed_at.elapsed() > REFRESH_INTERVAL)
+        };
+        // 刷新期间继续使用旧的索引
+        if outdated {
+            self.refresh(settings.clone());
+        }
+
+        let index = self.index.read().unwrap();
+        let mut search_index = self.search_index.lock().unwrap();
+
+        search::search(&mut search_index, &index.links, &query.query, &query.settings)
+            .into_iter()
+            .take(MAX_RESULTS)
+            .filter_map(|result| {
+                let entry = &index.entries[*index.positions.get(&result.program_link.uuid)?];
+
+                Some(SearchItem {
+                    label: entry.name.clone(),
+                    detail: format!("{}\n回车打开，Ctrl+回车在文件管理器中显示", entry.path.display()),
+                    icon: ItemIcon::Glyph(if entry.is_dir { "📁" } else { "📄" }),
+                    score: result.score * FILE_SCORE_FACTOR,
+                    highlights: result.highlights,
+                    action: SearchAction::OpenPath(entry.path.clone()),
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,43 @@
+use std::sync::Mutex;
+
+use crate::my_structs::ProgramLink;
+use crate::search::{self, SearchIndex, SearchQuery, SearchResult, SearchSettings};
+use super::{ProviderQuery, SearchItem, SearchProvider};
+
+
+/// 用户创建的快捷方式
+#[derive(Debug)]
+pub struct LinkProvider {
+    index: Mutex<SearchIndex>,
+}
+
+impl LinkProvider {
+    pub fn new() -> Self {
+        Self {
+            index: Mutex::new(SearchIndex::new()),
+        }
+    }
+
+    pub fn search_links(
+        &self,
+        program_links: &[ProgramLink],
+        query: &SearchQuery,
+        settings: &SearchSettings,
+    ) -> Vec<SearchResult> {
+        let mut index = self.index.lock().unwrap();
+        search::search(&mut index, program_links, query, settings)
+    }
+}
+
+impl SearchProvider for LinkProvider {
+    fn name(&self) -> &'static str {
+        "快捷方式"
+    }
+
+    fn search(&self, query: &ProviderQuery) -> Vec<SearchItem> {
+        self.search_links(&query.program_links, &query.query, &query.settings)
+            .into_iter()
+            .map(SearchItem::from)
+            .collect()
+    }
+}
@@ -0,0 +1,343 @@
+//! 搜索提供者
+//!
+//! 每个提供者接收同一个查询，返回带有得分、标签、图标与动作的结果，
+//! 所有提供者的结果会合并为一个按得分排序的列表
+
+mod links;
+mod calculator;
+mod files;
+mod web;
+mod commands;
+mod shell;
+#[cfg(target_os = "linux")]
+mod desktop_apps;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use log::{error, warn};
+use tokio::sync::oneshot;
+use tokio::task::JoinSet;
+
+use crate::commands::AppCommand;
+use crate::my_structs::ProgramLink;
+use super::{SearchQuery, SearchResult, SearchSettings};
+pub use links::LinkProvider;
+pub use calculator::CalculatorProvider;
+pub use files::{FileProvider, FileSearchSettings, open_path, reveal_path};
+pub use web::{WebSearchProvider, WebSearchSettings, WebTemplate, is_url, open_url};
+pub use commands::{CommandProvider, COMMAND_PREFIX};
+pub use shell::{ShellCommand, ShellOutput, ShellProvider, ShellSettings, SHELL_PREFIX};
+#[cfg(target_os = "linux")]
+pub use desktop_apps::{DesktopApp, DesktopAppProvider};
+
+
+/// 提供者默认的超时时间
+pub const DEFAULT_PROVIDER_TIMEOUT: Duration = Duration::from_millis(200);
+
+
+/// 传递给提供者的查询
+#[derive(Debug, Clone)]
+pub struct ProviderQuery {
+    // 搜索框中的原始文本
+    pub raw: String,
+    // 解析后的查询，包含侧边栏选中的标签
+    pub query: SearchQuery,
+    pub settings: SearchSettings,
+    // 当前所有的快捷方式
+    pub program_links: Arc<Vec<ProgramLink>>,
+}
+
+
+/// 搜索结果的图标
+#[derive(Debug, Clone, PartialEq)]
+pub enum ItemIcon {
+    // 图片文件的路径
+    Image(String),
+    // 使用一个字符作为图标
+    Glyph(&'static str),
+}
+
+/// 选中搜索结果时执行的动作
+#[derive(Debug, Clone)]
+pub enum SearchAction {
+    RunLink(ProgramLink),
+    CopyText(String),
+    // 打开文件或文件夹
+    OpenPath(PathBuf),
+    // 在浏览器中打开网址
+    OpenUrl(String),
+    // 执行 BaroBoard 自身的命令
+    RunCommand(AppCommand),
+    // 切换到另一个工作区，None 表示默认工作区
+    SwitchProfile(Option<String>),
+    // 使用shell执行的临时命令
+    RunShell(ShellCommand),
+    // 启动已安装的应用
+    #[cfg(target_os = "linux")]
+    LaunchApp(DesktopApp),
+}
+
+/// 由提供者返回的一条搜索结果
+#[derive(Debug, Clone)]
+pub struct SearchItem {
+    pub label: String,
+    // 悬停时显示的说明
+    pub detail: String,
+    pub icon: ItemIcon,
+    pub score: f64,
+    // 标签中被匹配的字符下标
+    pub highlights: Vec<usize>,
+    pub action: SearchAction,
+}
+
+impl SearchItem {
+    /// 结果对应的快捷方式，只有快捷方式可以编辑、拖拽与删除
+    pub fn program_link(&self) -> Option<&ProgramLink> {
+        match &self.action {
+            SearchAction::RunLink(program_link) => Some(program_link),
+            _ => None,
+        }
+    }
+
+    /// 可以固定为快捷方式的结果，返回对应的新快捷方式
+    pub fn pin_link(&self) -> Option<ProgramLink> {
+        match &self.action {
+            #[cfg(target_os = "linux")]
+            SearchAction::LaunchApp(app) => Some(app.to_program_link()),
+            SearchAction::RunShell(shell) => shell.to_program_link(),
+            _ => None,
+        }
+    }
+}
+
+impl SearchAction {
+    /// 从搜索栏执行后是否隐藏窗口
+    pub fn hides_window(&self) -> bool {
+        match self {
+            SearchAction::RunCommand(command) => command.hides_window(),
+            SearchAction::SwitchProfile(_) => false,
+            // 需要显示输出时保留窗口
+            SearchAction::RunShell(shell) => !shell.capture_output,
+            _ => true,
+        }
+    }
+}
+
+impl From<SearchResult> for SearchItem {
+    fn from(result: SearchResult) -> Self {
+        let program_link = result.program_link;
+
+        Self {
+            label: program_link.name.clone(),
+            detail: format!("{} {}", program_link.run_command, program_link.arguments.join(" ")),
+            icon: ItemIcon::Image(program_link.icon_path.clone()),
+            score: result.score,
+            highlights: result.highlights,
+            action: SearchAction::RunLink(program_link),
+        }
+    }
+}
+
+
+/// 搜索结果的来源
+///
+/// 提供者会在tokio的阻塞线程池中运行，超过 [`SearchProvider::timeout`] 的结果会被丢弃
+pub trait SearchProvider: Send + Sync {
+    /// 提供者的名称，用于日志
+    fn name(&self) -> &'static str;
+
+    /// 单次搜索允许的最长时间
+    fn timeout(&self) -> Duration {
+        DEFAULT_PROVIDER_TIMEOUT
+    }
+
+    /// 独占的前缀，以此开头的查询只交给该提供者处理，其余提供者不会参与
+    fn prefix(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn search(&self, query: &ProviderQuery) -> Vec<SearchItem>;
+
+    /// 所有提供者都没有结果时的后备结果
+    fn fallback(&self, _query: &ProviderQuery) -> Vec<SearchItem> {
+        Vec::new()
+    }
+}
+
+
+/// 运行单个提供者，出错或超时时返回空列表
+async fn run_provider(provider: Arc<dyn SearchProvider>, query: Arc<ProviderQuery>) -> Vec<SearchItem> {
+    let name = provider.name();
+    let timeout = provider.timeout();
+    let task = tokio::task::spawn_blocking(move || provider.search(&query));
+
+    match tokio::time::timeout(timeout, task).await {
+        Ok(Ok(items)) => items,
+        Ok(Err(e)) => {
+            error!("搜索提供者 {} 运行失败: {}", name, e);
+            Vec::new()
+        }
+        Err(_) => {
+            warn!("搜索提供者 {} 超时 ({:?})，结果已丢弃", name, timeout);
+            Vec::new()
+        }
+    }
+}
+
+
+/// 管理所有提供者，在后台执行搜索并在UI线程中收取结果
+pub struct SearchEngine {
+    links: Arc<LinkProvider>,
+    providers: Vec<Arc<dyn SearchProvider>>,
+
+    // 上一次提交的查询，用于避免重复搜索
+    last_query: Option<(String, SearchQuery, SearchSettings, Arc<Vec<ProgramLink>>)>,
+    // 正在进行的搜索
+    pending: Option<oneshot::Receiver<Vec<SearchItem>>>,
+    // 已经合并排序的结果
+    results: Vec<SearchItem>,
+}
+
+impl std::fmt::Debug for SearchEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SearchEngine")
+            .field("providers", &self.providers.iter().map(|provider| provider.name()).collect::<Vec<_>>())
+            .field("pending", &self.pending.is_some())
+            .field("results", &self.results.len())
+            .finish()
+    }
+}
+
+impl SearchEngine {
+    pub fn new(settings: &SearchSettings) -> Self {
+        let links = Arc::new(LinkProvider::new());
+
+        // 排在前面的提供者在同分时优先
+        let mut providers: Vec<Arc<dyn SearchProvider>> = vec![
+            Arc::new(CommandProvider),
+            Arc::new(ShellProvider),
+            Arc::new(CalculatorProvider),
+            links.clone(),
+        ];
+        #[cfg(target_os = "linux")]
+        providers.push(Arc::new(DesktopAppProvider::new()));
+        providers.push(Arc::new(FileProvider::new(&settings.files)));
+        providers.push(Arc::new(WebSearchProvider));
+
+        Self {
+            links: links,
+            providers: providers,
+
+            last_query: None,
+            pending: None,
+            results: Vec::new(),
+        }
+    }
+
+    /// 快捷方式提供者，没有搜索文本时直接在UI线程中使用
+    pub fn links(&self) -> &LinkProvider {
+        &self.links
+    }
+
+    /// 提交一次搜索，查询与上一次相同时不会重复执行
+    pub fn submit(
+        &mut self,
+        ctx: &egui::Context,
+        raw: &str,
+        query: SearchQuery,
+        settings: &SearchSettings,
+        program_links: &[ProgramLink],
+    ) {
+        if let Some((last_raw, last_query, last_settings, last_links)) = &self.last_query {
+            if last_raw == raw
+                && *last_query == query
+                && last_settings == settings
+                && last_links.as_slice() == program_links
+            {
+                return;
+            }
+        }
+
+        let program_links = Arc::new(program_links.to_vec());
+        self.last_query = Some((raw.to_string(), query.clone(), settings.clone(), program_links.clone()));
+
+        let query = Arc::new(ProviderQuery {
+            raw: raw.to_string(),
+            query: query,
+            settings: settings.clone(),
+            program_links: program_links,
+        });
+        // 以某个提供者的前缀开头时，只交给该提供者处理
+        let exclusive = self.providers
+            .iter()
+            .find(|provider| provider.prefix().is_some_and(|prefix| raw.trim_start().starts_with(prefix)));
+        let providers: Vec<Arc<dyn SearchProvider>> = match exclusive {
+            Some(provider) => vec![provider.clone()],
+            None => self.providers.iter().filter(|provider| provider.prefix().is_none()).cloned().collect(),
+        };
+        let ctx = ctx.clone();
+
+        // 替换掉旧的接收端，过期的结果会被直接丢弃
+        let (sender, receiver) = oneshot::channel();
+        self.pending = Some(receiver);
+
+        tokio::spawn(async move {
+            let mut tasks = JoinSet::new();
+            for (order, provider) in providers.iter().cloned().enumerate() {
+                let query = query.clone();
+                tasks.spawn(async move { (order, run_provider(provider, query).await) });
+            }
+
+            let mut outputs = tasks.join_all().await;
+            // 按提供者的注册顺序合并，保证同分时的顺序稳定
+            outputs.sort_by_key(|(order, _)| *order);
+            let mut items: Vec<SearchItem> = outputs.into_iter().flat_map(|(_, items)| items).collect();
+            items.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+
+            // 没有任何结果时，使用后备结果
+            if items.is_empty() {
+                items = providers.iter().flat_map(|provider| provider.fallback(&query)).collect();
+            }
+
+            if sender.send(items).is_ok() {
+                ctx.request_repaint();
+            }
+        });
+    }
+
+    /// 收取后台搜索的结果，每帧调用一次
+    pub fn poll(&mut self) {
+        let Some(receiver) = self.pending.as_mut() else {
+            return;
+        };
+
+        match receiver.try_recv() {
+            Ok(items) => {
+                self.results = items;
+                self.pending = None;
+            }
+            Err(oneshot::error::TryRecvError::Empty) => {}
+            Err(oneshot::error::TryRecvError::Closed) => {
+                error!("搜索任务意外终止");
+                self.pending = None;
+            }
+        }
+    }
+
+    /// 是否还有未完成的搜索
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    pub fn results(&self) -> &[SearchItem] {
+        &self.results
+    }
+
+    /// 清空结果，下一次提交时一定会重新搜索
+    pub fn clear(&mut self) {
+        self.last_query = None;
+        self.pending = None;
+        self.results.clear();
+    }
+}
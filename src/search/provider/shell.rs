@@ -0,0 +1,403 @@
+//! 以 `$` 开头的临时命令
+//!
+//! 命令交给设置中的shell执行，可以在终端中运行，也可以捕获输出显示在结果面板中
+
+use std::collections::HashSet;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use serde::{Serialize, Deserialize};
+use log::{debug, warn};
+
+use crate::my_structs::ProgramLink;
+use super::{ItemIcon, ProviderQuery, SearchAction, SearchItem, SearchProvider};
+
+
+/// 命令模式的前缀
+pub const SHELL_PREFIX: &str = "$";
+/// 命令总是排在第一位
+const SHELL_SCORE: f64 = 10.;
+/// 含有这些字符的命令需要交给shell解释，不能直接拆分为程序与参数
+const SHELL_OPERATORS: &[char] = &['|', '&', ';', '<', '>', '$', '`', '*', '?', '(', ')'];
+/// 捕获输出时最长的运行时间，超时后终止命令
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(30);
+/// 检查命令是否结束的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// 命令结束后等待读取输出的时间，留在后台的子进程可能一直占用输出
+const OUTPUT_GRACE: Duration = Duration::from_secs(1);
+
+
+/// 命令模式的设置
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ShellSettings {
+    // 执行命令的shell及其参数，命令会作为最后一个参数传入
+    pub shell: String,
+    // 打开终端的命令，放在shell之前，为空时在后台运行
+    pub terminal: String,
+    // 捕获输出并显示在结果面板中，而不是打开终端
+    pub capture_output: bool,
+}
+
+impl Default for ShellSettings {
+    fn default() -> Self {
+        #[cfg(target_os = "windows")]
+        let (shell, terminal) = ("cmd /C", "cmd /C start");
+        #[cfg(not(target_os = "windows"))]
+        let (shell, terminal) = ("sh -c", "x-terminal-emulator -e");
+
+        Self {
+            shell: shell.to_string(),
+            terminal: terminal.to_string(),
+            capture_output: false,
+        }
+    }
+}
+
+impl ShellSettings {
+    /// 拆分后的shell，无法解析时使用默认值
+    fn shell_words(&self) -> Vec<String> {
+        shlex::split(&self.shell)
+            .filter(|words| !words.is_empty())
+            .or_else(|| shlex::split(&Self::default().shell))
+            .unwrap_or_default()
+    }
+
+    fn terminal_words(&self) -> Vec<String> {
+        shlex::split(&self.terminal).unwrap_or_default()
+    }
+}
+
+
+/// 一条待执行的命令，以及执行它所需的shell与终端
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShellCommand {
+    pub command: String,
+    shell: Vec<String>,
+    terminal: Vec<String>,
+    pub capture_output: bool,
+}
+
+/// 捕获的命令输出
+#[derive(Debug, Clone)]
+pub struct ShellOutput {
+    pub command: String,
+    // 退出码，被信号终止或无法启动时为 None
+    pub status: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl ShellCommand {
+    pub fn new(command: &str, settings: &ShellSettings) -> Self {
+        Self {
+            command: command.to_string(),
+            shell: settings.shell_words(),
+            terminal: settings.terminal_words(),
+            capture_output: settings.capture_output,
+        }
+    }
+
+    /// 完整的命令行，第一个元素为程序
+    fn argv(&self, in_terminal: bool) -> Vec<String> {
+        let mut argv = if in_terminal { self.terminal.clone() } else { Vec::new() };
+        argv.extend(self.shell.iter().cloned());
+        argv.push(self.command.clone());
+        argv
+    }
+
+    fn build(&self, in_terminal: bool) -> Option<Command> {
+        let argv = self.argv(in_terminal);
+        let (program, args) = argv.split_first()?;
+
+        let mut command = Command::new(program);
+        command.args(args);
+        Some(command)
+    }
+
+    /// 在终端中运行，不等待结束
+    pub fn spawn(&self) {
+        let Some(mut command) = self.build(true) else {
+            warn!("无法运行 {}: shell 为空", self.command);
+            return;
+        };
+
+        match command.spawn() {
+            Ok(_) => debug!("运行命令 {}", self.command),
+            Err(e) => warn!("无法运行命令 {}: {}", self.command, e),
+        }
+    }
+
+    /// 运行并等待结束，返回捕获的输出，会阻塞当前线程
+    ///
+    /// 超过 [`CAPTURE_TIMEOUT`] 或 `cancel` 被设置时终止命令
+    pub fn run_captured(&self, cancel: &AtomicBool) -> ShellOutput {
+        let child = self.build(false)
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "shell 为空"))
+            .and_then(|mut command| {
+                command
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped())
+                    .spawn()
+            });
+        let mut child = match child {
+            Ok(child) => child,
+            Err(e) => return ShellOutput {
+                command: self.command.clone(),
+                status: None,
+                stdout: String::new(),
+                stderr: format!("无法运行命令: {}", e),
+            },
+        };
+
+        // 在单独的线程中读取，输出过多时不会因为管道写满而卡住
+        let stdout = read_pipe(child.stdout.take());
+        let stderr = read_pipe(child.stderr.take());
+
+        let started = Instant::now();
+        let (status, message) = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break (status.code(), None),
+                Ok(None) => {}
+                Err(e) => break (None, Some(format!("无法等待命令结束: {}", e))),
+            }
+
+            let message = if cancel.load(Ordering::SeqCst) {
+                Some("命令已取消".to_string())
+            } else if started.elapsed() > CAPTURE_TIMEOUT {
+                Some(format!("命令运行超过 {} 秒，已终止", CAPTURE_TIMEOUT.as_secs()))
+            } else {
+                None
+            };
+            if let Some(message) = message {
+                debug!("终止命令 {}: {}", self.command, message);
+                if let Err(e) = child.kill() {
+                    warn!("无法终止命令 {}: {}", self.command, e);
+                }
+                let _ = child.wait();
+                break (None, Some(message));
+            }
+
+            std::thread::sleep(POLL_INTERVAL);
+        };
+
+        let mut stderr = stderr.recv_timeout(OUTPUT_GRACE).unwrap_or_default();
+        if let Some(message) = message {
+            if !stderr.is_empty() && !stderr.ends_with('\n') {
+                stderr.push('\n');
+            }
+            stderr.push_str(&message);
+        }
+
+        ShellOutput {
+            command: self.command.clone(),
+            status: status,
+            stdout: stdout.recv_timeout(OUTPUT_GRACE).unwrap_or_default(),
+            stderr: stderr,
+        }
+    }
+
+    /// 转换为快捷方式
+    ///
+    /// 普通的命令会拆分为程序与参数，含有管道、重定向等shell语法的命令会交给shell执行
+    pub fn to_program_link(&self) -> Option<ProgramLink> {
+        let mut words = if self.command.contains(SHELL_OPERATORS) {
+            let mut words = self.shell.clone();
+            words.push(self.command.clone());
+            words
+        } else {
+            shlex::split(&self.command)?
+        };
+        if words.is_empty() {
+            return None;
+        }
+        let run_command = words.remove(0);
+
+        Some(ProgramLink::new(
+            self.command.clone(),
+            "".to_string(),
+            run_command,
+            words,
+            HashSet::new(),
+            false,
+            true,
+        ))
+    }
+}
+
+
+/// 在后台读取管道中的全部内容
+fn read_pipe(pipe: Option<impl Read + Send + 'static>) -> mpsc::Receiver<String> {
+    let (sender, receiver) = mpsc::channel();
+    if let Some(mut pipe) = pipe {
+        std::thread::spawn(move || {
+            let mut buffer = Vec::new();
+            let _ = pipe.read_to_end(&mut buffer);
+            let _ = sender.send(String::from_utf8_lossy(&buffer).to_string());
+        });
+    }
+    receiver
+}
+
+
+/// 命令模式
+#[derive(Debug)]
+pub struct ShellProvider;
+
+impl SearchProvider for ShellProvider {
+    fn name(&self) -> &'static str {
+        "命令模式"
+    }
+
+    fn prefix(&self) -> Option<&'static str> {
+        Some(SHELL_PREFIX)
+    }
+
+    fn search(&self, query: &ProviderQuery) -> Vec<SearchItem> {
+        let Some(command) = query.raw.trim_start().strip_prefix(SHELL_PREFIX) else {
+            return Vec::new();
+        };
+        let command = command.trim();
+        if command.is_empty() {
+            return Vec::new();
+        }
+
+        let shell = ShellCommand::new(command, &query.settings.shell);
+        let detail = format!(
+            "{}\n回车{}",
+            shell.argv(!shell.capture_output).join(" "),
+            if shell.capture_output { "运行并显示输出" } else { "在终端中运行" },
+        );
+
+        vec![SearchItem {
+            label: format!("{} {}", SHELL_PREFIX, command),
+            detail: detail,
+            icon: ItemIcon::Glyph("🖳"),
+            score: SHELL_SCORE,
+            highlights: Vec::new(),
+            action: SearchAction::RunShell(shell),
+        }]
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(shell: &str, terminal: &str) -> ShellSettings {
+        ShellSettings {
+            shell: shell.to_string(),
+            terminal: terminal.to_string(),
+            capture_output: false,
+        }
+    }
+
+    fn strings(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    #[test]
+    fn splits_shell_and_terminal() {
+        let default_shell = ShellSettings::default().shell_words();
+        assert!(!default_shell.is_empty());
+
+        let cases: &[(&str, &[&str])] = &[
+            ("sh -c", &["sh", "-c"]),
+            ("bash  -lc", &["bash", "-lc"]),
+            ("'/opt/my shell/bin/sh' -c", &["/opt/my shell/bin/sh", "-c"]),
+        ];
+        for (shell, expected) in cases {
+            assert_eq!(settings(shell, "").shell_words(), strings(expected), "{:?}", shell);
+        }
+
+        // 为空或无法解析时使用默认的shell
+        assert_eq!(settings("", "").shell_words(), default_shell);
+        assert_eq!(settings("  ", "").shell_words(), default_shell);
+        assert_eq!(settings("sh \"-c", "").shell_words(), default_shell);
+
+        assert_eq!(settings("sh -c", "x-terminal-emulator -e").terminal_words(), strings(&["x-terminal-emulator", "-e"]));
+        assert!(settings("sh -c", "").terminal_words().is_empty());
+        assert!(settings("sh -c", "xterm \"-e").terminal_words().is_empty());
+    }
+
+    #[test]
+    fn builds_argv() {
+        let shell = ShellCommand::new("ls | wc -l", &settings("sh -c", "xterm -e"));
+        assert_eq!(shell.argv(false), strings(&["sh", "-c", "ls | wc -l"]));
+        assert_eq!(shell.argv(true), strings(&["xterm", "-e", "sh", "-c", "ls | wc -l"]));
+
+        // 没有终端时直接交给shell
+        let shell = ShellCommand::new("ls", &settings("sh -c", ""));
+        assert_eq!(shell.argv(true), strings(&["sh", "-c", "ls"]));
+    }
+
+    #[test]
+    fn converts_to_program_link() {
+        // (命令, 程序, 参数)
+        let cases: &[(&str, &str, &[&str])] = &[
+            ("htop", "htop", &[]),
+            ("git log --oneline", "git", &["log", "--oneline"]),
+            ("echo 'a b' \"c d\"", "echo", &["a b", "c d"]),
+            ("ls ~/Downloads", "ls", &["~/Downloads"]),
+            // 含有shell语法时整条命令交给shell
+            ("ls | wc -l", "sh", &["-c", "ls | wc -l"]),
+            ("make && make install", "sh", &["-c", "make && make install"]),
+            ("echo $HOME", "sh", &["-c", "echo $HOME"]),
+            ("ls *.rs", "sh", &["-c", "ls *.rs"]),
+            ("cat < in > out", "sh", &["-c", "cat < in > out"]),
+            ("echo `date`", "sh", &["-c", "echo `date`"]),
+            // 带引号的运算符同样交给shell，由shell处理引号
+            ("echo 'a;b'", "sh", &["-c", "echo 'a;b'"]),
+        ];
+
+        for (command, program, arguments) in cases {
+            let program_link = ShellCommand::new(command, &settings("sh -c", "xterm -e"))
+                .to_program_link()
+                .unwrap_or_else(|| panic!("{:?} 应当可以转换", command));
+            assert_eq!(program_link.name, *command);
+            assert_eq!(program_link.run_command, *program, "{:?}", command);
+            assert_eq!(program_link.arguments, strings(arguments), "{:?}", command);
+        }
+    }
+
+    #[test]
+    fn rejects_unparsable_commands() {
+        for command in ["", "   ", "echo 'unclosed", "echo \"unclosed"] {
+            let shell = ShellCommand::new(command, &settings("sh -c", ""));
+            assert!(shell.to_program_link().is_none(), "{:?}", command);
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn captures_output_and_status() {
+        let shell = ShellCommand::new("echo out; echo err >&2; exit 3", &settings("sh -c", ""));
+        let output = shell.run_captured(&AtomicBool::new(false));
+        assert_eq!(output.status, Some(3));
+        assert_eq!(output.stdout, "out\n");
+        assert_eq!(output.stderr, "err\n");
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn cancels_running_command() {
+        let shell = ShellCommand::new("sleep 30", &settings("sh -c", ""));
+        let started = Instant::now();
+        let output = shell.run_captured(&AtomicBool::new(true));
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert_eq!(output.status, None);
+        assert!(output.stderr.ends_with("命令已取消"), "{}", output.stderr);
+    }
+
+    #[test]
+    fn reports_spawn_failure() {
+        let shell = ShellCommand::new("ls", &settings("baroboard-no-such-shell -c", ""));
+        let output = shell.run_captured(&AtomicBool::new(false));
+        assert_eq!(output.status, None);
+        assert!(output.stderr.starts_with("无法运行命令"), "{}", output.stderr);
+    }
+}
@@ -0,0 +1,156 @@
+//! 关键字触发的网页搜索
+//!
+//! 输入 `关键字 搜索内容` 时，用模板中的 `{query}` 替换为编码后的搜索内容并在浏览器中打开。
+//! 没有任何结果时使用后备模板
+
+use serde::{Serialize, Deserialize};
+use log::{debug, warn};
+
+use super::{ItemIcon, ProviderQuery, SearchAction, SearchItem, SearchProvider};
+
+
+/// 模板中被替换为搜索内容的占位符
+pub const QUERY_PLACEHOLDER: &str = "{query}";
+/// 关键字命中时总是排在快捷方式之前
+const KEYWORD_SCORE: f64 = 10.;
+
+
+/// 一个网页搜索模板
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct WebTemplate {
+    // 触发的关键字，不区分大小写
+    pub keyword: String,
+    // 显示的名称
+    pub name: String,
+    // 包含 {query} 的网址
+    pub url: String,
+}
+
+impl WebTemplate {
+    pub fn new(keyword: &str, name: &str, url: &str) -> Self {
+        Self {
+            keyword: keyword.to_string(),
+            name: name.to_string(),
+            url: url.to_string(),
+        }
+    }
+}
+
+
+/// 网页搜索的设置
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct WebSearchSettings {
+    pub templates: Vec<WebTemplate>,
+    // 没有任何结果时使用的模板，为空时不启用
+    pub fallback: String,
+}
+
+impl Default for WebSearchSettings {
+    fn default() -> Self {
+        Self {
+            templates: vec![
+                WebTemplate::new("g", "Google", "https://www.google.com/search?q={query}"),
+                WebTemplate::new("gh", "GitHub", "https://github.com/search?q={query}"),
+                WebTemplate::new("docs", "docs.rs", "https://docs.rs/releases/search?query={query}"),
+            ],
+            fallback: "https://www.google.com/search?q={query}".to_string(),
+        }
+    }
+}
+
+
+/// 按照 RFC 3986 对搜索内容进行百分号编码，只保留非保留字符
+pub fn encode_query(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    encoded
+}
+
+/// 将模板中的占位符替换为编码后的搜索内容
+pub fn fill_template(template: &str, text: &str) -> String {
+    template.replace(QUERY_PLACEHOLDER, &encode_query(text))
+}
+
+/// 可以交给浏览器打开的网址
+pub fn is_url(text: &str) -> bool {
+    ["http://", "https://", "ftp://", "file://"].iter().any(|scheme| {
+        text.get(..scheme.len()).is_some_and(|prefix| prefix.eq_ignore_ascii_case(scheme))
+    })
+}
+
+/// 在默认浏览器中打开网址
+pub fn open_url(url: &str) {
+    // 不经过 cmd，网址中的 & 等字符会被 cmd 当作命令分隔符
+    #[cfg(target_os = "windows")]
+    let result = std::process::Command::new("rundll32").args(["url.dll,FileProtocolHandler", url]).spawn();
+    #[cfg(target_os = "macos")]
+    let result = std::process::Command::new("open").arg(url).spawn();
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let result = std::process::Command::new("xdg-open").arg(url).spawn();
+
+    match result {
+        Ok(_) => debug!("打开网址 {}", url),
+        Err(e) => warn!("无法打开网址 {}: {}", url, e),
+    }
+}
+
+fn web_item(name: &str, text: &str, url: String, score: f64) -> SearchItem {
+    SearchItem {
+        label: format!("{}: {}", name, text),
+        detail: format!("{}\n回车在浏览器中打开", url),
+        icon: ItemIcon::Glyph("🌐"),
+        score: score,
+        highlights: Vec::new(),
+        action: SearchAction::OpenUrl(url),
+    }
+}
+
+
+/// 网页搜索模板
+#[derive(Debug)]
+pub struct WebSearchProvider;
+
+impl SearchProvider for WebSearchProvider {
+    fn name(&self) -> &'static str {
+        "网页搜索"
+    }
+
+    fn search(&self, query: &ProviderQuery) -> Vec<SearchItem> {
+        let raw = query.raw.trim_start();
+        let Some((keyword, text)) = raw.split_once(char::is_whitespace) else {
+            return Vec::new();
+        };
+        let text = text.trim();
+        if text.is_empty() {
+            return Vec::new();
+        }
+
+        query.settings.web.templates
+            .iter()
+            .filter(|template| !template.keyword.is_empty() && template.keyword.eq_ignore_ascii_case(keyword))
+            .map(|template| {
+                let name = if template.name.is_empty() { &template.keyword } else { &template.name };
+                web_item(name, text, fill_template(&template.url, text), KEYWORD_SCORE)
+            })
+            .collect()
+    }
+
+    fn fallback(&self, query: &ProviderQuery) -> Vec<SearchItem> {
+        let fallback = &query.settings.web.fallback;
+        let text = query.raw.trim();
+        if fallback.trim().is_empty() || text.is_empty() {
+            return Vec::new();
+        }
+
+        vec![web_item("网页搜索", text, fill_template(fallback, text), 0.)]
+    }
+}
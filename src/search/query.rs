@@ -0,0 +1,288 @@
+use crate::my_structs::ProgramLink;
+use super::normalize::fold;
+
+
+/// 查询语句中可以限定的字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryField {
+    // 名称（包括别名），用于带引号的短语与 -排除
+    Name,
+    // tag:
+    Tag,
+    // cmd:
+    Cmd,
+    // arg:
+    Arg,
+}
+
+impl QueryField {
+    fn from_prefix(prefix: &str) -> Option<Self> {
+        match prefix.to_lowercase().as_str() {
+            "tag" => Some(Self::Tag),
+            "cmd" => Some(Self::Cmd),
+            "arg" => Some(Self::Arg),
+            _ => None,
+        }
+    }
+}
+
+
+/// 一个字段过滤条件，例如 `tag:dev` 或 `-cmd:python`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryFilter {
+    pub field: QueryField,
+    pub value: String,
+    pub negated: bool,
+}
+
+impl QueryFilter {
+    pub fn new(field: QueryField, value: String, negated: bool) -> Self {
+        Self {
+            field: field,
+            value: value,
+            negated: negated,
+        }
+    }
+
+    /// 侧边栏选中的标签
+    pub fn tag(tag: String) -> Self {
+        Self::new(QueryField::Tag, tag, false)
+    }
+
+    pub fn matches(&self, program_link: &ProgramLink, fold_chinese: bool) -> bool {
+        let value = fold(&self.value, fold_chinese);
+        let contains = |field: &str| fold(field, fold_chinese).contains(&value);
+
+        let is_match = match self.field {
+            QueryField::Name => contains(&program_link.name) || program_link.keywords
+                .iter()
+                .any(|keyword| contains(keyword)),
+            // 标签需要完全一致，否则 tag:dev 会同时选中 devops
+            QueryField::Tag => program_link.tags
+                .iter()
+                .any(|tag| fold(tag, fold_chinese) == value),
+            QueryField::Cmd => contains(&program_link.run_command),
+            QueryField::Arg => program_link.arguments
+                .iter()
+                .any(|argument| contains(argument)),
+        };
+
+        is_match != self.negated
+    }
+}
+
+
+/// 解析后的搜索语句
+///
+/// 没有字段前缀的词会拼接为 `text`，用于和名称进行模糊匹配；
+/// 其余的部分都会成为必须满足的过滤条件
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    pub text: String,
+    pub filters: Vec<QueryFilter>,
+}
+
+impl SearchQuery {
+    pub fn parse(input: &str) -> Self {
+        let mut query = Self::default();
+        let mut words: Vec<String> = Vec::new();
+
+        for token in tokenize(input) {
+            // 单独的 - 或者空引号没有意义
+            if token.value.is_empty() {
+                continue;
+            }
+
+            match (token.field, token.negated, token.quoted) {
+                (Some(field), negated, _) => {
+                    query.filters.push(QueryFilter::new(field, token.value, negated));
+                }
+                // -word 与 "some phrase" 都是对名称的精确包含判断
+                (None, true, _) | (None, false, true) => {
+                    query.filters.push(QueryFilter::new(QueryField::Name, token.value, token.negated));
+                }
+                (None, false, false) => words.push(token.value),
+            }
+        }
+
+        query.text = words.join(" ");
+        query
+    }
+
+    pub fn with_filter(mut self, filter: QueryFilter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    pub fn matches_filters(&self, program_link: &ProgramLink, fold_chinese: bool) -> bool {
+        self.filters.iter().all(|filter| filter.matches(program_link, fold_chinese))
+    }
+}
+
+
+struct Token {
+    field: Option<QueryField>,
+    value: String,
+    negated: bool,
+    quoted: bool,
+}
+
+/// 按空白分词，引号内的空白会被保留
+///
+/// 支持 `-` 取反、`field:` 前缀以及 `field:"带空格的值"`，未闭合的引号会一直读到结尾
+fn tokenize(input: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    loop {
+        // 跳过空白
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut negated = false;
+        if chars.peek() == Some(&'-') {
+            negated = true;
+            chars.next();
+        }
+
+        let mut raw = String::new();
+        let mut quoted = false;
+        let mut field = None;
+
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() {
+                break;
+            }
+            chars.next();
+
+            if c == '"' {
+                quoted = true;
+                // 读取到下一个引号为止
+                while let Some(c) = chars.next() {
+                    if c == '"' {
+                        break;
+                    }
+                    raw.push(c);
+                }
+            } else if c == ':' && field.is_none() && !quoted {
+                // 只有已知的字段才会被当作前缀，否则冒号保留在文本中（例如 http://）
+                match QueryField::from_prefix(&raw) {
+                    Some(known) => {
+                        field = Some(known);
+                        raw.clear();
+                    }
+                    None => raw.push(c),
+                }
+            } else {
+                raw.push(c);
+            }
+        }
+
+        tokens.push(Token {
+            field: field,
+            value: raw,
+            negated: negated,
+            quoted: quoted,
+        });
+    }
+
+    tokens
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(field: QueryField, value: &str, negated: bool) -> QueryFilter {
+        QueryFilter::new(field, value.to_string(), negated)
+    }
+
+    #[test]
+    fn parses_queries() {
+        use QueryField::*;
+
+        // (输入, 模糊匹配的文本, 过滤条件)
+        let cases: Vec<(&str, &str, Vec<QueryFilter>)> = vec![
+            ("", "", vec![]),
+            ("firefox", "firefox", vec![]),
+            ("  visual   code  ", "visual code", vec![]),
+
+            // 字段前缀
+            ("tag:dev", "", vec![filter(Tag, "dev", false)]),
+            ("TAG:Dev", "", vec![filter(Tag, "Dev", false)]),
+            ("cmd:python arg:--version", "", vec![filter(Cmd, "python", false), filter(Arg, "--version", false)]),
+            ("code tag:dev editor", "code editor", vec![filter(Tag, "dev", false)]),
+            ("tag:a:b", "", vec![filter(Tag, "a:b", false)]),
+            // 未知的前缀保留在文本中
+            ("http://example.com", "http://example.com", vec![]),
+            ("foo:bar", "foo:bar", vec![]),
+
+            // - 取反
+            ("-tag:old code", "code", vec![filter(Tag, "old", true)]),
+            ("-cmd:python", "", vec![filter(Cmd, "python", true)]),
+            ("-chrome", "", vec![filter(Name, "chrome", true)]),
+            ("a-b", "a-b", vec![]),
+
+            // 带引号的短语
+            ("\"visual studio\" code", "code", vec![filter(Name, "visual studio", false)]),
+            ("-\"visual studio\"", "", vec![filter(Name, "visual studio", true)]),
+            ("tag:\"my tools\"", "", vec![filter(Tag, "my tools", false)]),
+            ("arg:\"--profile work\"", "", vec![filter(Arg, "--profile work", false)]),
+
+            // 未闭合的引号一直读到结尾
+            ("\"visual studio", "", vec![filter(Name, "visual studio", false)]),
+            ("code tag:\"my tools -x", "code", vec![filter(Tag, "my tools -x", false)]),
+
+            // 单独的 - 与空的值会被忽略
+            ("-", "", vec![]),
+            ("- fire", "fire", vec![]),
+            ("fire -", "fire", vec![]),
+            ("tag: -cmd:", "", vec![]),
+            ("\"\" code", "code", vec![]),
+            ("\"", "", vec![]),
+        ];
+
+        for (input, text, filters) in cases {
+            let query = SearchQuery::parse(input);
+            assert_eq!(query.text, text, "{:?}", input);
+            assert_eq!(query.filters, filters, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn matches_filters() {
+        let program_link = ProgramLink {
+            name: "Visual Studio Code".to_string(),
+            keywords: vec!["vscode".to_string()],
+            tags: ["Dev".to_string()].into_iter().collect(),
+            run_command: "/usr/bin/code".to_string(),
+            arguments: vec!["--new-window".to_string()],
+            ..Default::default()
+        };
+
+        let cases = [
+            ("tag:dev", true),
+            // 标签需要完全一致
+            ("tag:de", false),
+            ("-tag:dev", false),
+            ("-tag:old", true),
+            ("cmd:bin/code", true),
+            ("-cmd:python", true),
+            ("arg:new", true),
+            ("arg:--profile", false),
+            ("\"studio code\"", true),
+            ("\"vscode\"", true),
+            ("-\"visual\"", false),
+            ("tag:dev cmd:python", false),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(SearchQuery::parse(input).matches_filters(&program_link, false), expected, "{:?}", input);
+        }
+    }
+}
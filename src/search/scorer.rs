@@ -0,0 +1,201 @@
+use serde::{Serialize, Deserialize};
+use strsim::{jaro_winkler, normalized_damerau_levenshtein};
+
+
+/// 可选的匹配算法
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Scorer {
+    #[default]
+    JaroWinkler,
+    DamerauLevenshtein,
+    // 类似 fzf 的子序列匹配
+    Subsequence,
+    // 在 Jaro-Winkler 的基础上奖励前缀匹配
+    PrefixBoosted,
+}
+
+impl Scorer {
+    pub const ALL: [Scorer; 4] = [
+        Scorer::JaroWinkler,
+        Scorer::DamerauLevenshtein,
+        Scorer::Subsequence,
+        Scorer::PrefixBoosted,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Scorer::JaroWinkler => "Jaro-Winkler",
+            Scorer::DamerauLevenshtein => "Damerau-Levenshtein",
+            Scorer::Subsequence => "子序列 (fzf)",
+            Scorer::PrefixBoosted => "前缀优先",
+        }
+    }
+
+    /// 计算 `text` 与 `target` 的相似度，结果在 0 到 1 之间
+    pub fn score(&self, text: &str, target: &str) -> f64 {
+        match self {
+            Scorer::JaroWinkler => jaro_winkler(text, target),
+            Scorer::DamerauLevenshtein => normalized_damerau_levenshtein(text, target),
+            Scorer::Subsequence => subsequence_score(text, target),
+            Scorer::PrefixBoosted => prefix_boosted_score(text, target),
+        }
+    }
+}
+
+
+/// 是否处在单词的开头，例如空格、符号之后或者驼峰的大写字母
+fn is_word_start(chars: &[char], index: usize) -> bool {
+    if index == 0 {
+        return true;
+    }
+    let previous = chars[index - 1];
+    let current = chars[index];
+
+    !previous.is_alphanumeric() || (previous.is_lowercase() && current.is_uppercase())
+}
+
+/// 子序列匹配：所有字符必须按顺序出现，连续命中与单词开头的命中得分更高
+fn subsequence_score(text: &str, target: &str) -> f64 {
+    let text: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let target: Vec<char> = target.chars().collect();
+
+    if text.is_empty() || target.is_empty() {
+        return 0.;
+    }
+
+    let same = |a: char, b: char| a.to_lowercase().eq(b.to_lowercase());
+
+    // best[j] 表示当前字符命中 target[j] 时，前面所有字符能得到的最高分
+    // 每个字符的基础分为1，连续命中或者命中单词开头再加1分
+    let mut best: Vec<Option<f64>> = target
+        .iter()
+        .enumerate()
+        .map(|(j, c)| same(text[0], *c).then(|| 1. + if is_word_start(&target, j) { 1. } else { 0. }))
+        .collect();
+
+    for t in text.iter().skip(1) {
+        let mut next = vec![None; target.len()];
+        for j in 0..target.len() {
+            if !same(*t, target[j]) {
+                continue;
+            }
+            next[j] = (0..j)
+                .filter_map(|k| best[k].map(|score| {
+                    let bonus = if k + 1 == j || is_word_start(&target, j) { 1. } else { 0. };
+                    score + 1. + bonus
+                }))
+                .reduce(f64::max);
+        }
+        best = next;
+    }
+
+    let Some(total) = best.into_iter().flatten().reduce(f64::max) else {
+        return 0.;
+    };
+
+    // 每个字符最多可以得到2分
+    let normalized = total / (2. * text.len() as f64);
+    // 目标越短、覆盖率越高，得分越高
+    let coverage = text.len() as f64 / target.len().max(text.len()) as f64;
+
+    normalized * (0.8 + 0.2 * coverage)
+}
+
+/// 前缀优先：整个名称以搜索文本开头时得满分，某个单词以其开头时至少得到0.9分
+fn prefix_boosted_score(text: &str, target: &str) -> f64 {
+    let base = jaro_winkler(text, target);
+    let text = text.to_lowercase();
+    let target = target.to_lowercase();
+
+    if text.is_empty() {
+        return base;
+    }
+
+    if target.starts_with(&text) {
+        1.
+    } else if target
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| word.starts_with(&text))
+    {
+        base.max(0.9)
+    } else {
+        base
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subsequence_requires_all_chars_in_order() {
+        let cases = [
+            ("", "Firefox"),
+            ("fire", ""),
+            ("xyz", "Firefox"),
+            ("xof", "Firefox"),
+            ("firefoxx", "Firefox"),
+        ];
+        for (text, target) in cases {
+            assert_eq!(subsequence_score(text, target), 0., "{:?} in {:?}", text, target);
+        }
+    }
+
+    #[test]
+    fn subsequence_prefers_exact_contiguous_and_word_starts() {
+        // 完全相同时得满分，忽略大小写与空白
+        assert_eq!(subsequence_score("firefox", "Firefox"), 1.);
+        assert_eq!(subsequence_score("FIRE FOX", "firefox"), 1.);
+
+        // (得分更高的一组, 得分更低的一组)
+        let cases = [
+            // 前缀优于中间
+            (("fire", "Firefox"), ("fox", "Firefox")),
+            // 连续命中优于分散命中
+            (("code", "Code Editor"), ("code", "Cool Desktop Editor")),
+            // 单词开头与驼峰优于单词中间
+            (("gc", "GitClient"), ("gc", "bigcat")),
+            (("vsc", "Visual Studio Code"), ("vsc", "avsclient")),
+            // 名称越短得分越高
+            (("code", "Code"), ("code", "Code Editor")),
+        ];
+        for ((text, target), (worse_text, worse_target)) in cases {
+            let better = subsequence_score(text, target);
+            let worse = subsequence_score(worse_text, worse_target);
+            assert!(better > worse, "{:?} in {:?} ({}) 应高于 {:?} in {:?} ({})", text, target, better, worse_text, worse_target, worse);
+        }
+    }
+
+    #[test]
+    fn detects_word_starts() {
+        let chars: Vec<char> = "my-appName x".chars().collect();
+        let starts: Vec<usize> = (0..chars.len()).filter(|index| is_word_start(&chars, *index)).collect();
+        // 开头、符号与空格之后、驼峰的大写字母
+        assert_eq!(starts, vec![0, 3, 6, 11]);
+    }
+
+    #[test]
+    fn prefix_boosted_rewards_prefixes() {
+        assert_eq!(prefix_boosted_score("fire", "Firefox"), 1.);
+        assert!(prefix_boosted_score("code", "Visual Studio Code") >= 0.9);
+        assert_eq!(prefix_boosted_score("xyz", "Firefox"), jaro_winkler("xyz", "Firefox"));
+    }
+
+    #[test]
+    fn scores_stay_in_range() {
+        let pairs = [
+            ("fire", "Firefox"),
+            ("vsc", "Visual Studio Code"),
+            ("liulanqi", "liulanqi"),
+            ("x", "a very long name without the letter"),
+            ("abc", "abc"),
+        ];
+        for scorer in Scorer::ALL {
+            for (text, target) in pairs {
+                let score = scorer.score(text, target);
+                assert!((0. ..=1.).contains(&score), "{:?}: {:?} in {:?} = {}", scorer, text, target, score);
+            }
+        }
+    }
+}
@@ -0,0 +1,386 @@
+//! 应用程序设置
+//!
+//! 设置与快捷方式配置分开保存在 `settings.json` 中，文件带有版本号，
+//! 读取时会从文件中记录的版本依次迁移到 [`crate::SETTINGS_FILE_VERSION`]。
+//! 新增的设置都有默认值，不需要迁移；只有改动已有设置的含义或格式时才增加版本。各版本的变化：
+//!
+//! - v1: 最初的版本，没有版本号的设置同样视为 v1
+//!
+//! 无法读取的设置文件会被改名保留，避免下次保存时被默认设置覆盖
+
+use serde::{Serialize, Deserialize};
+use serde_json::{Value, json};
+use std::{fs::File, io::Read};
+use std::path::{Path, PathBuf};
+use chrono::Local;
+use log::{debug, error, info, warn};
+
+use crate::my_structs::MyApp;
+use crate::pages::popups::link::trash::TrashSettings;
+use crate::search::SearchSettings;
+use crate::utils::file_utils::write_atomic;
+
+
+/// 应用程序设置，与快捷方式配置分开保存
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct AppSettings {
+    pub version: u32,
+    pub hotkey: HotkeySettings,
+    pub window: WindowSettings,
+    pub launch: LaunchSettings,
+    pub search: SearchSettings,
+    // 共享目录，可以是配置文件或包含配置文件的文件夹，按顺序叠加在快捷方式下方
+    pub catalogs: Vec<String>,
+    pub trash: TrashSettings,
+    // 当前的工作区，None 表示默认工作区
+    pub profile: Option<String>,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            version: crate::SETTINGS_FILE_VERSION,
+            hotkey: HotkeySettings::default(),
+            window: WindowSettings::default(),
+            launch: LaunchSettings::default(),
+            search: SearchSettings::default(),
+            catalogs: Vec::new(),
+            trash: TrashSettings::default(),
+            profile: None,
+        }
+    }
+}
+
+
+/// 双击呼出使用的按键
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyKey {
+    Alt,
+    Control,
+    Shift,
+}
+
+impl HotkeyKey {
+    pub const ALL: [HotkeyKey; 3] = [
+        HotkeyKey::Alt,
+        HotkeyKey::Control,
+        HotkeyKey::Shift,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HotkeyKey::Alt => "Alt",
+            HotkeyKey::Control => "Ctrl",
+            HotkeyKey::Shift => "Shift",
+        }
+    }
+
+    /// 左右两侧的按键都可以触发，Alt 只监听左侧
+    pub fn matches(&self, key: rdev::Key) -> bool {
+        match self {
+            HotkeyKey::Alt => key == rdev::Key::Alt,
+            HotkeyKey::Control => matches!(key, rdev::Key::ControlLeft | rdev::Key::ControlRight),
+            HotkeyKey::Shift => matches!(key, rdev::Key::ShiftLeft | rdev::Key::ShiftRight),
+        }
+    }
+}
+
+/// 呼出快捷键的设置
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct HotkeySettings {
+    // 是否允许双击呼出，托盘菜单中的开关也会保存到这里
+    pub double_tap: bool,
+    pub key: HotkeyKey,
+    // 两次按键之间的最长间隔，单位为毫秒
+    pub interval: u64,
+}
+
+impl Default for HotkeySettings {
+    fn default() -> Self {
+        Self {
+            double_tap: true,
+            key: HotkeyKey::Alt,
+            interval: crate::DOUBLE_ALT_COOLDOWN,
+        }
+    }
+}
+
+
+/// 界面主题
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+    System,
+    Light,
+    Dark,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [
+        Theme::System,
+        Theme::Light,
+        Theme::Dark,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Theme::System => "跟随系统",
+            Theme::Light => "浅色",
+            Theme::Dark => "深色",
+        }
+    }
+
+    pub fn preference(&self) -> egui::ThemePreference {
+        match self {
+            Theme::System => egui::ThemePreference::System,
+            Theme::Light => egui::ThemePreference::Light,
+            Theme::Dark => egui::ThemePreference::Dark,
+        }
+    }
+}
+
+/// 界面语言，目前只有简体中文
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    #[serde(rename = "zh-CN")]
+    SimplifiedChinese,
+}
+
+impl Language {
+    pub const ALL: [Language; 1] = [
+        Language::SimplifiedChinese,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Language::SimplifiedChinese => "简体中文",
+        }
+    }
+}
+
+/// 窗口与外观的设置
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct WindowSettings {
+    pub width: f32,
+    pub height: f32,
+    // 每行显示的快捷方式数量
+    pub columns: usize,
+    pub theme: Theme,
+    pub language: Language,
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self {
+            width: crate::WINDOW_SIZE.0,
+            height: crate::WINDOW_SIZE.1,
+            columns: 6,
+            theme: Theme::System,
+            language: Language::SimplifiedChinese,
+        }
+    }
+}
+
+impl WindowSettings {
+    pub fn size(&self) -> egui::Vec2 {
+        egui::vec2(self.width, self.height)
+    }
+
+    /// 将主题与窗口大小应用到界面上
+    pub fn apply(&self, ctx: &egui::Context) {
+        ctx.set_theme(self.theme.preference());
+        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(self.size()));
+    }
+}
+
+
+/// 启动与运行快捷方式的设置
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct LaunchSettings {
+    // 程序启动后立即显示窗口，否则只显示托盘图标
+    pub show_on_startup: bool,
+    // 点击快捷方式运行后隐藏窗口
+    pub hide_after_run: bool,
+    // 新建快捷方式时的默认选项
+    pub new_window: bool,
+    pub admin: bool,
+}
+
+impl Default for LaunchSettings {
+    fn default() -> Self {
+        Self {
+            show_on_startup: false,
+            hide_after_run: false,
+            new_window: true,
+            admin: false,
+        }
+    }
+}
+
+
+fn settings_path() -> PathBuf {
+    crate::paths::config_dir().join(crate::SETTINGS_FILE_NAME)
+}
+
+/// 设置文件中记录的版本，没有版本号的设置视为 v1
+fn settings_version(settings: &Value) -> u32 {
+    settings.get("version")
+        .and_then(|v| v.as_u64())
+        .map_or(1, |version| version.max(1) as u32)
+}
+
+/// `MIGRATIONS[n]` 将版本 n+1 升级到 n+2
+const MIGRATIONS: [fn(&mut Value); 0] = [];
+
+/// 将设置依次升级到当前版本，比当前版本更新的设置不会被修改
+fn migrate_settings(settings: &mut Value) {
+    let from = settings_version(settings);
+    if from > crate::SETTINGS_FILE_VERSION {
+        warn!("设置文件的版本 v{} 比程序支持的 v{} 更新，无法识别的设置将被忽略", from, crate::SETTINGS_FILE_VERSION);
+        return;
+    }
+
+    for version in from..crate::SETTINGS_FILE_VERSION {
+        MIGRATIONS[version as usize - 1](settings);
+        if let Some(settings) = settings.as_object_mut() {
+            settings.insert("version".to_string(), json!(version + 1));
+        }
+        info!("设置文件已从 v{} 迁移到 v{}", version, version + 1);
+    }
+}
+
+pub fn load_settings() -> Result<AppSettings, std::io::Error> {
+    load_settings_from(&settings_path())
+}
+
+fn load_settings_from(path: &Path) -> Result<AppSettings, std::io::Error> {
+    let mut file = File::open(path)?;
+    let mut buffer = String::new();
+    file.read_to_string(&mut buffer)?;
+
+    let mut settings: Value = serde_json::from_str(&buffer)?;
+    migrate_settings(&mut settings);
+    let mut settings: AppSettings = serde_json::from_value(settings)?;
+    // 保存时写入程序支持的版本
+    settings.version = crate::SETTINGS_FILE_VERSION;
+
+    info!("加载设置: {}", path.display());
+    Ok(settings)
+}
+
+/// 读取设置，无法读取时使用默认设置
+pub fn load_settings_or_default() -> AppSettings {
+    load_settings_or_default_from(&settings_path())
+}
+
+fn load_settings_or_default_from(path: &Path) -> AppSettings {
+    match load_settings_from(path) {
+        Ok(settings) => settings,
+        // 第一次运行
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            debug!("设置文件不存在，将使用默认设置");
+            AppSettings::default()
+        }
+        Err(e) => {
+            error!("无法读取设置，将使用默认设置: {}", e);
+            keep_broken_settings(path);
+            AppSettings::default()
+        }
+    }
+}
+
+/// 将无法读取的设置文件改名保留，之后保存设置时不会覆盖它
+fn keep_broken_settings(path: &Path) {
+    let mut broken = path.as_os_str().to_owned();
+    broken.push(format!(".broken-{}", Local::now().format("%Y%m%d-%H%M%S")));
+    let broken = PathBuf::from(broken);
+
+    match std::fs::rename(path, &broken) {
+        Ok(_) => warn!("无法读取的设置已保留为 {}", broken.display()),
+        Err(e) => error!("无法保留设置文件 {}: {}", path.display(), e),
+    }
+}
+
+pub fn save_settings(settings: &AppSettings) -> Result<(), std::io::Error> {
+    let path = settings_path();
+    let serialized = serde_json::to_string_pretty(settings)?;
+    write_atomic(&path, serialized.as_bytes())?;
+    info!("保存设置: {}", path.display());
+    Ok(())
+}
+
+
+impl MyApp {
+    pub fn save_settings(&self) {
+        match save_settings(&self.settings) {
+            Ok(_) => debug!("设置保存成功"),
+            Err(e) => error!("设置保存失败: {}", e),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("baroboard-settings-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn loads_settings_of_any_version() {
+        let dir = temp_dir();
+        let path = dir.join(crate::SETTINGS_FILE_NAME);
+
+        // (设置文件的内容, 读取到的共享目录)
+        let cases = [
+            // 没有版本号，缺少的设置使用默认值
+            (r#"{"catalogs": ["/srv/links.json"]}"#, vec!["/srv/links.json"]),
+            (r#"{"version": 1, "catalogs": []}"#, vec![]),
+            // 更新的版本中无法识别的设置被忽略
+            (r#"{"version": 99, "catalogs": ["a"], "unknown": true}"#, vec!["a"]),
+        ];
+        for (contents, catalogs) in cases {
+            std::fs::write(&path, contents).unwrap();
+            let settings = load_settings_from(&path).unwrap();
+            assert_eq!(settings.catalogs, catalogs, "{}", contents);
+            assert_eq!(settings.version, crate::SETTINGS_FILE_VERSION);
+            assert_eq!(settings.launch, LaunchSettings::default());
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn keeps_broken_settings() {
+        let dir = temp_dir();
+        let path = dir.join(crate::SETTINGS_FILE_NAME);
+
+        // 第一次运行时不会创建文件
+        assert_eq!(load_settings_or_default_from(&path), AppSettings::default());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+        for contents in ["{", r#"{"catalogs": "不是列表"}"#] {
+            std::fs::write(&path, contents).unwrap();
+            assert_eq!(load_settings_or_default_from(&path), AppSettings::default());
+            assert!(!path.exists(), "{}", contents);
+
+            // 原来的内容被改名保留
+            let broken: Vec<PathBuf> = std::fs::read_dir(&dir).unwrap()
+                .map(|entry| entry.unwrap().path())
+                .collect();
+            assert_eq!(broken.len(), 1);
+            assert!(broken[0].file_name().unwrap().to_string_lossy().starts_with("settings.json.broken-"));
+            assert_eq!(std::fs::read_to_string(&broken[0]).unwrap(), contents);
+            std::fs::remove_file(&broken[0]).unwrap();
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
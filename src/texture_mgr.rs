@@ -9,13 +9,14 @@ impl my_structs::MyApp {
         
         let icon = get_icon_from_exe(&path)?;
         
-        std::fs::create_dir_all(format!("{}/cache/exe_icon", crate::CONFIG_SAVE_PATH))?;
+        let icon_dir = crate::paths::cache_dir().join("exe_icon");
+        std::fs::create_dir_all(&icon_dir)?;
 
-        let icon_path = format!("{}/cache/exe_icon/{:x}.png", crate::CONFIG_SAVE_PATH, md5::compute(path.as_bytes()));
-        if !std::path::Path::new(&icon_path).exists() {
-            std::fs::write(icon_path.clone(), icon)?;
+        let icon_path = icon_dir.join(format!("{:x}.png", md5::compute(path.as_bytes())));
+        if !icon_path.exists() {
+            std::fs::write(&icon_path, icon)?;
         }
 
-        Ok(icon_path)
+        Ok(icon_path.to_string_lossy().to_string())
     }
 }
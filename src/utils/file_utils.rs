@@ -0,0 +1,32 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+
+/// 与目标文件同目录的临时文件
+fn temp_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".tmp");
+    path.with_file_name(file_name)
+}
+
+/// 原子地写入文件
+///
+/// 内容会先写入同目录下的临时文件并同步到磁盘，再重命名覆盖目标文件。
+/// 写入中途失败时，原文件保持不变
+pub fn write_atomic(path: impl AsRef<Path>, contents: &[u8]) -> Result<(), std::io::Error> {
+    let path = path.as_ref();
+    let temp = temp_path(path);
+
+    let result = (|| {
+        let mut file = File::create(&temp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        std::fs::rename(&temp, path)
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
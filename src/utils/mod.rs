@@ -1,2 +1,3 @@
 pub mod windows_utils;
+pub mod file_utils;
 
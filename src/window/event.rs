@@ -11,9 +11,15 @@ pub enum UserEvent {
     LeftClickTrayIcon,
     RightClickTrayIcon,
     ChangeDoubleAlt,
+    HotkeyChanged,
+    // 从托盘菜单切换工作区，None 表示默认工作区
+    SwitchProfile(Option<String>),
+    // 工作区被新建、重命名、删除或切换，需要重建托盘菜单
+    ProfilesChanged,
 
     // 文件相关
     FileHovered(String),
     FileHoverCancelled,
     FileDropped(String),
+    ConfigFileChanged,
 }
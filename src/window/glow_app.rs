@@ -3,11 +3,12 @@ use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use log::debug;
 
+use crate::settings::HotkeySettings;
 use crate::window;
 
 
 pub struct GlowApp {
-    call_by_double_alt: Arc<Mutex<bool>>,
+    hotkey: Arc<Mutex<HotkeySettings>>,
     tray_icon: trayicon::TrayIcon<UserEvent>,
     proxy: winit::event_loop::EventLoopProxy<UserEvent>,
     gl_window: Option<GlutinWindowContext>,
@@ -24,14 +25,14 @@ pub struct GlowApp {
 
 impl GlowApp {
     pub fn new(
-        call_by_double_alt: Arc<Mutex<bool>>,
+        hotkey: Arc<Mutex<HotkeySettings>>,
         winit_window_builder: winit::window::WindowAttributes,
         tray_icon: trayicon::TrayIcon<UserEvent>,
         proxy: winit::event_loop::EventLoopProxy<UserEvent>,
         set_up: Box<dyn Fn(&egui::Context) -> Box<dyn window::App> + Send + Sync + 'static>,
     ) -> Self {
         Self {
-            call_by_double_alt,
+            hotkey,
             tray_icon,
             proxy,
             gl_window: None,
@@ -281,10 +282,38 @@ impl winit::application::ApplicationHandler<UserEvent> for GlowApp {
             }
             UserEvent::ChangeDoubleAlt => {
                 // self.is_checked = is_checked;
-                let mut call_by_double_alt = self.call_by_double_alt.lock().unwrap();
-                *call_by_double_alt = !*call_by_double_alt;
-                self.tray_icon.set_menu_item_checkable(UserEvent::ChangeDoubleAlt, *call_by_double_alt).unwrap();
-                debug!("是否双击呼出: {}", *call_by_double_alt);
+                let double_tap = {
+                    let mut hotkey = self.hotkey.lock().unwrap();
+                    hotkey.double_tap = !hotkey.double_tap;
+                    hotkey.double_tap
+                };
+                self.tray_icon.set_menu_item_checkable(UserEvent::ChangeDoubleAlt, double_tap).unwrap();
+                debug!("是否双击呼出: {}", double_tap);
+                // 保存到设置中
+                if let Some(update_ui) = self.update_ui.as_mut() {
+                    update_ui.on_hotkey_changed();
+                }
+            }
+            UserEvent::HotkeyChanged => {
+                // 设置窗口修改了快捷键，同步托盘菜单中的开关
+                let double_tap = self.hotkey.lock().unwrap().double_tap;
+                self.tray_icon.set_menu_item_checkable(UserEvent::ChangeDoubleAlt, double_tap).unwrap();
+            }
+            UserEvent::SwitchProfile(profile) => {
+                if let Some(update_ui) = self.update_ui.as_mut() {
+                    update_ui.on_switch_profile(profile);
+                }
+                if !self.window_hidden {
+                    if let Some(ref gl_window) = self.gl_window {
+                        gl_window.window().request_redraw();
+                    }
+                }
+            }
+            UserEvent::ProfilesChanged => {
+                let double_tap = self.hotkey.lock().unwrap().double_tap;
+                if let Err(e) = self.tray_icon.set_menu(&crate::commands::tray_menu(double_tap)) {
+                    debug!("重建托盘菜单失败: {:?}", e);
+                }
             }
 
             // 文件相关
@@ -303,6 +332,16 @@ impl winit::application::ApplicationHandler<UserEvent> for GlowApp {
                     update_ui.on_file_dropped(path);
                 }
             }
+            UserEvent::ConfigFileChanged => {
+                if let Some(update_ui) = self.update_ui.as_mut() {
+                    update_ui.on_config_file_changed();
+                }
+                if !self.window_hidden {
+                    if let Some(ref gl_window) = self.gl_window {
+                        gl_window.window().request_redraw();
+                    }
+                }
+            }
 
         }
     }
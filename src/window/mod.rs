@@ -20,6 +20,15 @@ pub trait App {
 
     // 文件释放
     fn on_file_dropped(&mut self, path: String);
+
+    // 配置文件被修改
+    fn on_config_file_changed(&mut self);
+
+    // 托盘菜单修改了呼出快捷键
+    fn on_hotkey_changed(&mut self);
+
+    // 托盘菜单切换了工作区
+    fn on_switch_profile(&mut self, profile: Option<String>);
 }
 
 